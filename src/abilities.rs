@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level,
+    player::{Player, Remote},
+    save::SaveData,
+    state::InGame,
+};
+
+/// Moves the player has to find before they can use them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub(crate) enum Ability {
+    Hook,
+    Dash,
+    WallJump,
+    DoubleJump,
+}
+
+impl Ability {
+    fn color(self) -> Color {
+        match self {
+            Ability::Hook => Color::hsl(30., 0.5, 0.6),
+            Ability::Dash => Color::hsl(190., 0.9, 0.6),
+            Ability::WallJump => Color::hsl(130., 0.7, 0.55),
+            Ability::DoubleJump => Color::hsl(280., 0.7, 0.65),
+        }
+    }
+}
+
+/// What a new save starts out with, the first levels are built around the
+/// hook.
+pub(crate) fn starting() -> HashSet<Ability> {
+    HashSet::from([Ability::Hook])
+}
+
+/// The abilities the player has, checked before acting on their input.
+#[derive(Component, Clone)]
+pub(crate) struct Abilities(pub(crate) HashSet<Ability>);
+
+impl Abilities {
+    pub(crate) fn has(&self, ability: Ability) -> bool {
+        self.0.contains(&ability)
+    }
+}
+
+/// Grants an ability for good when touched.
+#[derive(Component)]
+pub(crate) struct AbilityPickup(Ability);
+
+pub(crate) fn make_pickup(
+    placed: &level::AbilityPickup,
+) -> (
+    SpriteBundle,
+    AbilityPickup,
+    Collider,
+    Sensor,
+    StateScoped<InGame>,
+) {
+    let r = 25.;
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: placed.ability.color(),
+                custom_size: Some(Vec2::splat(r * 2.)),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, 0.)
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            ..default()
+        },
+        AbilityPickup(placed.ability),
+        Collider::circle(r),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+/// Unlocks the ability on the player and in the save straight away, so it's
+/// kept even if the level isn't finished.
+pub(crate) fn collect(
+    mut started: EventReader<CollisionStarted>,
+    mut players: Query<(&mut Abilities, Has<Remote>), With<Player>>,
+    pickups: Query<&AbilityPickup>,
+    mut save: ResMut<SaveData>,
+    mut commands: Commands,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (pickup, other) = if pickups.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok(AbilityPickup(ability)) = pickups.get(pickup) else {
+            continue;
+        };
+        let Ok((mut abilities, remote)) = players.get_mut(other) else {
+            continue;
+        };
+
+        commands.entity(pickup).despawn();
+        abilities.0.insert(*ability);
+        // The other player online keeps what they found in their own save
+        if !remote && save.abilities.insert(*ability) {
+            save.write();
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::{Level, Levels, Medal},
+    locale::Locale,
+    pickup::Coins,
+    player::{HookAttached, Player, Remote},
+    replay::Replay,
+    run::Run,
+    save::SaveData,
+    state::{AppState, GameplaySet},
+};
+
+/// Real seconds a toast stays on screen
+const TOAST_SECONDS: f32 = 4.;
+/// Coins to collect over every completed level for [`Achievement::Coins`]
+const COIN_GOAL: u32 = 100;
+
+/// Goals to reach across all the levels, kept in the save file. Unlocks pop
+/// up a toast in the corner.
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts>()
+            .add_systems(OnEnter(AppState::LevelComplete), complete)
+            .add_systems(Update, first_hook.in_set(GameplaySet))
+            .add_systems(Update, show_toasts);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub(crate) enum Achievement {
+    /// Attach the hook to anything
+    FirstHook,
+    /// Complete a level without dying
+    Deathless,
+    /// Collect [`COIN_GOAL`] coins in completed levels
+    Coins,
+    /// Finish a level fast enough for gold
+    GoldMedal,
+}
+
+impl Achievement {
+    /// Id of the achievement's name in the locale files, its description is
+    /// under the same id with `-description` on the end.
+    fn message(self) -> &'static str {
+        match self {
+            Achievement::FirstHook => "achievement-first-hook",
+            Achievement::Deathless => "achievement-deathless",
+            Achievement::Coins => "achievement-coins",
+            Achievement::GoldMedal => "achievement-gold-medal",
+        }
+    }
+}
+
+/// Unlocks waiting to be shown, the first one with its time left.
+#[derive(Resource, Default)]
+struct Toasts {
+    queue: VecDeque<Achievement>,
+    shown: f32,
+}
+
+/// Mark `achievement` unlocked and queue its toast, returns whether it's new.
+/// The caller writes the save.
+fn unlock(save: &mut SaveData, toasts: &mut Toasts, achievement: Achievement) -> bool {
+    if !save.achievements.insert(achievement) {
+        return false;
+    }
+    toasts.queue.push_back(achievement);
+    true
+}
+
+fn first_hook(
+    mut attached: EventReader<HookAttached>,
+    players: Query<(), (With<Player>, Without<Remote>)>,
+    mut save: ResMut<SaveData>,
+    mut toasts: ResMut<Toasts>,
+    replay: Res<Replay>,
+) {
+    // Not for the other player's hook online
+    let hooked = attached
+        .read()
+        .filter(|ev| players.contains(ev.player))
+        .count();
+    // Replays hook exactly where the recorded run did
+    if hooked == 0 || replay.is_playing() {
+        return;
+    }
+    if unlock(&mut save, &mut toasts, Achievement::FirstHook) {
+        save.write();
+    }
+}
+
+/// Checks the finished run and writes the save, daily challenges don't
+/// go through [`record_completion`](crate::save::record_completion) to do it.
+fn complete(
+    mut save: ResMut<SaveData>,
+    mut toasts: ResMut<Toasts>,
+    levels: Res<Levels>,
+    assets: Res<Assets<Level>>,
+    run: Res<Run>,
+    coins: Res<Coins>,
+    replay: Res<Replay>,
+) {
+    // Watching a replay finishes the level too
+    if replay.is_playing() {
+        return;
+    }
+
+    if run.deaths == 0 {
+        unlock(&mut save, &mut toasts, Achievement::Deathless);
+    }
+
+    save.coins_collected += coins.0;
+    if save.coins_collected >= COIN_GOAL {
+        unlock(&mut save, &mut toasts, Achievement::Coins);
+    }
+
+    let medal = levels
+        .current(&assets)
+        .and_then(|level| level.medals.award(run.time.elapsed_secs()));
+    if medal == Some(Medal::Gold) {
+        unlock(&mut save, &mut toasts, Achievement::GoldMedal);
+    }
+    save.write();
+}
+
+fn show_toasts(
+    mut contexts: EguiContexts,
+    mut toasts: ResMut<Toasts>,
+    time: Res<Time<Real>>,
+    locale: Res<Locale>,
+) {
+    let Some(&achievement) = toasts.queue.front() else {
+        return;
+    };
+
+    toasts.shown += time.delta_seconds();
+    if toasts.shown > TOAST_SECONDS {
+        toasts.queue.pop_front();
+        toasts.shown = 0.;
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("achievement toast"))
+        .anchor(egui::Align2::RIGHT_TOP, [-10., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(egui::RichText::new(locale.get("achievement-unlocked")).small());
+                ui.label(egui::RichText::new(locale.get(achievement.message())).strong());
+                ui.label(locale.get(&format!("{}-description", achievement.message())));
+            });
+        });
+}
@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use crate::state::InGame;
+
+const COLOR: Color = Color::hsl(190., 0.9, 0.6);
+/// Default angle, in degrees, within which the hook snaps to an anchor
+pub(crate) const DEFAULT_AIM_ASSIST: f32 = 10.;
+
+/// A point the hook snaps to when aimed close enough to it.
+#[derive(Component, Default)]
+pub(crate) struct HookAnchor;
+
+/// How far off the aim can be, in radians, for the hook to still snap to an
+/// anchor. Set from the current level.
+#[derive(Resource)]
+pub(crate) struct AimAssist(pub(crate) f32);
+
+impl Default for AimAssist {
+    fn default() -> Self {
+        Self(DEFAULT_AIM_ASSIST.to_radians())
+    }
+}
+
+pub(crate) fn make_anchor(x: f32, y: f32) -> (SpriteBundle, HookAnchor, StateScoped<InGame>) {
+    let size = 24.;
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: COLOR,
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            // Diamond shaped so it doesn't look like a coin
+            transform: Transform::from_xyz(x, y, -0.5)
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            ..default()
+        },
+        HookAnchor,
+        StateScoped(InGame),
+    )
+}
@@ -0,0 +1,245 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::Deserialize;
+
+use crate::{
+    level::RonLoaderError,
+    skins::{self, SkinSheets},
+};
+
+const MANIFEST: &str = "sprites/atlas.ron";
+
+/// Draws sprites from one packed image instead of an image each, so they
+/// batch together. Entities ask for a region by name with [`AtlasSprite`],
+/// and play through an animation's frames with [`Animated`].
+pub struct AtlasPlugin;
+
+impl Plugin for AtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SpriteAtlas>()
+            .init_asset_loader::<AtlasLoader>()
+            .init_resource::<Atlas>()
+            .init_resource::<SkinSheets>()
+            .add_systems(Startup, (load_atlas, skins::load_sheets))
+            .add_systems(PostUpdate, (use_atlas, animate).chain());
+    }
+}
+
+/// Where each sprite is in the packed image, as written in the manifest.
+#[derive(Deserialize)]
+struct Manifest {
+    /// From the assets folder
+    image: String,
+    size: UVec2,
+    sprites: HashMap<String, URect>,
+    #[serde(default)]
+    animations: HashMap<String, ManifestClip>,
+}
+
+#[derive(Deserialize)]
+struct ManifestClip {
+    /// Names of sprites in the manifest
+    frames: Vec<String>,
+    fps: f32,
+}
+
+/// Frames of an animation, as indices into the layout.
+struct Clip {
+    frames: Vec<usize>,
+    fps: f32,
+}
+
+/// A packed image and the named regions and animations in it.
+#[derive(Asset, TypePath)]
+pub(crate) struct SpriteAtlas {
+    image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    indices: HashMap<String, usize>,
+    clips: HashMap<String, Clip>,
+}
+
+impl SpriteAtlas {
+    /// Index of the sprite `name`, or the first frame if it's an animation.
+    fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied().or_else(|| {
+            self.clips
+                .get(name)
+                .and_then(|clip| clip.frames.first().copied())
+        })
+    }
+
+    /// The image and the part of it, from 0.0 to 1.0, showing the sprite
+    /// `name`. For drawing a sprite in egui.
+    pub(crate) fn uv(
+        &self,
+        name: &str,
+        layouts: &Assets<TextureAtlasLayout>,
+    ) -> Option<(&Handle<Image>, Rect)> {
+        let layout = layouts.get(&self.layout)?;
+        let rect = layout.textures.get(self.index(name)?)?.as_rect();
+        let size = layout.size.as_vec2();
+        Some((
+            &self.image,
+            Rect::from_corners(rect.min / size, rect.max / size),
+        ))
+    }
+}
+
+/// Reads the manifest and loads its image along with it, so the atlas only
+/// counts as loaded once the image is too.
+#[derive(Default)]
+struct AtlasLoader;
+
+impl AssetLoader for AtlasLoader {
+    type Asset = SpriteAtlas;
+    type Settings = ();
+    type Error = RonLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<SpriteAtlas, RonLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let manifest: Manifest = ron::de::from_bytes(&bytes)?;
+
+        let mut layout = TextureAtlasLayout::new_empty(manifest.size);
+        let indices: HashMap<_, _> = manifest
+            .sprites
+            .into_iter()
+            .map(|(name, rect)| (name, layout.add_texture(rect)))
+            .collect();
+        let clips = manifest
+            .animations
+            .into_iter()
+            .map(|(name, clip)| {
+                let frames = clip
+                    .frames
+                    .iter()
+                    .filter_map(|frame| {
+                        let index = indices.get(frame).copied();
+                        if index.is_none() {
+                            warn!("Animation {name} has a frame {frame} that isn't a sprite");
+                        }
+                        index
+                    })
+                    .collect();
+                let clip = Clip {
+                    frames,
+                    fps: clip.fps,
+                };
+                (name, clip)
+            })
+            .collect();
+
+        Ok(SpriteAtlas {
+            image: load_context.load(manifest.image),
+            layout: load_context.add_labeled_asset("layout".into(), layout),
+            indices,
+            clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// The atlas sprites are drawn from.
+#[derive(Resource, Default)]
+pub(crate) struct Atlas(pub(crate) Handle<SpriteAtlas>);
+
+/// Draws the entity's sprite with the region of the atlas named here, tinted
+/// by the sprite's color. Shows plain color until the atlas has loaded. Naming
+/// an animation shows its first frame.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct AtlasSprite(pub(crate) &'static str);
+
+/// Draws the entity's [`AtlasSprite`] from this atlas instead of the usual
+/// one, like the sheet of a player skin with the same names in it.
+#[derive(Component, Clone)]
+pub(crate) struct Sheet(pub(crate) Handle<SpriteAtlas>);
+
+/// Plays the animation named by the entity's [`AtlasSprite`] on a loop.
+#[derive(Component, Default)]
+pub(crate) struct Animated {
+    /// Seconds since it started
+    elapsed: f32,
+}
+
+fn load_atlas(mut atlas: ResMut<Atlas>, asset_server: Res<AssetServer>) {
+    atlas.0 = asset_server.load(MANIFEST);
+}
+
+/// Points new atlas sprites at their region, and every one of them again
+/// when an atlas is loaded or changes.
+fn use_atlas(
+    mut events: EventReader<AssetEvent<SpriteAtlas>>,
+    atlas: Res<Atlas>,
+    atlases: Res<Assets<SpriteAtlas>>,
+    sprites: Query<(Entity, Ref<AtlasSprite>, Option<Ref<Sheet>>)>,
+    mut commands: Commands,
+) {
+    let reloaded = events.read().any(|event| {
+        matches!(
+            event,
+            AssetEvent::LoadedWithDependencies { .. } | AssetEvent::Modified { .. }
+        )
+    });
+
+    for (entity, sprite, sheet) in &sprites {
+        let added = sprite.is_added() || sheet.as_ref().is_some_and(Ref::is_changed);
+        if !reloaded && !added {
+            continue;
+        }
+        let handle = sheet.as_ref().map_or(&atlas.0, |sheet| &sheet.0);
+        let Some(loaded) = atlases.get(handle) else {
+            continue;
+        };
+        let Some(index) = loaded.index(sprite.0) else {
+            warn!("No sprite {} in the atlas", sprite.0);
+            continue;
+        };
+        commands.entity(entity).insert((
+            loaded.image.clone(),
+            TextureAtlas {
+                layout: loaded.layout.clone(),
+                index,
+            },
+        ));
+    }
+}
+
+/// Shows the frame each animation is on.
+fn animate(
+    atlas: Res<Atlas>,
+    atlases: Res<Assets<SpriteAtlas>>,
+    mut sprites: Query<(
+        &AtlasSprite,
+        Option<&Sheet>,
+        &mut Animated,
+        &mut TextureAtlas,
+    )>,
+    time: Res<Time>,
+) {
+    for (sprite, sheet, mut animated, mut texture) in &mut sprites {
+        animated.elapsed += time.delta_seconds();
+        let handle = sheet.map_or(&atlas.0, |sheet| &sheet.0);
+        let Some(clip) = atlases
+            .get(handle)
+            .and_then(|loaded| loaded.clips.get(sprite.0))
+            .filter(|clip| !clip.frames.is_empty())
+        else {
+            continue;
+        };
+        let frame = (animated.elapsed * clip.fps) as usize % clip.frames.len();
+        if texture.index != clip.frames[frame] {
+            texture.index = clip.frames[frame];
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use avian2d::prelude::*;
+use bevy::{audio::Volume, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    deterministic::GameRng,
+    elevator::ElevatorStarted,
+    enemy::EnemyDefeated,
+    explosion::Exploded,
+    pendulum::Swung,
+    pickup::PickedUp,
+    player::{HookAttached, HookFired, HookReleased, Jumped, Landed, Player, Remote, SlideStarted},
+    projectile::ProjectileHit,
+    surface::SurfaceMaterial,
+};
+
+/// Landings slower than this are just bumps and stay quiet
+const MIN_LAND_SPEED: f32 = 150.0;
+/// Distance traveled on the ground between footsteps
+const STRIDE: f32 = 140.0;
+
+/// Plays sound effects in response to gameplay events.
+pub struct SfxPlugin;
+
+impl Plugin for SfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VolumeSettings>()
+            .init_resource::<SfxRng>()
+            .add_systems(Startup, load_sounds)
+            .add_systems(
+                Update,
+                (player_sfx, hook_sfx, world_sfx, platform_sfx, footsteps),
+            );
+    }
+}
+
+/// Randomness for how sounds play, kept apart from [`GameRng`] so playing
+/// them never changes what the simulation rolls next.
+#[derive(Resource, Default)]
+struct SfxRng(GameRng);
+
+/// Groups of sounds that have their own volume slider.
+#[derive(Clone, Copy)]
+pub(crate) enum SoundCategory {
+    Player,
+    Hook,
+    World,
+    Music,
+}
+
+/// Volume levels, 0.0 is silent and 1.0 is full volume.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct VolumeSettings {
+    pub(crate) master: f32,
+    pub(crate) player: f32,
+    pub(crate) hook: f32,
+    pub(crate) world: f32,
+    pub(crate) music: f32,
+}
+
+impl Default for VolumeSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            player: 1.0,
+            hook: 1.0,
+            world: 1.0,
+            music: 0.6,
+        }
+    }
+}
+
+impl VolumeSettings {
+    pub(crate) fn get(&self, category: SoundCategory) -> f32 {
+        self.master
+            * match category {
+                SoundCategory::Player => self.player,
+                SoundCategory::Hook => self.hook,
+                SoundCategory::World => self.world,
+                SoundCategory::Music => self.music,
+            }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct Sounds {
+    jump: Handle<AudioSource>,
+    land: Handle<AudioSource>,
+    slide: Handle<AudioSource>,
+    hook_fire: Handle<AudioSource>,
+    hook_attach: Handle<AudioSource>,
+    hook_release: Handle<AudioSource>,
+    pickup: Handle<AudioSource>,
+    explosion: Handle<AudioSource>,
+    impact: Handle<AudioSource>,
+    stomp: Handle<AudioSource>,
+    elevator: Handle<AudioSource>,
+    swing: Handle<AudioSource>,
+    footstep_stone: Handle<AudioSource>,
+    footstep_metal: Handle<AudioSource>,
+    footstep_ice: Handle<AudioSource>,
+}
+
+impl Sounds {
+    pub(crate) fn all(&self) -> impl Iterator<Item = &Handle<AudioSource>> {
+        [
+            &self.jump,
+            &self.land,
+            &self.slide,
+            &self.hook_fire,
+            &self.hook_attach,
+            &self.hook_release,
+            &self.pickup,
+            &self.explosion,
+            &self.impact,
+            &self.stomp,
+            &self.elevator,
+            &self.swing,
+            &self.footstep_stone,
+            &self.footstep_metal,
+            &self.footstep_ice,
+        ]
+        .into_iter()
+    }
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Sounds {
+        jump: asset_server.load("sounds/jump.wav"),
+        land: asset_server.load("sounds/land.wav"),
+        slide: asset_server.load("sounds/slide.wav"),
+        hook_fire: asset_server.load("sounds/hook_fire.wav"),
+        hook_attach: asset_server.load("sounds/hook_attach.wav"),
+        hook_release: asset_server.load("sounds/hook_release.wav"),
+        pickup: asset_server.load("sounds/pickup.wav"),
+        explosion: asset_server.load("sounds/explosion.wav"),
+        impact: asset_server.load("sounds/impact.wav"),
+        stomp: asset_server.load("sounds/stomp.wav"),
+        elevator: asset_server.load("sounds/elevator.wav"),
+        swing: asset_server.load("sounds/swing.wav"),
+        footstep_stone: asset_server.load("sounds/footstep_stone.wav"),
+        footstep_metal: asset_server.load("sounds/footstep_metal.wav"),
+        footstep_ice: asset_server.load("sounds/footstep_ice.wav"),
+    });
+}
+
+fn play(commands: &mut Commands, sound: &Handle<AudioSource>, volume: f32) {
+    commands.spawn(AudioBundle {
+        source: sound.clone(),
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+    });
+}
+
+fn spatial(sound: &Handle<AudioSource>, volume: f32) -> AudioBundle {
+    AudioBundle {
+        source: sound.clone(),
+        settings: PlaybackSettings::DESPAWN
+            .with_volume(Volume::new(volume))
+            .with_spatial(true),
+    }
+}
+
+/// Play a sound at `position` in world space, panned and attenuated relative
+/// to the listener on the camera.
+pub(crate) fn play_at(
+    commands: &mut Commands,
+    sound: &Handle<AudioSource>,
+    volume: f32,
+    position: Vec2,
+) {
+    commands.spawn((
+        spatial(sound, volume),
+        SpatialBundle::from_transform(Transform::from_translation(position.extend(0.))),
+    ));
+}
+
+/// Like [`play_at`], but from `emitter` and following it while it moves.
+/// Nothing plays if `emitter` is already gone.
+pub(crate) fn play_on(
+    commands: &mut Commands,
+    sound: &Handle<AudioSource>,
+    volume: f32,
+    emitter: Entity,
+) {
+    if let Some(mut emitter) = commands.get_entity(emitter) {
+        emitter.with_children(|parent| {
+            parent.spawn((spatial(sound, volume), SpatialBundle::default()));
+        });
+    }
+}
+
+fn player_sfx(
+    mut jumped: EventReader<Jumped>,
+    mut landed: EventReader<Landed>,
+    mut slide_started: EventReader<SlideStarted>,
+    sounds: Res<Sounds>,
+    volume: Res<VolumeSettings>,
+    mut commands: Commands,
+) {
+    let volume = volume.get(SoundCategory::Player);
+
+    for _ in jumped.read() {
+        play(&mut commands, &sounds.jump, volume);
+    }
+    for ev in landed.read() {
+        if ev.impact_speed > MIN_LAND_SPEED {
+            let loudness = (ev.impact_speed / 1000.0).clamp(0.2, 1.0);
+            play(&mut commands, &sounds.land, volume * loudness);
+        }
+    }
+    for _ in slide_started.read() {
+        play(&mut commands, &sounds.slide, volume);
+    }
+}
+
+fn hook_sfx(
+    mut fired: EventReader<HookFired>,
+    mut attached: EventReader<HookAttached>,
+    mut released: EventReader<HookReleased>,
+    sounds: Res<Sounds>,
+    volume: Res<VolumeSettings>,
+    mut commands: Commands,
+) {
+    let volume = volume.get(SoundCategory::Hook);
+
+    for _ in fired.read() {
+        play(&mut commands, &sounds.hook_fire, volume);
+    }
+    for ev in attached.read() {
+        play_at(&mut commands, &sounds.hook_attach, volume, ev.position);
+    }
+    for _ in released.read() {
+        play(&mut commands, &sounds.hook_release, volume);
+    }
+}
+
+fn world_sfx(
+    mut picked_up: EventReader<PickedUp>,
+    mut exploded: EventReader<Exploded>,
+    mut hits: EventReader<ProjectileHit>,
+    mut defeated: EventReader<EnemyDefeated>,
+    sounds: Res<Sounds>,
+    volume: Res<VolumeSettings>,
+    mut commands: Commands,
+) {
+    let volume = volume.get(SoundCategory::World);
+
+    for ev in picked_up.read() {
+        play_at(&mut commands, &sounds.pickup, volume, ev.position);
+    }
+    for ev in exploded.read() {
+        play_at(&mut commands, &sounds.explosion, volume, ev.position);
+    }
+    for ev in hits.read() {
+        play_at(&mut commands, &sounds.impact, volume, ev.position);
+    }
+    for ev in defeated.read() {
+        play_at(&mut commands, &sounds.stomp, volume, ev.position);
+    }
+}
+
+fn platform_sfx(
+    mut elevators: EventReader<ElevatorStarted>,
+    mut swung: EventReader<Swung>,
+    sounds: Res<Sounds>,
+    volume: Res<VolumeSettings>,
+    mut commands: Commands,
+) {
+    let volume = volume.get(SoundCategory::World);
+
+    for ev in elevators.read() {
+        play_on(&mut commands, &sounds.elevator, volume, ev.elevator);
+    }
+    for ev in swung.read() {
+        play_on(&mut commands, &sounds.swing, volume, ev.platform);
+    }
+}
+
+fn footsteps(
+    players: Query<(&Player, &LinearVelocity), Without<Remote>>,
+    surfaces: Query<&SurfaceMaterial>,
+    sounds: Res<Sounds>,
+    volume: Res<VolumeSettings>,
+    time: Res<Time>,
+    mut rng: ResMut<SfxRng>,
+    mut distance: Local<f32>,
+    mut commands: Commands,
+) {
+    let (player, velocity) = match players.iter().next() {
+        Some(x) => x,
+        None => return,
+    };
+
+    if !player.is_grounded || player.is_sliding {
+        *distance = 0.0;
+        return;
+    }
+
+    *distance += velocity.x.abs() * time.delta_seconds();
+    if *distance < STRIDE {
+        return;
+    }
+    *distance = 0.0;
+
+    let material = player
+        .ground
+        .and_then(|ground| surfaces.get(ground).ok())
+        .copied()
+        .unwrap_or_default();
+
+    let sound = match material {
+        SurfaceMaterial::Stone => &sounds.footstep_stone,
+        SurfaceMaterial::Metal => &sounds.footstep_metal,
+        SurfaceMaterial::Ice => &sounds.footstep_ice,
+    };
+
+    // Slightly different every step so a run doesn't sound mechanical
+    commands.spawn(AudioBundle {
+        source: sound.clone(),
+        settings: PlaybackSettings::DESPAWN
+            .with_volume(Volume::new(volume.get(SoundCategory::Player) * 0.5))
+            .with_speed(rng.0.range(0.9..1.1)),
+    });
+}
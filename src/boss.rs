@@ -0,0 +1,406 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{de::Error, Deserialize, Deserializer};
+
+use crate::{
+    camera::CameraLock,
+    enemy::{Enemy, EnemyDefeated},
+    health::Health,
+    level,
+    player::Player,
+    pool::Pool,
+    projectile::{self, Projectile},
+    state::{AppState, GameplaySet, InGame},
+};
+
+const SIZE: f32 = 160.;
+/// How high above its resting spot the boss jumps before slamming down
+const SLAM_HEIGHT: f32 = 500.;
+const RISE_SPEED: f32 = 900.;
+const DROP_SPEED: f32 = 2200.;
+/// Horizontal speed while lining up a slam over the player
+const TRACK_SPEED: f32 = 600.;
+const SHOCKWAVE_SIZE: Vec2 = Vec2::new(40., 50.);
+const SHOCKWAVE_SPEED: f32 = 700.;
+const VOLLEY_SPEED: f32 = 800.;
+const BAR_WIDTH: f32 = 400.;
+
+/// Bosses: phases of scripted attacks inside a locked arena, beating one
+/// completes the level.
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), setup_health_bar)
+            .add_systems(
+                FixedUpdate,
+                (enter_arena, change_phase, act, defeat)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(Update, update_health_bar.run_if(in_state(InGame)));
+    }
+}
+
+/// One stage of a fight, cycling through its attacks with a pause after each.
+#[derive(Deserialize, Clone)]
+pub(crate) struct Phase {
+    /// Starts once the boss is down to this fraction of its health
+    pub(crate) health: f32,
+    /// Seconds between attacks
+    pub(crate) pause: f32,
+    pub(crate) attacks: Vec<Attack>,
+}
+
+/// A boss's phases, refusing a boss without any since it would have nothing
+/// to do.
+pub(crate) fn deserialize_phases<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Phase>, D::Error> {
+    let phases = Vec::<Phase>::deserialize(deserializer)?;
+    if phases.is_empty() {
+        return Err(D::Error::custom("a boss needs at least one phase"));
+    }
+    Ok(phases)
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub(crate) enum Attack {
+    /// Jump over the player and come down hard, sending shockwaves along the
+    /// floor in both directions
+    Slam,
+    /// Fire `count` projectiles fanned out over `spread` degrees
+    Volley { count: u32, spread: f32 },
+}
+
+#[derive(Clone, Copy)]
+enum BossState {
+    Waiting,
+    Rising,
+    Dropping,
+}
+
+#[derive(Component)]
+pub(crate) struct Boss {
+    phases: Vec<Phase>,
+    phase: usize,
+    next_attack: usize,
+    state: BossState,
+    timer: Timer,
+    /// Where it rests and lands, on the arena floor
+    home: Vec2,
+    arena: Rect,
+    /// Asleep until the player walks into the arena
+    active: bool,
+}
+
+impl Boss {
+    /// How far into the fight it is, from the first phase at a fraction above
+    /// 0 to the last at 1. `None` while asleep.
+    pub(crate) fn progress(&self) -> Option<f32> {
+        self.active
+            .then(|| (self.phase + 1) as f32 / self.phases.len().max(1) as f32)
+    }
+}
+
+/// Wakes up the boss when the player enters it.
+#[derive(Component)]
+struct ArenaTrigger(Entity);
+
+#[derive(Component)]
+struct HealthBar;
+
+#[derive(Component)]
+struct HealthBarFill;
+
+/// Spawn the boss placed in a level along with its arena.
+pub(crate) fn spawn(commands: &mut Commands, placed: &level::Boss) {
+    let pause = placed.phases.first().map_or(1., |phase| phase.pause);
+    let arena = Rect::from_center_size(
+        Vec2::new(placed.arena.x, placed.arena.y),
+        Vec2::new(placed.arena.w, placed.arena.h),
+    );
+
+    let boss = commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::hsl(280., 0.6, 0.4),
+                    custom_size: Some(Vec2::splat(SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(placed.x, placed.y, 0.),
+                ..default()
+            },
+            Boss {
+                phases: placed.phases.clone(),
+                phase: 0,
+                next_attack: 0,
+                state: BossState::Waiting,
+                timer: Timer::from_seconds(pause, TimerMode::Once),
+                home: Vec2::new(placed.x, placed.y),
+                arena,
+                active: false,
+            },
+            Enemy,
+            Health::new(placed.health),
+            RigidBody::Kinematic,
+            Collider::rectangle(SIZE, SIZE),
+            StateScoped(InGame),
+        ))
+        .id();
+
+    commands.spawn((
+        ArenaTrigger(boss),
+        TransformBundle::from_transform(Transform::from_translation(arena.center().extend(0.))),
+        Collider::rectangle(arena.width(), arena.height()),
+        Sensor,
+        StateScoped(InGame),
+    ));
+}
+
+fn enter_arena(
+    mut started: EventReader<CollisionStarted>,
+    triggers: Query<&ArenaTrigger>,
+    players: Query<(), With<Player>>,
+    mut bosses: Query<&mut Boss>,
+    mut lock: ResMut<CameraLock>,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (zone, other) = if triggers.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok(ArenaTrigger(boss)) = triggers.get(zone) else {
+            continue;
+        };
+        if !players.contains(other) {
+            continue;
+        }
+
+        if let Ok(mut boss) = bosses.get_mut(*boss) {
+            boss.active = true;
+            lock.0 = Some(boss.arena.center());
+        }
+    }
+}
+
+/// Move on to the next phase once enough health is lost.
+fn change_phase(mut bosses: Query<(&mut Boss, &Health), Changed<Health>>) {
+    for (mut boss, health) in &mut bosses {
+        let fraction = health.current as f32 / health.max as f32;
+        let phase = boss
+            .phases
+            .iter()
+            .rposition(|phase| fraction <= phase.health)
+            .unwrap_or(0);
+
+        if phase != boss.phase {
+            boss.phase = phase;
+            boss.next_attack = 0;
+        }
+    }
+}
+
+fn act(
+    mut bosses: Query<(Entity, &mut Boss, &mut Position, &mut LinearVelocity)>,
+    players: Query<&Position, (With<Player>, Without<Boss>)>,
+    time: Res<Time>,
+    mut shots: ResMut<Pool<Projectile>>,
+    mut commands: Commands,
+) {
+    for (entity, mut boss, mut position, mut velocity) in &mut bosses {
+        if !boss.active {
+            continue;
+        }
+        // Goes after whoever is closest
+        let Some(target) = players
+            .iter()
+            .map(|player| player.0)
+            .min_by(|a, b| a.distance(position.0).total_cmp(&b.distance(position.0)))
+        else {
+            return;
+        };
+        let pause = boss.phases[boss.phase].pause;
+
+        match boss.state {
+            BossState::Waiting => {
+                if !boss.timer.tick(time.delta()).just_finished() {
+                    continue;
+                }
+
+                let attacks = &boss.phases[boss.phase].attacks;
+                let Some(&attack) = attacks.get(boss.next_attack % attacks.len().max(1)) else {
+                    continue;
+                };
+                boss.next_attack += 1;
+
+                match attack {
+                    Attack::Slam => boss.state = BossState::Rising,
+                    Attack::Volley { count, spread } => {
+                        volley(
+                            &mut commands,
+                            &mut shots,
+                            entity,
+                            position.0,
+                            target,
+                            count,
+                            spread,
+                        );
+                        boss.timer = Timer::from_seconds(pause, TimerMode::Once);
+                    }
+                }
+            }
+            BossState::Rising => {
+                // Line up over the player without leaving the arena
+                let half = SIZE / 2.;
+                let over = target
+                    .x
+                    .clamp(boss.arena.min.x + half, boss.arena.max.x - half);
+                velocity.x = ((over - position.x) * 4.).clamp(-TRACK_SPEED, TRACK_SPEED);
+                velocity.y = RISE_SPEED;
+
+                if position.y >= boss.home.y + SLAM_HEIGHT {
+                    boss.state = BossState::Dropping;
+                }
+            }
+            BossState::Dropping => {
+                velocity.0 = Vec2::NEG_Y * DROP_SPEED;
+
+                if position.y <= boss.home.y {
+                    position.y = boss.home.y;
+                    velocity.0 = Vec2::ZERO;
+                    shockwaves(&mut commands, &mut shots, entity, position.0);
+                    boss.state = BossState::Waiting;
+                    boss.timer = Timer::from_seconds(pause, TimerMode::Once);
+                }
+            }
+        }
+    }
+}
+
+fn volley(
+    commands: &mut Commands,
+    shots: &mut Pool<Projectile>,
+    boss: Entity,
+    origin: Vec2,
+    target: Vec2,
+    count: u32,
+    spread: f32,
+) {
+    let Ok(aim) = Dir2::new(target - origin) else {
+        return;
+    };
+
+    for i in 0..count {
+        // Evenly fanned out, centered on the player
+        let t = if count > 1 {
+            i as f32 / (count - 1) as f32 - 0.5
+        } else {
+            0.
+        };
+        let dir = Rot2::degrees(spread * t) * aim;
+
+        shots.spawn(
+            commands,
+            projectile::make_projectile(boss, origin + dir * SIZE * 0.6, dir * VOLLEY_SPEED, 1),
+        );
+    }
+}
+
+fn shockwaves(commands: &mut Commands, shots: &mut Pool<Projectile>, boss: Entity, origin: Vec2) {
+    // Skimming just above the floor so they don't break on it
+    let y = origin.y - SIZE / 2. + SHOCKWAVE_SIZE.y / 2. + 2.;
+
+    for side in [-1., 1.] {
+        let x = origin.x + side * (SIZE + SHOCKWAVE_SIZE.x) / 2.;
+        let mut bundle =
+            projectile::make_projectile(boss, Vec2::new(x, y), Vec2::X * side * SHOCKWAVE_SPEED, 1);
+        bundle.0.sprite.custom_size = Some(SHOCKWAVE_SIZE);
+        bundle.3 = Collider::rectangle(SHOCKWAVE_SIZE.x, SHOCKWAVE_SIZE.y);
+        shots.spawn(commands, bundle);
+    }
+}
+
+fn defeat(
+    bosses: Query<(Entity, &Health, &Position), With<Boss>>,
+    mut defeated: EventWriter<EnemyDefeated>,
+    mut lock: ResMut<CameraLock>,
+    mut next: ResMut<NextState<AppState>>,
+    mut commands: Commands,
+) {
+    for (entity, health, position) in &bosses {
+        if health.current > 0 {
+            continue;
+        }
+
+        defeated.send(EnemyDefeated {
+            position: position.0,
+        });
+        commands.entity(entity).despawn_recursive();
+        lock.0 = None;
+        next.set(AppState::LevelComplete);
+    }
+}
+
+fn setup_health_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(30.),
+                    left: Val::Percent(50.),
+                    margin: UiRect::left(Val::Px(-BAR_WIDTH / 2.)),
+                    width: Val::Px(BAR_WIDTH),
+                    height: Val::Px(16.),
+                    ..default()
+                },
+                background_color: Color::srgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HealthBar,
+            StateScoped(InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    background_color: Color::hsl(0., 0.8, 0.5).into(),
+                    ..default()
+                },
+                HealthBarFill,
+            ));
+        });
+}
+
+/// Only shown while a fight is on.
+fn update_health_bar(
+    bosses: Query<(&Boss, &Health)>,
+    mut bars: Query<&mut Visibility, With<HealthBar>>,
+    mut fills: Query<&mut Style, With<HealthBarFill>>,
+) {
+    let active = bosses.iter().find(|(boss, _)| boss.active);
+
+    for mut visibility in &mut bars {
+        let shown = if active.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(shown);
+    }
+
+    if let Some((_, health)) = active {
+        for mut style in &mut fills {
+            style.width = Val::Percent(health.current as f32 / health.max as f32 * 100.);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::{
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    lighting,
+    player::{Player, PlayerSprite, Remote},
+    state::{AppState, CameraSet, InGame, InputSet},
+};
+
+/// How quickly the camera catches up with the player, higher is snappier
+const FOLLOW_SPEED: f32 = 5.0;
+/// Radians a second the camera turns in photo mode
+const ROTATE_SPEED: f32 = 1.0;
+
+/// The main camera, mouse panning and zooming, turning in photo mode, and
+/// the cursor position in world space.
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MyWorldCoords>()
+            .init_resource::<CameraLock>()
+            .init_resource::<CameraOverride>()
+            .add_systems(Startup, setup_camera)
+            .add_systems(PreUpdate, world_cursor.in_set(InputSet))
+            .add_systems(
+                PostUpdate,
+                (
+                    follow_player
+                        .run_if(in_state(AppState::Playing).or_else(in_state(AppState::Dying))),
+                    pan_camera.run_if(in_state(InGame)),
+                    zoom_camera.run_if(in_state(InGame)),
+                    rotate_camera.run_if(in_state(AppState::PhotoMode)),
+                )
+                    .chain()
+                    .in_set(CameraSet),
+            );
+    }
+}
+
+/// We will store the world position of the mouse cursor here.
+#[derive(Resource, Default)]
+pub(crate) struct MyWorldCoords(pub(crate) Vec2);
+
+/// Holds the camera on a fixed point instead of following the player, e.g.
+/// the middle of a boss arena.
+#[derive(Resource, Default)]
+pub(crate) struct CameraLock(pub(crate) Option<Vec2>);
+
+/// Points the camera somewhere for a cutscene, over any [`CameraLock`].
+#[derive(Resource, Default)]
+pub(crate) struct CameraOverride(pub(crate) Option<Vec2>);
+
+/// Used to help identify our main camera
+#[derive(Component)]
+pub(crate) struct MainCamera;
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2dBundle::default(),
+        MainCamera,
+        lighting::default_ambient(),
+        // Ears roughly half a screen apart
+        SpatialListener::new(800.),
+    ));
+}
+
+fn follow_player(
+    mut q_camera: Query<&mut Transform, With<MainCamera>>,
+    players: Query<(&Transform, &Children), (With<Player>, Without<Remote>, Without<MainCamera>)>,
+    sprites: Query<&Transform, (With<PlayerSprite>, Without<MainCamera>)>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    lock: Res<CameraLock>,
+    cutscene: Res<CameraOverride>,
+    time: Res<Time>,
+) {
+    // Panning takes over while the button is held
+    if buttons.pressed(MouseButton::Middle) {
+        return;
+    }
+
+    let (player, children) = match players.iter().next() {
+        Some(x) => x,
+        None => return,
+    };
+
+    // Follow the interpolated sprite rather than the body so it doesn't jitter
+    let target = match (cutscene.0.or(lock.0), sprites.iter_many(children).next()) {
+        (Some(point), _) => point.extend(0.),
+        (None, Some(sprite)) => player.transform_point(sprite.translation),
+        (None, None) => player.translation,
+    };
+
+    let mut transform = q_camera.single_mut();
+    let t = 1.0 - (-FOLLOW_SPEED * time.delta_seconds()).exp();
+    let target = target.truncate().extend(transform.translation.z);
+    transform.translation = transform.translation.lerp(target, t);
+}
+
+fn pan_camera(
+    mut q_camera: Query<&mut Transform, With<MainCamera>>,
+    mut motion_evr: EventReader<MouseMotion>,
+    buttons: Res<ButtonInput<MouseButton>>,
+) {
+    if !buttons.pressed(MouseButton::Middle) {
+        return;
+    }
+
+    let mut transform = q_camera.single_mut();
+    let delta = motion_evr.read().fold(Vec2::ZERO, |sum, x| sum + x.delta);
+
+    // Each axis on its own, x is negative while the level is mirrored
+    let delta = (Vec2::new(-delta.x, delta.y) * transform.scale.truncate()).extend(0.0);
+
+    let rotation = transform.rotation;
+    transform.translation += rotation * delta;
+}
+
+fn zoom_camera(
+    mut q_camera: Query<&mut Transform, With<MainCamera>>,
+    mut scroll_evr: EventReader<MouseWheel>,
+) {
+    let mut transform = q_camera.single_mut();
+
+    use bevy::input::mouse::MouseScrollUnit;
+    let amount: f32 = scroll_evr
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y * 0.1, // TODO: Tune
+        })
+        .sum();
+
+    let amount = -amount; // invert
+
+    let unit = transform.scale.normalize();
+
+    let new: Vec3 = transform.scale + (unit * amount * 0.1);
+    if new.dot(unit) > 0.0 {
+        transform.scale = new;
+    }
+}
+
+/// Q and E turn the camera, on real time since photo mode stops the clock.
+fn rotate_camera(
+    mut q_camera: Query<&mut Transform, With<MainCamera>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    let turn = match (keys.pressed(KeyCode::KeyQ), keys.pressed(KeyCode::KeyE)) {
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        _ => return,
+    };
+
+    let mut transform = q_camera.single_mut();
+    transform.rotate_z(turn * ROTATE_SPEED * time.delta_seconds());
+}
+
+fn world_cursor(
+    mut mycoords: ResMut<MyWorldCoords>,
+    // query to get the window (so we can read the current cursor position)
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    // query to get camera transform
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    // get the camera info and transform
+    // assuming there is exactly one main camera entity, so Query::single() is OK
+    let (camera, camera_transform) = q_camera.single();
+
+    // There is only one primary window, so we can similarly get it from the query:
+    let window = q_window.single();
+
+    // check if the cursor is inside the window and get its position
+    // then, ask bevy to convert into world coordinates, and truncate to discard Z
+    if let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate())
+    {
+        mycoords.0 = world_position;
+        // eprintln!("World coords: {}/{}", world_position.x, world_position.y);
+    }
+}
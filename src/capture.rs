@@ -0,0 +1,242 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts};
+
+#[cfg(not(target_arch = "wasm32"))]
+const SCREENSHOT_DIR: &str = "screenshots";
+#[cfg(not(target_arch = "wasm32"))]
+const CLIP_DIR: &str = "clips";
+/// How much a clip holds
+const CLIP_LENGTH: Duration = Duration::from_secs(10);
+/// Clip frames per second, kept low since every frame is read back from the
+/// GPU
+const CLIP_RATE: f32 = 10.;
+
+/// F9 saves a screenshot, downloaded in the browser. F10 starts keeping the
+/// last few seconds at a low frame rate and size, F11 writes them out as
+/// numbered PNGs, or downloads them laid out in one sheet in the browser.
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Clip>()
+            .init_resource::<Screenshots>()
+            .add_systems(Update, (screenshot, record, save_clip, indicator));
+    }
+}
+
+/// Numbers screenshots taken within the same second apart.
+#[derive(Resource, Default)]
+pub(crate) struct Screenshots {
+    taken: u32,
+}
+
+impl Screenshots {
+    /// Saves what `window` shows on the next frame to the screenshots folder,
+    /// or downloads it in the browser.
+    pub(crate) fn save(&mut self, manager: &mut ScreenshotManager, window: Entity) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(err) = std::fs::create_dir_all(SCREENSHOT_DIR) {
+            error!("Could not create {SCREENSHOT_DIR}: {err}");
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let path = format!("{SCREENSHOT_DIR}/glatformer-{}-{}.png", stamp(), self.taken);
+        #[cfg(target_arch = "wasm32")]
+        let path = format!("glatformer-{}.png", self.taken);
+
+        self.taken += 1;
+        if let Err(err) = manager.save_screenshot_to_disk(window, path) {
+            warn!("Could not take screenshot: {err}");
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Clip {
+    recording: bool,
+    /// Real time of the last captured frame
+    last: Option<Duration>,
+    /// Filled in by the renderer a frame or two after asking, oldest first
+    frames: Arc<Mutex<VecDeque<(Duration, image::RgbImage)>>>,
+}
+
+/// Seconds since the epoch to tell captures apart.
+#[cfg(not(target_arch = "wasm32"))]
+fn stamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+fn screenshot(
+    keys: Res<ButtonInput<KeyCode>>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut manager: ResMut<ScreenshotManager>,
+    mut screenshots: ResMut<Screenshots>,
+) {
+    if !keys.just_pressed(KeyCode::F9) {
+        return;
+    }
+    if let Some(window) = window.iter().next() {
+        screenshots.save(&mut manager, window);
+    }
+}
+
+fn record(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut clip: ResMut<Clip>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    time: Res<Time<Real>>,
+) {
+    if keys.just_pressed(KeyCode::F10) {
+        clip.recording = !clip.recording;
+        clip.last = None;
+        if let Ok(mut frames) = clip.frames.lock() {
+            frames.clear();
+        }
+    }
+    if !clip.recording {
+        return;
+    }
+
+    let now = time.elapsed();
+    let interval = Duration::from_secs_f32(1. / CLIP_RATE);
+    if clip.last.is_some_and(|last| now - last < interval) {
+        return;
+    }
+    let Some(window) = window.iter().next() else {
+        return;
+    };
+
+    let frames = clip.frames.clone();
+    let requested = screenshots.take_screenshot(window, move |image| {
+        let Ok(image) = image.try_into_dynamic() else {
+            return;
+        };
+        // Half size, most of the memory a clip takes is these
+        let frame = image
+            .thumbnail(image.width() / 2, image.height() / 2)
+            .into_rgb8();
+
+        let Ok(mut frames) = frames.lock() else {
+            return;
+        };
+        frames.push_back((now, frame));
+        while frames
+            .front()
+            .is_some_and(|(at, _)| now.saturating_sub(*at) > CLIP_LENGTH)
+        {
+            frames.pop_front();
+        }
+    });
+    // Someone else asked for this frame, try again on the next
+    if requested.is_ok() {
+        clip.last = Some(now);
+    }
+}
+
+fn save_clip(keys: Res<ButtonInput<KeyCode>>, clip: Res<Clip>) {
+    if !keys.just_pressed(KeyCode::F11) || !clip.recording {
+        return;
+    }
+
+    let frames = match clip.frames.lock() {
+        Ok(frames) => frames
+            .iter()
+            .map(|(_, frame)| frame.clone())
+            .collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+    write_clip(frames);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_clip(frames: Vec<image::RgbImage>) {
+    let dir = std::path::Path::new(CLIP_DIR).join(stamp().to_string());
+    // Encoding takes a while, don't hold up the game
+    std::thread::spawn(move || {
+        let result = std::fs::create_dir_all(&dir)
+            .map_err(|err| err.to_string())
+            .and_then(|_| {
+                frames.iter().enumerate().try_for_each(|(i, frame)| {
+                    frame
+                        .save(dir.join(format!("{i:04}.png")))
+                        .map_err(|err| err.to_string())
+                })
+            });
+
+        match result {
+            Ok(()) => info!("Saved {} frames to {}", frames.len(), dir.display()),
+            Err(err) => error!("Could not save clip: {err}"),
+        }
+    });
+}
+
+/// A page can only download single files, so the frames are tiled left to
+/// right and top to bottom into one PNG.
+#[cfg(target_arch = "wasm32")]
+fn write_clip(frames: Vec<image::RgbImage>) {
+    let Some((width, height)) = frames.first().map(image::RgbImage::dimensions) else {
+        return;
+    };
+    let count = frames.len() as u32;
+    let columns = (count as f32).sqrt().ceil() as u32;
+    let mut sheet = image::RgbImage::new(width * columns, height * count.div_ceil(columns));
+    for (i, frame) in (0..).zip(&frames) {
+        let x = (i % columns) * width;
+        let y = (i / columns) * height;
+        image::imageops::replace(&mut sheet, frame, x.into(), y.into());
+    }
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    if let Err(err) = sheet.write_to(&mut png, image::ImageFormat::Png) {
+        error!("Could not encode clip: {err}");
+        return;
+    }
+    let name = format!("glatformer-clip-{}.png", js_sys::Date::now() as u64);
+    match download(png.get_ref(), &name) {
+        Ok(()) => info!("Downloaded {count} frames as {name}"),
+        Err(err) => error!("Could not download clip: {err:?}"),
+    }
+}
+
+/// Hands `bytes` to the browser as a file called `name`.
+#[cfg(target_arch = "wasm32")]
+fn download(bytes: &[u8], name: &str) -> Result<(), wasm_bindgen::JsValue> {
+    use wasm_bindgen::JsCast;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let link = document.create_element("a")?;
+    link.set_attribute("href", &url)?;
+    link.set_attribute("download", name)?;
+    link.dyn_into::<web_sys::HtmlElement>()?.click();
+    web_sys::Url::revoke_object_url(&url)
+}
+
+fn indicator(mut contexts: EguiContexts, clip: Res<Clip>) {
+    if !clip.recording {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("clip"))
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(
+                egui::RichText::new("● REC  F11 saves")
+                    .color(egui::Color32::RED)
+                    .strong(),
+            );
+        });
+}
@@ -0,0 +1,81 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{player::Player, run::Run, state::InGame};
+
+const INACTIVE_COLOR: Color = Color::hsl(0., 0., 0.5);
+const ACTIVE_COLOR: Color = Color::hsl(120., 0.8, 0.5);
+
+#[derive(Component, Default)]
+pub(crate) struct Checkpoint {
+    /// Order in the level file, identifies its split
+    index: usize,
+}
+
+/// Sent the first time a checkpoint is reached in a run.
+#[derive(Event)]
+pub(crate) struct SplitRecorded {
+    pub(crate) index: usize,
+    pub(crate) time: f32,
+}
+
+pub(crate) fn make_checkpoint(
+    index: usize,
+    x: f32,
+    y: f32,
+) -> (
+    SpriteBundle,
+    Checkpoint,
+    Collider,
+    Sensor,
+    StateScoped<InGame>,
+) {
+    let (w, h) = (10., 120.);
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: INACTIVE_COLOR,
+                custom_size: Some(Vec2::new(w, h)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, -0.5),
+            ..default()
+        },
+        Checkpoint { index },
+        Collider::rectangle(w, h),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn activate(
+    mut started: EventReader<CollisionStarted>,
+    players: Query<(), With<Player>>,
+    mut checkpoints: Query<(&Checkpoint, &Transform, &mut Sprite)>,
+    mut run: ResMut<Run>,
+    mut splits: EventWriter<SplitRecorded>,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (checkpoint, other) = if checkpoints.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok((data, transform, mut sprite)) = checkpoints.get_mut(checkpoint) else {
+            continue;
+        };
+        if !players.contains(other) {
+            continue;
+        }
+
+        sprite.color = ACTIVE_COLOR;
+        run.checkpoint = Some(transform.translation.truncate());
+        if let Some(time) = run.split(data.index) {
+            splits.send(SplitRecorded {
+                index: data.index,
+                time,
+            });
+        }
+    }
+}
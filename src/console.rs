@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::input::ActionSources;
+
+/// Lines of output kept in the console
+const SCROLLBACK: usize = 200;
+
+/// A console dropping down from the top on the backtick key. Commands come
+/// from whichever plugins register them with [`ConsoleExt`].
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            // Typing shouldn't move the player
+            .configure_sets(PreUpdate, ActionSources.run_if(console_closed))
+            .add_console_command("help", "help  List the commands", help)
+            .add_console_command("clear", "clear  Empty the console", clear)
+            .add_systems(Update, (toggle, window, run).chain());
+    }
+}
+
+/// Runs a command with the words after its name, returns what to print.
+pub(crate) type CommandFn = fn(&mut World, &[&str]) -> Result<String, String>;
+
+struct Command {
+    /// Usage and what it does, shown by `help`
+    help: &'static str,
+    run: CommandFn,
+}
+
+#[derive(Resource, Default)]
+struct ConsoleCommands(BTreeMap<&'static str, Command>);
+
+pub(crate) trait ConsoleExt {
+    /// Make `name` a console command. `help` starts with its usage.
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        run: CommandFn,
+    ) -> &mut Self;
+}
+
+impl ConsoleExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        run: CommandFn,
+    ) -> &mut Self {
+        // Works whether or not the console plugin was added yet
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .0
+            .insert(name, Command { help, run });
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    output: Vec<String>,
+    /// Submitted lines waiting for exclusive world access
+    pending: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: String) {
+        self.output.push(line);
+        let extra = self.output.len().saturating_sub(SCROLLBACK);
+        self.output.drain(..extra);
+    }
+}
+
+fn console_closed(console: Res<Console>) -> bool {
+    !console.open
+}
+
+fn toggle(keys: Res<ButtonInput<KeyCode>>, mut console: ResMut<Console>) {
+    if keys.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+    }
+}
+
+fn window(mut contexts: EguiContexts, mut console: ResMut<Console>) {
+    if !console.open {
+        return;
+    }
+
+    egui::TopBottomPanel::top("console").show(contexts.ctx_mut(), |ui| {
+        egui::ScrollArea::vertical()
+            .max_height(200.)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                for line in &console.output {
+                    ui.monospace(line);
+                }
+            });
+
+        let edit = ui.add(
+            egui::TextEdit::singleline(&mut console.input)
+                .font(egui::TextStyle::Monospace)
+                .desired_width(f32::INFINITY),
+        );
+        // The backtick that opened the console would end up in the input
+        console.input.retain(|c| c != '`');
+
+        if edit.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            let line = std::mem::take(&mut console.input);
+            if !line.trim().is_empty() {
+                console.pending.push(line);
+            }
+        }
+        edit.request_focus();
+    });
+}
+
+fn run(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+
+    for line in pending {
+        world.resource_mut::<Console>().print(format!("> {line}"));
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let command = world
+            .resource::<ConsoleCommands>()
+            .0
+            .get(words[0])
+            .map(|command| command.run);
+
+        let result = match command {
+            Some(run) => run(world, &words[1..]),
+            None => Err(format!("Unknown command {}, try help", words[0])),
+        };
+
+        let mut console = world.resource_mut::<Console>();
+        match result {
+            Ok(output) if output.is_empty() => (),
+            Ok(output) => console.print(output),
+            Err(err) => console.print(format!("error: {err}")),
+        }
+    }
+}
+
+fn help(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let commands = world.resource::<ConsoleCommands>();
+    Ok(commands
+        .0
+        .values()
+        .map(|command| command.help)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn clear(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    world.resource_mut::<Console>().output.clear();
+    Ok(String::new())
+}
+
+/// The argument at `index` parsed, or why it couldn't be.
+pub(crate) fn arg<T: std::str::FromStr>(
+    args: &[&str],
+    index: usize,
+    name: &str,
+) -> Result<T, String> {
+    let value = args.get(index).ok_or(format!("missing {name}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("{name} should be a number, not {value}"))
+}
@@ -0,0 +1,53 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{cull::Cull, level, player::Hookable, state::InGame, surface::SurfaceMaterial};
+
+/// Light enough for the player to shove around, heavy enough to hold down a
+/// pressure plate. An 80 wide crate weighs about 3200, the player about 7800.
+const DENSITY: f32 = 0.5;
+
+/// A box to push, stack, stand on and drag around with the hook.
+#[derive(Component, Default)]
+pub(crate) struct Crate;
+
+pub(crate) fn make_crate(
+    placed: &level::Crate,
+) -> (
+    SpriteBundle,
+    Crate,
+    Hookable,
+    RigidBody,
+    Collider,
+    ColliderDensity,
+    LockedAxes,
+    Friction,
+    Restitution,
+    SurfaceMaterial,
+    Cull,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(30., 0.55, 0.4),
+                custom_size: Some(Vec2::splat(placed.size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, 0.),
+            ..default()
+        },
+        Crate,
+        Hookable,
+        RigidBody::Dynamic,
+        Collider::rectangle(placed.size, placed.size),
+        ColliderDensity(DENSITY),
+        // Tipping over makes stacks too fiddly
+        LockedAxes::ROTATION_LOCKED,
+        Friction::new(0.6),
+        Restitution::new(0.).with_combine_rule(CoefficientCombine::Min),
+        SurfaceMaterial::default(),
+        Cull::Sleep,
+        StateScoped(InGame),
+    )
+}
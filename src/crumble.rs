@@ -0,0 +1,161 @@
+use avian2d::prelude::*;
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    atlas::AtlasSprite, difficulty::Difficulty, level, player::Player, state::InGame,
+    surface::SurfaceMaterial,
+};
+
+/// Furthest the sprite moves while shaking, right before it gives way
+const SHAKE: f32 = 4.;
+
+/// A platform that shakes for a moment once the player stands on it, then
+/// falls or disappears and comes back a while later.
+#[derive(Component)]
+pub(crate) struct Crumbling {
+    home: Vec2,
+    delay: f32,
+    respawn: f32,
+    fall: bool,
+    state: CrumbleState,
+}
+
+enum CrumbleState {
+    Solid,
+    Shaking(Timer),
+    /// Fallen or vanished, until it respawns
+    Gone(Timer),
+}
+
+/// The shaking before it gives way lasts longer on easier [`Difficulty`].
+pub(crate) fn make_crumbling(
+    placed: &level::Crumbling,
+    difficulty: Difficulty,
+) -> (
+    SpriteBundle,
+    AtlasSprite,
+    Crumbling,
+    RigidBody,
+    Collider,
+    CollisionLayers,
+    LockedAxes,
+    Friction,
+    SurfaceMaterial,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(35., 0.5, 0.35),
+                custom_size: Some(Vec2::new(placed.w, placed.h)),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, 0.),
+            ..default()
+        },
+        AtlasSprite("tile"),
+        Crumbling {
+            home: Vec2::new(placed.x, placed.y),
+            delay: placed.delay * difficulty.hazard_time(),
+            respawn: placed.respawn,
+            fall: placed.fall,
+            state: CrumbleState::Solid,
+        },
+        RigidBody::Static,
+        Collider::rectangle(placed.w, placed.h),
+        CollisionLayers::default(),
+        // Drops straight down
+        LockedAxes::ROTATION_LOCKED,
+        Friction::new(1.),
+        SurfaceMaterial::Stone,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn step_on(players: Query<&Player>, mut platforms: Query<&mut Crumbling>) {
+    for player in &players {
+        let Some(mut platform) = player
+            .ground
+            .and_then(|ground| platforms.get_mut(ground).ok())
+        else {
+            continue;
+        };
+
+        if matches!(platform.state, CrumbleState::Solid) {
+            platform.state =
+                CrumbleState::Shaking(Timer::from_seconds(platform.delay, TimerMode::Once));
+        }
+    }
+}
+
+pub(crate) fn crumble(
+    mut platforms: Query<(
+        &mut Crumbling,
+        &mut RigidBody,
+        &mut CollisionLayers,
+        &mut Visibility,
+        &mut Position,
+        &mut LinearVelocity,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut platform, mut body, mut layers, mut visibility, mut position, mut velocity) in
+        &mut platforms
+    {
+        let (home, respawn, fall) = (platform.home, platform.respawn, platform.fall);
+        match &mut platform.state {
+            CrumbleState::Solid => (),
+            CrumbleState::Shaking(timer) => {
+                if !timer.tick(time.delta()).finished() {
+                    continue;
+                }
+
+                if fall {
+                    *body = RigidBody::Dynamic;
+                } else {
+                    *layers = CollisionLayers::NONE;
+                    *visibility = Visibility::Hidden;
+                }
+                platform.state = CrumbleState::Gone(Timer::from_seconds(respawn, TimerMode::Once));
+            }
+            CrumbleState::Gone(timer) => {
+                if !timer.tick(time.delta()).finished() {
+                    continue;
+                }
+
+                *body = RigidBody::Static;
+                *layers = CollisionLayers::default();
+                *visibility = Visibility::Inherited;
+                position.0 = home;
+                velocity.0 = Vec2::ZERO;
+                platform.state = CrumbleState::Solid;
+            }
+        }
+    }
+}
+
+/// Rattle the sprite harder the closer the platform is to giving way, the
+/// collider stays put.
+pub(crate) fn shake(mut platforms: Query<(&Crumbling, &mut Sprite)>, time: Res<Time>) {
+    for (platform, mut sprite) in &mut platforms {
+        let offset = match &platform.state {
+            CrumbleState::Shaking(timer) => {
+                let t = time.elapsed_seconds() * 60.;
+                Vec2::new(t.sin(), (t * 1.3).cos()) * SHAKE * timer.fraction()
+            }
+            _ => Vec2::ZERO,
+        };
+
+        let Some(size) = sprite.custom_size else {
+            continue;
+        };
+        let anchor = if offset == Vec2::ZERO {
+            Anchor::Center
+        } else {
+            Anchor::Custom(-offset / size)
+        };
+        if sprite.anchor != anchor {
+            sprite.anchor = anchor;
+        }
+    }
+}
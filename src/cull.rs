@@ -0,0 +1,115 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    level::{Level, Levels},
+    player::Player,
+    pool::Pool,
+    projectile::Projectile,
+    spawner::SpawnedBy,
+};
+
+/// How far past the level geometry things may go before they're culled
+const MARGIN: f32 = 2000.;
+/// Distance from the player at which things count as far off screen. The
+/// player stands in for the camera, which moves every frame rather than every
+/// tick and would make replays depend on the frame rate.
+const FAR: f32 = 3000.;
+
+/// What happens to something short lived once it's far off screen.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Cull {
+    /// Gone for good
+    Despawn,
+    /// Brought to a stop and put to sleep, until something gets it moving
+    /// again. Gone once it leaves the level.
+    Sleep,
+    /// Sent back to its spawner or its [`Pool`] to be used again, or
+    /// despawned without either
+    Recycle,
+}
+
+/// The area the level takes up, and then some.
+#[derive(Resource, Default)]
+pub(crate) struct Bounds(pub(crate) Rect);
+
+impl Bounds {
+    /// Just the area the level takes up.
+    pub(crate) fn level(&self) -> Rect {
+        self.0.inflate(-MARGIN)
+    }
+}
+
+pub(crate) fn bounds(mut commands: Commands, levels: Res<Levels>, assets: Res<Assets<Level>>) {
+    let Some(level) = levels.current(&assets) else {
+        return;
+    };
+
+    let area = level.cubes.iter().fold(
+        Rect::from_center_size(level.spawn, Vec2::ZERO),
+        |area, cube| {
+            let (sin, cos) = cube.rotation.to_radians().sin_cos();
+            let size = Vec2::new(
+                (cube.w * cos).abs() + (cube.h * sin).abs(),
+                (cube.w * sin).abs() + (cube.h * cos).abs(),
+            );
+            area.union(Rect::from_center_size(Vec2::new(cube.x, cube.y), size))
+        },
+    );
+    commands.insert_resource(Bounds(area.inflate(MARGIN)));
+}
+
+pub(crate) fn cull(
+    mut culled: Query<(
+        Entity,
+        &Cull,
+        &mut Position,
+        &mut LinearVelocity,
+        Has<Sleeping>,
+        Option<&SpawnedBy>,
+        Has<Projectile>,
+    )>,
+    players: Query<&Position, (With<Player>, Without<Cull>)>,
+    spawners: Query<&GlobalTransform>,
+    bounds: Res<Bounds>,
+    mut projectiles: ResMut<Pool<Projectile>>,
+    mut commands: Commands,
+) {
+    let players = players
+        .iter()
+        .map(|position| position.0)
+        .collect::<Vec<_>>();
+
+    for (entity, cull, mut position, mut velocity, sleeping, spawned_by, projectile) in &mut culled
+    {
+        let outside = !bounds.0.contains(position.0);
+        // Far from every player, with none nothing is
+        let far = outside
+            || !players.is_empty()
+                && players
+                    .iter()
+                    .all(|player| player.distance(position.0) > FAR);
+        if !far {
+            continue;
+        }
+
+        let spawner = spawned_by.and_then(|by| spawners.get(by.0).ok());
+        match (cull, spawner) {
+            (Cull::Sleep, _) if !outside => {
+                // Stop first, a body whose velocity changed wakes straight
+                // back up
+                if velocity.0 != Vec2::ZERO {
+                    velocity.0 = Vec2::ZERO;
+                } else if !sleeping {
+                    commands.entity(entity).insert(Sleeping);
+                }
+            }
+            (Cull::Recycle, Some(spawner)) => {
+                position.0 = spawner.translation().truncate();
+                velocity.0 = Vec2::ZERO;
+            }
+            (Cull::Recycle, None) if projectile => projectiles.park(&mut commands, entity),
+            _ => commands.entity(entity).despawn_recursive(),
+        }
+    }
+}
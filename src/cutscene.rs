@@ -0,0 +1,305 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    camera::{CameraOverride, MainCamera},
+    dialogue::Npc,
+    input::{ActionFilters, ActionState},
+    level,
+    locale::Locale,
+    plate::Triggered,
+    player::Player,
+    run::Run,
+    state::{AppState, GameplaySet, InGame},
+};
+
+const CARD_FONT_SIZE: f32 = 40.;
+
+/// Short scripted sequences for level intros and boss reveals: the camera
+/// pans, things move, text cards come up and the player waits.
+pub struct CutscenePlugin;
+
+impl Plugin for CutscenePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Cutscene>()
+            .add_systems(OnEnter(InGame), (setup, stop))
+            .add_systems(
+                PreUpdate,
+                lock_input
+                    .in_set(ActionFilters)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Update, (trigger, play).chain().in_set(GameplaySet));
+    }
+}
+
+/// What starts a cutscene. Each one plays at most once per attempt.
+#[derive(Deserialize, Clone, Copy)]
+pub(crate) enum Trigger {
+    /// As soon as the level starts, but not again after dying
+    Start,
+    /// When the channel is turned on, see [`Triggered`]
+    Channel(u32),
+    /// When the player walks into the area
+    Area { x: f32, y: f32, w: f32, h: f32 },
+}
+
+/// Something a cutscene moves around.
+#[derive(Deserialize, Clone)]
+pub(crate) enum Target {
+    Player,
+    /// The [`Npc`] going by this name
+    Npc(String),
+}
+
+/// One beat of a timeline, each starts when the one before it is over.
+#[derive(Deserialize, Clone)]
+pub(crate) enum Step {
+    /// Swing the camera over to `to`, it stays there until the end
+    Pan {
+        to: Vec2,
+        seconds: f32,
+    },
+    /// Slide something over to `to`
+    Move {
+        target: Target,
+        to: Vec2,
+        seconds: f32,
+    },
+    /// Show a line of text in the middle of the screen
+    Card {
+        text: String,
+        seconds: f32,
+    },
+    Wait(f32),
+}
+
+impl Step {
+    fn seconds(&self) -> f32 {
+        match self {
+            Step::Pan { seconds, .. } | Step::Move { seconds, .. } | Step::Card { seconds, .. } => {
+                *seconds
+            }
+            Step::Wait(seconds) => *seconds,
+        }
+    }
+}
+
+/// A cutscene waiting in the level for its trigger.
+#[derive(Component)]
+pub(crate) struct Timeline {
+    trigger: Trigger,
+    steps: Vec<Step>,
+    lock_input: bool,
+}
+
+/// The cutscene playing, if any.
+#[derive(Resource, Default)]
+pub(crate) struct Cutscene {
+    steps: Vec<Step>,
+    lock_input: bool,
+    step: usize,
+    elapsed: f32,
+    /// Where whatever the current step moves started out
+    from: Option<Vec2>,
+}
+
+impl Cutscene {
+    fn is_playing(&self) -> bool {
+        self.step < self.steps.len()
+    }
+
+    /// The player can't act, so can't be hurt either.
+    pub(crate) fn is_locking(&self) -> bool {
+        self.is_playing() && self.lock_input
+    }
+}
+
+/// The text card, hidden between cutscenes.
+#[derive(Component)]
+struct Card;
+
+pub(crate) fn make_timeline(placed: &level::Cutscene) -> (Timeline, StateScoped<InGame>) {
+    (
+        Timeline {
+            trigger: placed.trigger,
+            steps: placed.steps.clone(),
+            lock_input: placed.lock_input,
+        },
+        StateScoped(InGame),
+    )
+}
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Card,
+            StateScoped(InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: CARD_FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn stop(mut cutscene: ResMut<Cutscene>, mut camera: ResMut<CameraOverride>) {
+    *cutscene = Cutscene::default();
+    camera.0 = None;
+}
+
+fn lock_input(cutscene: Res<Cutscene>, mut state: ResMut<ActionState>) {
+    if cutscene.is_locking() {
+        state.block();
+    }
+}
+
+/// Starts the first cutscene whose trigger went off, the others keep
+/// waiting until nothing is playing.
+fn trigger(
+    timelines: Query<(Entity, &Timeline)>,
+    players: Query<&Transform, With<Player>>,
+    mut triggered: EventReader<Triggered>,
+    run: Res<Run>,
+    mut cutscene: ResMut<Cutscene>,
+    mut commands: Commands,
+) {
+    let channels: Vec<u32> = triggered
+        .read()
+        .filter(|event| event.active)
+        .map(|event| event.channel)
+        .collect();
+    if cutscene.is_playing() {
+        return;
+    }
+    let positions = players
+        .iter()
+        .map(|player| player.translation.truncate())
+        .collect::<Vec<_>>();
+
+    let fired = timelines
+        .iter()
+        .find(|(_, timeline)| match timeline.trigger {
+            Trigger::Start => run.deaths == 0,
+            Trigger::Channel(channel) => channels.contains(&channel),
+            Trigger::Area { x, y, w, h } => positions
+                .iter()
+                .any(|&pos| Rect::from_center_size(Vec2::new(x, y), Vec2::new(w, h)).contains(pos)),
+        });
+    let Some((entity, timeline)) = fired else {
+        return;
+    };
+
+    *cutscene = Cutscene {
+        steps: timeline.steps.clone(),
+        lock_input: timeline.lock_input,
+        ..default()
+    };
+    commands.entity(entity).despawn();
+}
+
+/// Steps through the playing cutscene, handing the camera back at the end.
+fn play(
+    mut cutscene: ResMut<Cutscene>,
+    mut camera: ResMut<CameraOverride>,
+    cameras: Query<&Transform, With<MainCamera>>,
+    mut movable: Query<
+        (
+            &mut Transform,
+            Option<&mut Position>,
+            Has<Player>,
+            Option<&Npc>,
+        ),
+        Without<MainCamera>,
+    >,
+    mut cards: Query<(&mut Visibility, &Children), With<Card>>,
+    mut texts: Query<&mut Text>,
+    time: Res<Time>,
+    locale: Res<Locale>,
+) {
+    if !cutscene.is_playing() {
+        return;
+    }
+
+    cutscene.elapsed += time.delta_seconds();
+    let step = cutscene.steps[cutscene.step].clone();
+    let t = (cutscene.elapsed / step.seconds().max(f32::EPSILON)).min(1.);
+
+    let mut card = None;
+    match &step {
+        Step::Pan { to, .. } => {
+            let from = *cutscene.from.get_or_insert_with(|| {
+                camera
+                    .0
+                    .or(cameras
+                        .iter()
+                        .next()
+                        .map(|camera| camera.translation.truncate()))
+                    .unwrap_or(*to)
+            });
+            camera.0 = Some(from.lerp(*to, t));
+        }
+        Step::Move { target, to, .. } => {
+            let found = movable.iter_mut().find(|(_, _, player, npc)| match target {
+                Target::Player => *player,
+                Target::Npc(name) => npc.is_some_and(|npc| npc.name == *name),
+            });
+            if let Some((mut transform, position, _, _)) = found {
+                let from = *cutscene
+                    .from
+                    .get_or_insert(transform.translation.truncate());
+                let at = from.lerp(*to, t);
+                // Bodies are moved through the physics so it doesn't undo it
+                match position {
+                    Some(mut position) => position.0 = at,
+                    None => transform.translation = at.extend(transform.translation.z),
+                }
+            }
+        }
+        Step::Card { text, .. } => card = Some(locale.get(text)),
+        Step::Wait(_) => {}
+    }
+
+    if t >= 1. {
+        cutscene.step += 1;
+        cutscene.elapsed = 0.;
+        cutscene.from = None;
+        if !cutscene.is_playing() {
+            camera.0 = None;
+            card = None;
+        }
+    }
+
+    for (mut visibility, children) in &mut cards {
+        visibility.set_if_neq(match card {
+            Some(_) => Visibility::Inherited,
+            None => Visibility::Hidden,
+        });
+        if let Some(card) = card {
+            let mut iter = texts.iter_many_mut(children);
+            while let Some(mut text) = iter.fetch_next() {
+                if text.sections[0].value != card {
+                    text.sections[0].value = card.to_string();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::{
+    prelude::*,
+    utils::{SystemTime, SystemTimeError},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    deterministic::GameRng,
+    mutators::{Mutator, Mutators},
+    run::Run,
+    save::SaveData,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// What a day's challenge picks its mutators from, changing it changes
+/// every day's challenge
+const POOL: [Mutator; 4] = [
+    Mutator::ShortHook,
+    Mutator::BulletTime,
+    Mutator::NoCheckpoints,
+    Mutator::OneHit,
+];
+
+/// One day's level and mutators, the same for everyone playing that day.
+#[derive(Clone)]
+pub(crate) struct Challenge {
+    pub(crate) day: u64,
+    /// Index into [`Levels`](crate::level::Levels)
+    pub(crate) level: usize,
+    pub(crate) mutators: Vec<Mutator>,
+}
+
+impl Challenge {
+    /// Picks the level and one or two mutators from the day alone, so
+    /// `levels` has to be the same number for everyone.
+    pub(crate) fn new(day: u64, levels: usize) -> Self {
+        let mut rng = GameRng::new(day);
+        let level = (rng.next_u64() % levels.max(1) as u64) as usize;
+        let mut pool = POOL.to_vec();
+        let count = 1 + rng.next_u64() % 2;
+        let mutators = (0..count)
+            .map(|_| pool.swap_remove((rng.next_u64() % pool.len() as u64) as usize))
+            .collect();
+
+        Self {
+            day,
+            level,
+            mutators,
+        }
+    }
+
+    /// Name the day's times are submitted under, a board of its own each day.
+    pub(crate) fn board(&self) -> String {
+        format!("daily-{}", self.day)
+    }
+}
+
+/// The one daily attempt, kept in the save so it can't be retried.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct Attempt {
+    pub(crate) day: u64,
+    /// Completion time, `None` if it was given up or is still going
+    pub(crate) time: Option<f32>,
+}
+
+/// The daily challenge being played, if any.
+#[derive(Resource, Default)]
+pub(crate) struct Daily(pub(crate) Option<Challenge>);
+
+/// Days since the Unix epoch in UTC, everyone's day turns over together.
+pub(crate) fn today() -> Result<u64, SystemTimeError> {
+    let since = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+    Ok(since.as_secs() / SECONDS_PER_DAY)
+}
+
+pub(crate) fn is_active(daily: Res<Daily>) -> bool {
+    daily.0.is_some()
+}
+
+/// Start today's challenge with its mutators in place of the chosen ones,
+/// using up the attempt straight away.
+pub(crate) fn start(
+    daily: &mut Daily,
+    mutators: &mut Mutators,
+    save: &mut SaveData,
+    run: &mut Run,
+    challenge: Challenge,
+) {
+    save.daily = Some(Attempt {
+        day: challenge.day,
+        time: None,
+    });
+    save.write();
+    *run = Run::default();
+    mutators.0 = challenge.mutators.iter().copied().collect();
+    daily.0 = Some(challenge);
+}
+
+pub(crate) fn finish(daily: Res<Daily>, run: Res<Run>, mut save: ResMut<SaveData>) {
+    let Some(challenge) = &daily.0 else {
+        return;
+    };
+
+    save.daily = Some(Attempt {
+        day: challenge.day,
+        time: Some(run.time.elapsed_secs()),
+    });
+    save.write();
+}
+
+/// Ends the challenge along with its mutators.
+pub(crate) fn end(mut daily: ResMut<Daily>, mut mutators: ResMut<Mutators>) {
+    if daily.0.take().is_some() {
+        mutators.0.clear();
+    }
+}
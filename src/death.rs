@@ -0,0 +1,105 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{health::Health, kinematic::KinematicBody, player::Player, run::Run, state::AppState};
+
+/// Falling below this counts as dying
+const KILL_HEIGHT: f32 = -2000.0;
+/// Seconds the body tumbles for before the death screen
+const TUMBLE_TIME: f32 = 1.0;
+/// Upward speed the body gets at least as it goes limp
+const DEATH_POP: f32 = 600.0;
+/// Spin the body gets as it goes limp, in radians per second
+const DEATH_SPIN: f32 = 12.0;
+
+/// On the player while they tumble after dying: free to rotate and not kept
+/// upright. Taken off again if they're ever played on without respawning.
+#[derive(Component)]
+pub(crate) struct Ragdoll;
+
+/// Counts down the tumble before the death screen.
+#[derive(Resource)]
+pub(crate) struct Tumble(Timer);
+
+/// Any player dying ends the attempt for everyone.
+pub(crate) fn die(
+    players: Query<(&Transform, &Health), With<Player>>,
+    mut run: ResMut<Run>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    let dead = players
+        .iter()
+        .any(|(transform, health)| health.current == 0 || transform.translation.y < KILL_HEIGHT);
+    if dead {
+        run.deaths += 1;
+        next.set(AppState::Dying);
+    }
+}
+
+/// Unlocks the player's rotation and knocks them up and spinning, away from
+/// the way they were going. A kinematic player turns dynamic for it, so the
+/// body can tumble like any other.
+pub(crate) fn go_limp(
+    mut players: Query<
+        (
+            Entity,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+            Has<KinematicBody>,
+        ),
+        With<Player>,
+    >,
+    mut commands: Commands,
+) {
+    commands.insert_resource(Tumble(Timer::from_seconds(TUMBLE_TIME, TimerMode::Once)));
+
+    for (entity, mut velocity, mut spin, kinematic) in &mut players {
+        velocity.y = velocity.y.max(DEATH_POP);
+        spin.0 = if velocity.x > 0.0 {
+            -DEATH_SPIN
+        } else {
+            DEATH_SPIN
+        };
+        commands.entity(entity).insert((Ragdoll, LockedAxes::new()));
+        if kinematic {
+            commands.entity(entity).insert(RigidBody::Dynamic);
+        }
+    }
+}
+
+pub(crate) fn tumble(
+    mut tumble: ResMut<Tumble>,
+    time: Res<Time>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    if tumble.0.tick(time.delta()).just_finished() {
+        next.set(AppState::Dead);
+    }
+}
+
+/// Stands the player back up with their rotation locked again, and kinematic
+/// again if they were.
+pub(crate) fn recover(
+    mut players: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut AngularVelocity,
+            Has<KinematicBody>,
+        ),
+        With<Ragdoll>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, mut transform, mut spin, kinematic) in &mut players {
+        transform.rotation = Quat::IDENTITY;
+        spin.0 = 0.0;
+        commands
+            .entity(entity)
+            .remove::<Ragdoll>()
+            .insert(LockedAxes::ROTATION_LOCKED);
+        if kinematic {
+            commands.entity(entity).insert(RigidBody::Kinematic);
+        }
+    }
+}
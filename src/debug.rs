@@ -0,0 +1,184 @@
+use avian2d::prelude::*;
+use bevy::{color::palettes::css::ORANGE, prelude::*};
+
+use crate::{
+    camera::MyWorldCoords,
+    console::{self, ConsoleExt},
+    frame_step::{self, FrameStep},
+    geometry::Merged,
+    health::GodMode,
+    input::{Action, ActionState},
+    inspector::{self, Inspector},
+    level,
+    overlay::OverlayPlugin,
+    pickup, player,
+    state::{AppState, InGame},
+    tuning::{self, TuningPanel},
+};
+
+/// Collider outlines and how level geometry got merged, the Ctrl + click
+/// tools for drawing geometry and teleporting the player, a panel for tuning
+/// how the game feels, an entity inspector, stepping through ticks one at a
+/// time and performance numbers.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((PhysicsDebugPlugin::default(), OverlayPlugin))
+            .init_resource::<DebugTools>()
+            .init_resource::<TuningPanel>()
+            .add_systems(
+                Update,
+                (
+                    toggle_tools,
+                    show_colliders.run_if(resource_changed::<DebugTools>),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                show_merged.run_if(tools_enabled.and_then(in_state(InGame))),
+            )
+            .add_systems(
+                Update,
+                debug.run_if(
+                    tools_enabled
+                        .and_then(in_state(AppState::Playing).or_else(in_state(AppState::Editor))),
+                ),
+            )
+            .add_systems(Update, (tuning::toggle, tuning::panel).chain())
+            .register_type::<player::Player>()
+            .register_type::<player::Hooks>()
+            .init_resource::<Inspector>()
+            .add_systems(Update, (inspector::toggle, inspector::window).chain())
+            .add_console_command("tp", "tp <x> <y>  Move the player", teleport)
+            .add_console_command("spawn", "spawn <bomb|coin>  Put one at the cursor", spawn)
+            .add_console_command("god", "god  Toggle taking no damage", god)
+            .add_console_command("set", tuning::SET_HELP, tuning::set)
+            .init_resource::<FrameStep>()
+            .add_systems(
+                Update,
+                (frame_step::toggle, frame_step::advance, frame_step::banner).chain(),
+            );
+    }
+}
+
+/// Whether the collider outlines and the Ctrl + click tools are on, F5
+/// toggles them. Off by default in release builds.
+#[derive(Resource)]
+pub(crate) struct DebugTools(pub(crate) bool);
+
+impl Default for DebugTools {
+    fn default() -> Self {
+        Self(cfg!(debug_assertions))
+    }
+}
+
+fn tools_enabled(tools: Res<DebugTools>) -> bool {
+    tools.0
+}
+
+fn toggle_tools(keys: Res<ButtonInput<KeyCode>>, mut tools: ResMut<DebugTools>) {
+    if keys.just_pressed(KeyCode::F5) {
+        tools.0 = !tools.0;
+    }
+}
+
+fn show_colliders(tools: Res<DebugTools>, mut store: ResMut<GizmoConfigStore>) {
+    store.config_mut::<PhysicsGizmos>().0.enabled = tools.0;
+}
+
+/// Crosses out each of the rectangles level geometry was merged into, on
+/// top of the collider outlines.
+fn show_merged(merged: Query<&Merged>, mut gizmos: Gizmos) {
+    for rect in merged.iter().flat_map(|merged| &merged.rects) {
+        gizmos.rect_2d(rect.center(), 0., rect.size(), ORANGE);
+        gizmos.line_2d(rect.min, rect.max, ORANGE);
+        gizmos.line_2d(
+            Vec2::new(rect.min.x, rect.max.y),
+            Vec2::new(rect.max.x, rect.min.y),
+            ORANGE,
+        );
+    }
+}
+
+fn debug(
+    mut player: Query<&mut Transform, (With<player::Player>, Without<player::Remote>)>,
+    mut last_click_pos: Local<Option<Vec2>>,
+    actions: Res<ActionState>,
+    coords: Res<MyWorldCoords>,
+    mut commands: Commands,
+) {
+    let coords = coords.0;
+
+    if !actions.pressed(Action::DebugModifier) {
+        return;
+    }
+
+    // Make geo
+    if actions.just_pressed_this_frame(Action::DebugPlace) {
+        match *last_click_pos {
+            Some(pos) => {
+                let center = (pos + coords) / 2.0;
+                let len = pos.distance(coords);
+
+                let mut cube = level::make_cube(center.x, center.y, len, 10.0, 50.0);
+
+                let diff = coords - pos;
+                let rotation = f32::atan2(diff.y, diff.x);
+
+                cube.sprite.transform.rotate_z(rotation);
+
+                commands.spawn(cube);
+
+                *last_click_pos = None;
+            }
+            None => *last_click_pos = Some(coords),
+        }
+    }
+
+    let mut transform = match player.iter_mut().next() {
+        Some(x) => x,
+        None => return,
+    };
+
+    // Teleport
+    if actions.just_pressed_this_frame(Action::DebugTeleport) {
+        transform.translation = Vec3::new(coords.x, coords.y, transform.translation.z);
+    }
+}
+
+fn teleport(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let x = console::arg(args, 0, "x")?;
+    let y = console::arg(args, 1, "y")?;
+
+    let mut players = world.query_filtered::<&mut Transform, With<player::Player>>();
+    let mut transform = players.iter_mut(world).next().ok_or("no player")?;
+    transform.translation = Vec3::new(x, y, transform.translation.z);
+    Ok(String::new())
+}
+
+fn spawn(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if !world.contains_resource::<State<InGame>>() {
+        return Err("not in a level".into());
+    }
+
+    let at = world.resource::<MyWorldCoords>().0;
+    match args.first().copied() {
+        Some("bomb") => world.spawn(player::make_bomb(
+            player::BombKind::Timed,
+            None,
+            at,
+            Vec2::ZERO,
+        )),
+        Some("coin") => world.spawn(pickup::make_coin(at.x, at.y)),
+        _ => return Err("spawn what? bomb or coin".into()),
+    };
+    Ok(String::new())
+}
+
+fn god(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let mut god = world.resource_mut::<GodMode>();
+    god.0 = !god.0;
+    Ok(format!("God mode {}", if god.0 { "on" } else { "off" }))
+}
@@ -0,0 +1,189 @@
+use std::{
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use avian2d::prelude::*;
+use bevy::{ecs::schedule::ExecutorKind, prelude::*, time::TimeUpdateStrategy};
+
+use crate::world::{FIXED_HZ, SUBSTEPS};
+
+/// Makes a run of the game depend only on its inputs, so replays and future
+/// rollback netplay come out identical on the same platform. Opt in by adding
+/// it after [`GamePlugins`](crate::GamePlugins).
+///
+/// Every update advances time by exactly one fixed tick instead of following
+/// the wall clock, all schedules run their systems one at a time in a stable
+/// order, the substep count stays pinned and [`GameRng`] starts from `seed`.
+pub struct DeterministicPlugin {
+    pub seed: u64,
+}
+
+impl Plugin for DeterministicPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Deterministic)
+            .insert_resource(GameRng::new(self.seed))
+            .insert_resource(SubstepCount(SUBSTEPS))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1. / FIXED_HZ,
+            )));
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Systems spawning in parallel hand out entity ids in a different
+        // order every run, in any schedule including the state transitions
+        // that build levels. Done once every plugin has added its systems.
+        let mut schedules = app.world_mut().resource_mut::<Schedules>();
+        for (_, schedule) in schedules.iter_mut() {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        }
+    }
+}
+
+/// Present while [`DeterministicPlugin`] is on, anything tuning itself to the
+/// frame rate has to leave the simulation alone.
+#[derive(Resource)]
+pub(crate) struct Deterministic;
+
+/// The simulation's only source of randomness, seeded by
+/// [`DeterministicPlugin`] and from the OS otherwise. SplitMix64, small and
+/// the same everywhere.
+#[derive(Resource)]
+pub(crate) struct GameRng(u64);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(
+            std::collections::hash_map::RandomState::new()
+                .build_hasher()
+                .finish(),
+        )
+    }
+}
+
+impl GameRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `range`.
+    pub(crate) fn range(&mut self, range: std::ops::Range<f32>) -> f32 {
+        // The top 24 bits fill an f32 mantissa exactly
+        let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        range.start + unit * (range.end - range.start)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        input::{ActionFrame, ActionState},
+        level::{Level, Levels},
+        player::Player,
+        state::AppState,
+    };
+
+    /// Run right, jump onto the step halfway along and keep going, as
+    /// `(ticks, frame)` like a recording. Bits are `MoveRight` and `Jump`.
+    const INPUTS: &str = "[
+        (30, (held: 0, latched: 0, stick: None, aim: None)),
+        (35, (held: 2, latched: 0, stick: None, aim: None)),
+        (1, (held: 6, latched: 4, stick: None, aim: None)),
+        (20, (held: 6, latched: 0, stick: None, aim: None)),
+        (40, (held: 2, latched: 0, stick: None, aim: None)),
+        (30, (held: 0, latched: 0, stick: None, aim: None)),
+    ]";
+
+    const LEVEL: &str = "(
+        name: \"Test\",
+        spawn: (0., 100.),
+        medals: (gold: 1., silver: 2., bronze: 3.),
+        cubes: [
+            (x: 0., y: 0., w: 4000., h: 50., hue: 0.),
+            (x: 1300., y: 80., w: 1200., h: 120., hue: 0.),
+        ],
+    )";
+
+    /// Frames to play, one per fixed tick.
+    #[derive(Resource)]
+    pub(crate) struct Inputs(pub(crate) Vec<ActionFrame>);
+
+    /// Picks the frame by tick so rolling back plays the same inputs again.
+    fn feed(inputs: Res<Inputs>, mut actions: ResMut<ActionState>, time: Res<Time<Fixed>>) {
+        let tick = time.elapsed().as_nanos() / time.timestep().as_nanos();
+        let frame = usize::try_from(tick)
+            .ok()
+            .and_then(|tick| inputs.0.get(tick.checked_sub(1)?))
+            .copied()
+            .unwrap_or_default();
+        actions.play(&frame);
+    }
+
+    /// Everything the player needs to run around a level, without a window.
+    pub(crate) fn app(seed: u64) -> App {
+        // Nothing from disk, the index would replace the test level whenever
+        // it happens to finish loading
+        let mut app = crate::headless_app(AssetPlugin {
+            file_path: "none".into(),
+            ..default()
+        });
+        app.add_plugins(DeterministicPlugin { seed })
+            .add_systems(FixedPreUpdate, feed);
+
+        let level: Level = ron::from_str(LEVEL).unwrap();
+        let handle = app.world_mut().resource_mut::<Assets<Level>>().add(level);
+        let mut levels = app.world_mut().resource_mut::<Levels>();
+        levels.names = vec!["test.ron".into()];
+        levels.handles = vec![handle];
+        app.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Playing);
+        app
+    }
+
+    /// One frame per tick from a log of `(ticks, frame)`.
+    pub(crate) fn frames(log: &str) -> Vec<ActionFrame> {
+        let log: Vec<(u32, ActionFrame)> = ron::from_str(log).unwrap();
+        log.into_iter()
+            .flat_map(|(count, frame)| std::iter::repeat_n(frame, count as usize))
+            .collect()
+    }
+
+    pub(crate) fn play(mut app: App, log: &str) -> Vec2 {
+        let frames = frames(log);
+        let ticks = frames.len();
+
+        app.insert_resource(Inputs(frames));
+        // One more to enter the level
+        for _ in 0..=ticks {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        let mut players = world.query_filtered::<&Position, With<Player>>();
+        players.single(world).0
+    }
+
+    #[test]
+    fn replay_is_identical() {
+        let first = play(app(7), INPUTS);
+        let second = play(app(7), INPUTS);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn replay_ends_up_on_the_step() {
+        let end = play(app(7), INPUTS);
+        // Loose enough to survive float differences between platforms
+        assert!(end.distance(Vec2::new(1655.4, 190.)) < 1., "ended at {end}");
+    }
+}
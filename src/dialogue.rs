@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    input::{Action, ActionFilters, ActionState},
+    level,
+    locale::Locale,
+    player::{Player, Remote, PLAYER_RADIUS},
+    state::{AppState, InGame},
+};
+
+/// How close the player has to be to talk to someone
+const TALK_RANGE: f32 = PLAYER_RADIUS + 80.;
+const NPC_SIZE: Vec2 = Vec2::new(60., 100.);
+
+/// Characters to talk to, with their lines shown a page at a time. The
+/// player can't move while a conversation is open.
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Dialogue>()
+            .add_systems(OnEnter(InGame), close)
+            .add_systems(
+                PreUpdate,
+                talk.in_set(ActionFilters)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Update, show.run_if(in_state(AppState::Playing)));
+    }
+}
+
+/// Someone who says their lines when talked to.
+#[derive(Component)]
+pub(crate) struct Npc {
+    pub(crate) name: String,
+    pages: Vec<String>,
+}
+
+/// The conversation going on, if any.
+#[derive(Resource, Default)]
+pub(crate) struct Dialogue {
+    speaker: Option<Entity>,
+    page: usize,
+    /// Closed, but input stays blocked until the button that closed it is
+    /// let go so it doesn't jump or start talking again
+    releasing: bool,
+}
+
+pub(crate) fn make_npc(placed: &level::Npc) -> (SpriteBundle, Npc, StateScoped<InGame>) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(placed.hue, 0.5, 0.6),
+                custom_size: Some(NPC_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, -0.5),
+            ..default()
+        },
+        Npc {
+            name: placed.name.clone(),
+            pages: placed.pages.clone(),
+        },
+        StateScoped(InGame),
+    )
+}
+
+fn close(mut dialogue: ResMut<Dialogue>) {
+    *dialogue = Dialogue::default();
+}
+
+/// Starts a conversation with Interact next to someone, then turns the page
+/// with Interact or Jump. Input is kept from the player the whole time.
+fn talk(
+    mut state: ResMut<ActionState>,
+    mut dialogue: ResMut<Dialogue>,
+    players: Query<&Transform, (With<Player>, Without<Remote>)>,
+    npcs: Query<(Entity, &Transform, &Npc)>,
+) {
+    let next_page = state.just_pressed_this_frame(Action::Interact)
+        || state.just_pressed_this_frame(Action::Jump);
+
+    if let Some(speaker) = dialogue.speaker {
+        if next_page {
+            dialogue.page += 1;
+        }
+        let pages = npcs.get(speaker).map_or(0, |(_, _, npc)| npc.pages.len());
+        if dialogue.page >= pages {
+            dialogue.speaker = None;
+            dialogue.releasing = true;
+        }
+        state.block();
+        return;
+    }
+
+    if dialogue.releasing {
+        if state.pressed(Action::Interact) || state.pressed(Action::Jump) {
+            state.block();
+        } else {
+            dialogue.releasing = false;
+        }
+        return;
+    }
+
+    if !state.just_pressed_this_frame(Action::Interact) {
+        return;
+    }
+    let Some(player) = players.iter().next() else {
+        return;
+    };
+    let pos = player.translation.truncate();
+    let nearest = npcs
+        .iter()
+        .filter(|(_, _, npc)| !npc.pages.is_empty())
+        .map(|(entity, transform, _)| (entity, transform.translation.truncate().distance(pos)))
+        .filter(|(_, distance)| *distance <= TALK_RANGE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((speaker, _)) = nearest {
+        dialogue.speaker = Some(speaker);
+        dialogue.page = 0;
+        state.block();
+    }
+}
+
+/// The open conversation as a box along the bottom of the screen.
+/// Names and lines are looked up in the locale files, and shown as they are
+/// when they aren't ids there.
+fn show(
+    mut contexts: EguiContexts,
+    dialogue: Res<Dialogue>,
+    npcs: Query<&Npc>,
+    locale: Res<Locale>,
+) {
+    let Some(npc) = dialogue.speaker.and_then(|speaker| npcs.get(speaker).ok()) else {
+        return;
+    };
+    let Some(page) = npc.pages.get(dialogue.page) else {
+        return;
+    };
+
+    egui::Window::new(locale.get(&npc.name))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0., -40.])
+        .min_width(500.)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(egui::RichText::new(locale.get(page)).size(18.));
+            ui.add_space(10.);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+                ui.label(
+                    egui::RichText::new(format!("{} / {}", dialogue.page + 1, npc.pages.len()))
+                        .weak(),
+                );
+            });
+        });
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How hard the levels are. Enemies read it as they act, everything else
+/// when the level is loaded, so a change takes full effect on the next one.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub(crate) const ALL: [Difficulty; 3] =
+        [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// Id of the preset's name in the locale files.
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty-easy",
+            Difficulty::Normal => "difficulty-normal",
+            Difficulty::Hard => "difficulty-hard",
+        }
+    }
+
+    /// How fast enemies move, shoot and reload compared to normal.
+    pub(crate) fn enemy_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    /// Health an enemy or its shot takes from the player.
+    pub(crate) fn enemy_damage(self) -> u32 {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => 1,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    /// How long hazard cycles and warnings last compared to normal, longer
+    /// gives more time to react.
+    pub(crate) fn hazard_time(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.3,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.8,
+        }
+    }
+
+    /// Whether the level's checkpoint at `index` out of `count` is placed.
+    /// Hard leaves out every other one but always keeps the last, Easy also
+    /// places the level's `easy_checkpoints`.
+    pub(crate) fn keeps_checkpoint(self, index: usize, count: usize) -> bool {
+        self != Difficulty::Hard || index % 2 == 1 || index + 1 == count
+    }
+}
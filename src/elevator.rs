@@ -0,0 +1,187 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    level,
+    plate::Triggered,
+    player::{self, Player},
+    state::InGame,
+    surface::SurfaceMaterial,
+};
+
+const HEIGHT: f32 = 20.;
+/// How directly the elevator has to be pushing the player into something
+/// else for it to count as a pinch
+const PINCH_DOT: f32 = -0.5;
+
+/// How the speed changes over a trip.
+#[derive(Deserialize, Clone, Copy, Default)]
+pub(crate) enum Easing {
+    #[default]
+    Linear,
+    /// Speeds up away from each end and slows down into the next
+    Smooth,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::Smooth => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+/// What an elevator does when it would crush the player.
+#[derive(Deserialize, Clone, Copy, Default)]
+pub(crate) enum OnCrush {
+    /// Waits until the player gets out of the way
+    #[default]
+    Stop,
+    /// Heads back where it came from until triggered again
+    Reverse,
+}
+
+/// A platform travelling between `from` and `to`, towards `to` while its
+/// channel is triggered and back to `from` once it isn't.
+#[derive(Component)]
+pub(crate) struct Elevator {
+    from: Vec2,
+    to: Vec2,
+    channel: u32,
+    speed: f32,
+    easing: Easing,
+    on_crush: OnCrush,
+    /// 0 at `from`, 1 at `to`
+    progress: f32,
+    active: bool,
+    /// Backing off after nearly crushing the player
+    reversed: bool,
+    /// Moved on the last tick
+    moving: bool,
+}
+
+/// Sent when an elevator sets off after standing still.
+#[derive(Event)]
+pub(crate) struct ElevatorStarted {
+    pub(crate) elevator: Entity,
+}
+
+pub(crate) fn make_elevator(
+    placed: &level::Elevator,
+) -> (
+    SpriteBundle,
+    Elevator,
+    RigidBody,
+    Collider,
+    Friction,
+    SurfaceMaterial,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(45., 0.4, 0.45),
+                custom_size: Some(Vec2::new(placed.w, HEIGHT)),
+                ..default()
+            },
+            transform: Transform::from_translation(placed.from.extend(0.)),
+            ..default()
+        },
+        Elevator {
+            from: placed.from,
+            to: placed.to,
+            channel: placed.channel,
+            speed: placed.speed,
+            easing: placed.easing,
+            on_crush: placed.on_crush,
+            progress: 0.,
+            active: false,
+            reversed: false,
+            moving: false,
+        },
+        RigidBody::Kinematic,
+        Collider::rectangle(placed.w, HEIGHT),
+        Friction::new(1.),
+        SurfaceMaterial::Metal,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn listen(mut triggered: EventReader<Triggered>, mut elevators: Query<&mut Elevator>) {
+    for event in triggered.read() {
+        for mut elevator in &mut elevators {
+            if elevator.channel == event.channel {
+                elevator.active = event.active;
+                elevator.reversed = false;
+            }
+        }
+    }
+}
+
+/// Moves elevators by setting their velocity, so whatever stands on them
+/// gets carried along.
+pub(crate) fn travel(
+    mut elevators: Query<(Entity, &mut Elevator, &Position, &mut LinearVelocity)>,
+    players: Query<(Entity, &Transform), With<Player>>,
+    collisions: Res<Collisions>,
+    time: Res<Time>,
+    mut started: EventWriter<ElevatorStarted>,
+) {
+    let delta = time.delta_seconds();
+    if delta == 0. {
+        return;
+    }
+
+    for (entity, mut elevator, position, mut velocity) in &mut elevators {
+        let pinching = players
+            .iter()
+            .any(|(player, transform)| pinches(&collisions, entity, player, transform.rotation));
+        if pinching {
+            match elevator.on_crush {
+                OnCrush::Stop => {
+                    velocity.0 = Vec2::ZERO;
+                    elevator.moving = false;
+                    continue;
+                }
+                OnCrush::Reverse => elevator.reversed = true,
+            }
+        }
+
+        let length = elevator.from.distance(elevator.to).max(1.);
+        let step = elevator.speed * delta / length;
+        let towards_end = elevator.active != elevator.reversed;
+        let progress = if towards_end {
+            (elevator.progress + step).min(1.)
+        } else {
+            (elevator.progress - step).max(0.)
+        };
+        let moving = progress != elevator.progress;
+        if moving && !elevator.moving {
+            started.send(ElevatorStarted { elevator: entity });
+        }
+        elevator.progress = progress;
+        elevator.moving = moving;
+
+        let target = elevator
+            .from
+            .lerp(elevator.to, elevator.easing.apply(elevator.progress));
+        velocity.0 = (target - position.0) / delta;
+    }
+}
+
+/// Whether `elevator` is pushing `player` into something on its other side.
+fn pinches(collisions: &Collisions, elevator: Entity, player: Entity, rotation: Quat) -> bool {
+    let Some(contacts) = collisions.get(elevator, player) else {
+        return false;
+    };
+    let pushes = player::surface_normals(contacts, player, rotation).collect::<Vec<_>>();
+
+    collisions
+        .collisions_with_entity(player)
+        .filter(|contacts| !contacts.is_sensor)
+        .filter(|contacts| contacts.entity1 != elevator && contacts.entity2 != elevator)
+        .flat_map(|contacts| player::surface_normals(contacts, player, rotation))
+        .any(|normal| pushes.iter().any(|push| push.dot(normal) < PINCH_DOT))
+}
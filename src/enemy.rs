@@ -0,0 +1,326 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    cull::Cull,
+    difficulty::Difficulty,
+    gravity::GravityDirection,
+    health::{Damage, Health},
+    player::{self, Player},
+    pool::Pool,
+    projectile::{self, Projectile},
+    spawner,
+    state::{GameplaySet, InGame},
+};
+
+/// Chasers notice the player this close
+const DETECT_RADIUS: f32 = 600.;
+/// And give up once the player gets this far away
+const LOSE_RADIUS: f32 = 1000.;
+const CHASER_SPEED: f32 = 450.;
+/// Low enough that explosions and crashes knock them off course for a while
+const CHASER_ACCELERATION: f32 = 900.;
+const CHASER_RADIUS: f32 = 35.;
+/// Turrets only shoot at players this close
+const TURRET_RANGE: f32 = 1500.;
+/// Seconds between shots
+const TURRET_INTERVAL: f32 = 1.5;
+const TURRET_SIZE: f32 = 60.;
+const SHOT_SPEED: f32 = 900.;
+/// Upward speed the player bounces off a stomped enemy with
+const STOMP_BOUNCE: f32 = 800.;
+
+/// Hostile creatures placed in levels.
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EnemyDefeated>()
+            .add_systems(
+                FixedUpdate,
+                (spawner::trigger, spawner::emit)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(FixedUpdate, (detect, chase).chain().in_set(GameplaySet))
+            .add_systems(FixedUpdate, fire_turrets.in_set(GameplaySet))
+            .add_systems(FixedUpdate, contact.in_set(GameplaySet));
+    }
+}
+
+/// Anything that hurts the player on touch, and can be stomped on.
+#[derive(Component, Default)]
+pub(crate) struct Enemy;
+
+/// The kinds of enemy that spawners can emit.
+#[derive(Clone, Copy, Deserialize)]
+pub(crate) enum EnemyKind {
+    Chaser,
+    Turret,
+}
+
+/// Spawn an enemy of the given kind.
+pub(crate) fn spawn(commands: &mut Commands, kind: EnemyKind, position: Vec2) -> Entity {
+    match kind {
+        EnemyKind::Chaser => commands.spawn(make_chaser(position.x, position.y)).id(),
+        EnemyKind::Turret => commands.spawn(make_turret(position.x, position.y)).id(),
+    }
+}
+
+/// Sent when the player stomps on an enemy.
+#[derive(Event)]
+pub(crate) struct EnemyDefeated {
+    pub(crate) position: Vec2,
+}
+
+/// Hovers in place until the player comes close, then flies after them.
+#[derive(Component, Default)]
+pub(crate) struct Chaser {
+    target: Option<Entity>,
+}
+
+pub(crate) fn make_chaser(
+    x: f32,
+    y: f32,
+) -> (
+    SpriteBundle,
+    Enemy,
+    Chaser,
+    RigidBody,
+    Collider,
+    GravityScale,
+    LockedAxes,
+    Cull,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(330., 0.8, 0.5),
+                custom_size: Some(Vec2::splat(CHASER_RADIUS * 2.)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, 0.),
+            ..default()
+        },
+        Enemy,
+        Chaser::default(),
+        RigidBody::Dynamic,
+        Collider::circle(CHASER_RADIUS),
+        GravityScale(0.),
+        LockedAxes::ROTATION_LOCKED,
+        Cull::Sleep,
+        StateScoped(InGame),
+    )
+}
+
+/// Stays put and shoots at the player whenever it can see them.
+#[derive(Component)]
+pub(crate) struct Turret {
+    reload: Timer,
+}
+
+pub(crate) fn make_turret(
+    x: f32,
+    y: f32,
+) -> (
+    SpriteBundle,
+    Enemy,
+    Turret,
+    RigidBody,
+    Collider,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(0., 0.7, 0.35),
+                custom_size: Some(Vec2::splat(TURRET_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, 0.),
+            ..default()
+        },
+        Enemy,
+        Turret {
+            reload: Timer::from_seconds(TURRET_INTERVAL, TimerMode::Repeating),
+        },
+        RigidBody::Static,
+        Collider::rectangle(TURRET_SIZE, TURRET_SIZE),
+        StateScoped(InGame),
+    )
+}
+
+fn detect(
+    mut chasers: Query<(&mut Chaser, &Position)>,
+    players: Query<&Position, With<Player>>,
+    spatial_query: SpatialQuery,
+) {
+    for (mut chaser, position) in &mut chasers {
+        if let Some(target) = chaser.target {
+            let gone = players
+                .get(target)
+                .map_or(true, |player| player.distance(position.0) > LOSE_RADIUS);
+            if gone {
+                chaser.target = None;
+            }
+            continue;
+        }
+
+        chaser.target = spatial_query
+            .shape_intersections(
+                &Collider::circle(DETECT_RADIUS),
+                position.0,
+                0.,
+                SpatialQueryFilter::default(),
+            )
+            .into_iter()
+            .filter_map(|entity| Some((entity, players.get(entity).ok()?.distance(position.0))))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity);
+    }
+}
+
+/// Steer toward the target, or come to a stop without one. The change in
+/// velocity is capped so knockback isn't undone straight away.
+fn chase(
+    mut chasers: Query<(&Chaser, &Position, &mut LinearVelocity)>,
+    players: Query<&Position, With<Player>>,
+    difficulty: Res<Difficulty>,
+    time: Res<Time>,
+) {
+    let speed = difficulty.enemy_speed();
+    for (chaser, position, mut velocity) in &mut chasers {
+        let desired = chaser
+            .target
+            .and_then(|target| players.get(target).ok())
+            .map_or(Vec2::ZERO, |player| {
+                (player.0 - position.0).normalize_or_zero() * CHASER_SPEED * speed
+            });
+
+        let steer = (desired - velocity.0)
+            .clamp_length_max(CHASER_ACCELERATION * speed * time.delta_seconds());
+        // Leave resting chasers alone so they can sleep
+        if steer != Vec2::ZERO {
+            velocity.0 += steer;
+        }
+    }
+}
+
+/// Landing on an enemy defeats it, or hurts it if it has health, and bounces
+/// the player off. Any other contact hurts the player, by how much depends
+/// on the [`Difficulty`].
+fn contact(
+    mut started: EventReader<CollisionStarted>,
+    collisions: Res<Collisions>,
+    enemies: Query<(&Position, Has<Health>), With<Enemy>>,
+    mut players: Query<(&Transform, &mut LinearVelocity, &GravityDirection), With<Player>>,
+    mut defeated: EventWriter<EnemyDefeated>,
+    mut damage: EventWriter<Damage>,
+    difficulty: Res<Difficulty>,
+    mut commands: Commands,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (enemy, other) = if enemies.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok((position, tough)) = enemies.get(enemy) else {
+            continue;
+        };
+        let Ok((transform, mut velocity, gravity)) = players.get_mut(other) else {
+            continue;
+        };
+        let Some(contacts) = collisions.get(enemy, other) else {
+            continue;
+        };
+
+        let up = gravity.up();
+        if player::stands_on(contacts, other, transform.rotation, up) {
+            let rising = velocity.dot(up);
+            velocity.0 += up * (STOMP_BOUNCE - rising);
+
+            // Enemies with health take a few stomps
+            if tough {
+                damage.send(Damage {
+                    target: enemy,
+                    amount: 1,
+                    source: transform.translation.truncate(),
+                });
+                continue;
+            }
+
+            defeated.send(EnemyDefeated {
+                position: position.0,
+            });
+            commands.entity(enemy).despawn_recursive();
+        } else {
+            damage.send(Damage {
+                target: other,
+                amount: difficulty.enemy_damage(),
+                source: position.0,
+            });
+        }
+    }
+}
+
+fn fire_turrets(
+    mut turrets: Query<(Entity, &mut Turret, &Transform)>,
+    players: Query<(Entity, &Position), With<Player>>,
+    spatial_query: SpatialQuery,
+    sensors: Query<(), With<Sensor>>,
+    time: Res<Time>,
+    mut shots: ResMut<Pool<Projectile>>,
+    difficulty: Res<Difficulty>,
+    mut commands: Commands,
+) {
+    let speed = difficulty.enemy_speed();
+    for (turret, mut data, transform) in &mut turrets {
+        if !data
+            .reload
+            .tick(time.delta().mul_f32(speed))
+            .just_finished()
+        {
+            continue;
+        }
+
+        let origin = transform.translation.truncate();
+        // Aims at whoever is closest
+        let Some((player, target)) = players
+            .iter()
+            .min_by(|(_, a), (_, b)| a.distance(origin).total_cmp(&b.distance(origin)))
+        else {
+            continue;
+        };
+        if origin.distance(target.0) > TURRET_RANGE {
+            continue;
+        }
+        let Ok(dir) = Dir2::new(target.0 - origin) else {
+            continue;
+        };
+
+        // Only shoot when nothing but the player is in the way
+        let filter = SpatialQueryFilter::default().with_excluded_entities([turret]);
+        let sees_player = spatial_query
+            .cast_ray_predicate(origin, dir, TURRET_RANGE, true, filter, &|entity| {
+                !sensors.contains(entity)
+            })
+            .is_some_and(|hit| hit.entity == player);
+        if !sees_player {
+            continue;
+        }
+
+        shots.spawn(
+            &mut commands,
+            projectile::make_projectile(
+                turret,
+                origin + dir * TURRET_SIZE,
+                dir * SHOT_SPEED * speed,
+                difficulty.enemy_damage(),
+            ),
+        );
+    }
+}
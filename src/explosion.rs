@@ -0,0 +1,83 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    health::{Damage, Health},
+    kinematic::KinematicBody,
+    player::Player,
+    settings::GameplaySettings,
+};
+
+/// Speed given to a body right at the center of an explosion
+const STRENGTH: f32 = 1500.0;
+
+/// Sent when something explodes, pushing away nearby bodies.
+#[derive(Event)]
+pub(crate) struct Exploded {
+    pub(crate) position: Vec2,
+    pub(crate) radius: f32,
+}
+
+/// Pushes dynamic bodies and a kinematic player away from the blast, harder
+/// the closer they are. Speed they had heading into it is cancelled first, so
+/// a bomb going off under a falling player still launches them as high as one
+/// under a standing player, which is what makes bomb jumps reliable.
+pub(crate) fn push_bodies(
+    mut exploded: EventReader<Exploded>,
+    spatial_query: SpatialQuery,
+    mut bodies: Query<(
+        &RigidBody,
+        &Position,
+        &mut LinearVelocity,
+        Has<KinematicBody>,
+    )>,
+) {
+    for explosion in exploded.read() {
+        let hits = spatial_query.shape_intersections(
+            &Collider::circle(explosion.radius),
+            explosion.position,
+            0.0,
+            SpatialQueryFilter::default(),
+        );
+
+        for entity in hits {
+            let Ok((body, position, mut velocity, kinematic)) = bodies.get_mut(entity) else {
+                continue;
+            };
+
+            if !body.is_dynamic() && !kinematic {
+                continue;
+            }
+
+            let offset = position.0 - explosion.position;
+            let falloff = 1.0 - (offset.length() / explosion.radius).min(1.0);
+            let dir = offset.normalize_or_zero();
+            let against = velocity.dot(dir).min(0.0);
+            velocity.0 += dir * (STRENGTH * falloff - against);
+        }
+    }
+}
+
+/// Takes one health from everything caught in a blast, sparing the player
+/// unless bombs are set to hurt them.
+pub(crate) fn damage(
+    mut exploded: EventReader<Exploded>,
+    targets: Query<(Entity, &Position, Has<Player>), With<Health>>,
+    settings: Res<GameplaySettings>,
+    mut damage: EventWriter<Damage>,
+) {
+    for explosion in exploded.read() {
+        for (target, position, is_player) in &targets {
+            if is_player && !settings.bomb_self_damage {
+                continue;
+            }
+            if position.distance(explosion.position) < explosion.radius {
+                damage.send(Damage {
+                    target,
+                    amount: 1,
+                    source: explosion.position,
+                });
+            }
+        }
+    }
+}
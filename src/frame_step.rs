@@ -0,0 +1,88 @@
+use avian2d::prelude::*;
+use bevy::{app::FixedMain, color::palettes::css::LIME, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+/// F6 freezes the game, F7 then runs exactly one fixed tick. Collider
+/// outlines and contacts show while frozen.
+#[derive(Resource, Default)]
+pub(crate) struct FrameStep {
+    on: bool,
+    /// Ticks run since freezing
+    ticks: u32,
+    /// Whether gizmos were on and the contact colors, to go back to on
+    /// resuming
+    gizmos: Option<(bool, Option<Color>, Option<Color>)>,
+}
+
+pub(crate) fn toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut step: ResMut<FrameStep>,
+    mut time: ResMut<Time<Virtual>>,
+    mut store: ResMut<GizmoConfigStore>,
+) {
+    if !keys.just_pressed(KeyCode::F6) {
+        return;
+    }
+
+    step.on = !step.on;
+    step.ticks = 0;
+    let (config, gizmos) = store.config_mut::<PhysicsGizmos>();
+    if step.on {
+        time.pause();
+        step.gizmos = Some((
+            config.enabled,
+            gizmos.contact_point_color,
+            gizmos.contact_normal_color,
+        ));
+        config.enabled = true;
+        gizmos.contact_point_color = Some(LIME.into());
+        gizmos.contact_normal_color = Some(LIME.into());
+    } else {
+        time.unpause();
+        if let Some((enabled, point, normal)) = step.gizmos.take() {
+            config.enabled = enabled;
+            gizmos.contact_point_color = point;
+            gizmos.contact_normal_color = normal;
+        }
+    }
+}
+
+/// Run one tick of the fixed schedules by hand, the same way the main loop
+/// would if time had moved on by a timestep.
+pub(crate) fn advance(world: &mut World) {
+    if !world.resource::<FrameStep>().on
+        || !world
+            .resource::<ButtonInput<KeyCode>>()
+            .just_pressed(KeyCode::F7)
+    {
+        return;
+    }
+
+    let time = *world.resource::<Time>();
+    let mut fixed = world.resource_mut::<Time<Fixed>>();
+    let timestep = fixed.timestep();
+    fixed.advance_by(timestep);
+    *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+    world.run_schedule(FixedMain);
+    *world.resource_mut::<Time>() = time;
+
+    world.resource_mut::<FrameStep>().ticks += 1;
+}
+
+pub(crate) fn banner(mut contexts: EguiContexts, step: Res<FrameStep>) {
+    if !step.on {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("frame step"))
+        .anchor(egui::Align2::LEFT_BOTTOM, [10., -10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(
+                egui::RichText::new(format!(
+                    "FRAME STEP  tick {}  F7 steps, F6 resumes",
+                    step.ticks
+                ))
+                .strong(),
+            );
+        });
+}
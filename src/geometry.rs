@@ -0,0 +1,144 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{state::InGame, surface::SurfaceMaterial};
+
+/// Edges closer than this count as touching
+const EPSILON: f32 = 0.01;
+
+/// A compound collider standing in for several straight pieces of level
+/// geometry, which are still drawn one by one.
+#[derive(Component)]
+pub(crate) struct Merged {
+    /// What the pieces were merged into, one shape of the collider each
+    pub(crate) rects: Vec<Rect>,
+}
+
+/// One compound collider per material out of the given pieces, so sounds
+/// can still tell surfaces apart. Physics the same as
+/// [`make_cube`](crate::level::make_cube).
+pub(crate) fn spawn_merged(commands: &mut Commands, pieces: Vec<(SurfaceMaterial, Rect)>) {
+    let mut materials = Vec::new();
+    for (material, _) in &pieces {
+        if !materials.contains(material) {
+            materials.push(*material);
+        }
+    }
+
+    for material in materials {
+        let group = pieces
+            .iter()
+            .filter(|(of, _)| *of == material)
+            .map(|(_, rect)| *rect)
+            .collect::<Vec<_>>();
+        let count = group.len();
+        let rects = merge(group);
+        debug!(
+            "Merged {count} pieces of level geometry into {}",
+            rects.len()
+        );
+        let shapes = rects
+            .iter()
+            .map(|rect| {
+                let size = rect.size();
+                (
+                    Position(rect.center()),
+                    Rotation::default(),
+                    Collider::rectangle(size.x, size.y),
+                )
+            })
+            .collect();
+
+        commands.spawn((
+            TransformBundle::default(),
+            RigidBody::Static,
+            Collider::compound(shapes),
+            Friction::new(1.),
+            Restitution::new(0.).with_combine_rule(CoefficientCombine::Multiply),
+            material,
+            Merged { rects },
+            StateScoped(InGame),
+        ));
+    }
+}
+
+/// Greedily join any two rectangles that make up a rectangle together, until
+/// none are left that do.
+pub(crate) fn merge(mut rects: Vec<Rect>) -> Vec<Rect> {
+    'search: loop {
+        for i in 0..rects.len() {
+            for j in i + 1..rects.len() {
+                if let Some(joined) = join(rects[i], rects[j]) {
+                    rects[i] = joined;
+                    rects.swap_remove(j);
+                    continue 'search;
+                }
+            }
+        }
+        return rects;
+    }
+}
+
+fn join(a: Rect, b: Rect) -> Option<Rect> {
+    let close = |a: f32, b: f32| (a - b).abs() < EPSILON;
+    let spans = |a_min: f32, a_max: f32, b_min: f32, b_max: f32| {
+        a_min <= b_max + EPSILON && b_min <= a_max + EPSILON
+    };
+
+    let row = close(a.min.y, b.min.y)
+        && close(a.max.y, b.max.y)
+        && spans(a.min.x, a.max.x, b.min.x, b.max.x);
+    let column = close(a.min.x, b.min.x)
+        && close(a.max.x, b.max.x)
+        && spans(a.min.y, a.max.y, b.min.y, b.max.y);
+    let inside = |outer: Rect, inner: Rect| {
+        outer.inflate(EPSILON).contains(inner.min) && outer.inflate(EPSILON).contains(inner.max)
+    };
+
+    (row || column || inside(a, b) || inside(b, a)).then(|| a.union(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+        Rect::from_center_size(Vec2::new(x, y), Vec2::new(w, h))
+    }
+
+    #[test]
+    fn joins_a_row_and_a_column() {
+        let row = merge(vec![
+            rect(0., 0., 100., 50.),
+            rect(200., 0., 100., 50.),
+            rect(100., 0., 100., 50.),
+        ]);
+        assert_eq!(row, vec![rect(100., 0., 300., 50.)]);
+
+        let column = merge(vec![rect(0., 0., 50., 100.), rect(0., 100., 50., 100.)]);
+        assert_eq!(column, vec![rect(0., 50., 50., 200.)]);
+    }
+
+    #[test]
+    fn keeps_shapes_that_arent_rectangles() {
+        // An L, a step and a gap
+        assert_eq!(
+            merge(vec![rect(0., 0., 100., 50.), rect(25., 50., 50., 50.)]).len(),
+            2
+        );
+        assert_eq!(
+            merge(vec![rect(0., 0., 100., 50.), rect(100., 10., 100., 50.)]).len(),
+            2
+        );
+        assert_eq!(
+            merge(vec![rect(0., 0., 100., 50.), rect(110., 0., 100., 50.)]).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn swallows_pieces_inside_others() {
+        let merged = merge(vec![rect(0., 0., 400., 50.), rect(50., 0., 100., 20.)]);
+        assert_eq!(merged, vec![rect(0., 0., 400., 50.)]);
+    }
+}
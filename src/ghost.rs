@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atlas::AtlasSprite,
+    level::Levels,
+    mutators::Mutators,
+    player::{Player, Remote, PLAYER_RADIUS},
+    run::Run,
+    save::SaveData,
+    state::InGame,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const GHOST_DIR: &str = "ghosts";
+/// Seconds of run time between recorded positions
+const INTERVAL: f32 = 1. / 16.;
+
+/// Where the player was over a run, sampled at a fixed interval of run time.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub(crate) struct GhostTrack {
+    positions: Vec<Vec2>,
+}
+
+impl GhostTrack {
+    /// Position `seconds` into the run, blending between samples.
+    fn at(&self, seconds: f32) -> Option<Vec2> {
+        let t = seconds / INTERVAL;
+        let i = t.floor() as usize;
+        let a = self.positions.get(i).or(self.positions.last())?;
+        let b = self.positions.get(i + 1).unwrap_or(a);
+        Some(a.lerp(*b, t.fract()))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn path(level: &str) -> std::path::PathBuf {
+        std::path::Path::new(GHOST_DIR).join(level)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read(level: &str) -> Option<Self> {
+        let text = std::fs::read_to_string(Self::path(level)).ok()?;
+        ron::from_str(&text).ok()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write(&self, level: &str) {
+        let result = std::fs::create_dir_all(GHOST_DIR)
+            .map_err(|err| err.to_string())
+            .and_then(|_| ron::to_string(self).map_err(|err| err.to_string()))
+            .and_then(|text| {
+                std::fs::write(Self::path(level), text).map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            error!("Could not write ghost: {err}");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read(_level: &str) -> Option<Self> {
+        None
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn write(&self, _level: &str) {}
+}
+
+/// Replays the best run of the current level for the player to race.
+#[derive(Component)]
+pub(crate) struct Ghost(GhostTrack);
+
+/// Sample the player's position whenever the run timer passes an interval.
+pub(crate) fn record(
+    mut track: ResMut<GhostTrack>,
+    players: Query<&Transform, (With<Player>, Without<Remote>)>,
+    run: Res<Run>,
+) {
+    let Some(transform) = players.iter().next() else {
+        return;
+    };
+
+    // Starting a run over winds the timer back, and the track with it
+    let due = (run.time.elapsed_secs() / INTERVAL) as usize + 1;
+    if track.positions.len() > due {
+        track.positions.clear();
+    }
+    while track.positions.len() < due {
+        track.positions.push(transform.translation.truncate());
+    }
+}
+
+/// Keep the track of a new best run.
+pub(crate) fn save(
+    track: Res<GhostTrack>,
+    save: Res<SaveData>,
+    levels: Res<Levels>,
+    run: Res<Run>,
+    mutators: Res<Mutators>,
+) {
+    let Some(name) = levels.names.get(levels.current) else {
+        return;
+    };
+    let Some(progress) = save.levels.get(name) else {
+        return;
+    };
+
+    if run.time.elapsed_secs() <= progress.best_time && !mutators.speed_up() {
+        track.write(name);
+    }
+}
+
+pub(crate) fn spawn(mut commands: Commands, levels: Res<Levels>) {
+    let Some(track) = levels
+        .names
+        .get(levels.current)
+        .and_then(|name| GhostTrack::read(name))
+    else {
+        return;
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(0.6, 0.8, 1., 0.35),
+                custom_size: Some(Vec2::splat(PLAYER_RADIUS * 2.)),
+                ..default()
+            },
+            // Behind the player
+            transform: Transform::from_xyz(0., 0., -0.2),
+            ..default()
+        },
+        AtlasSprite("player"),
+        Ghost(track),
+        StateScoped(InGame),
+    ));
+}
+
+pub(crate) fn follow(mut ghosts: Query<(&Ghost, &mut Transform)>, run: Res<Run>) {
+    for (ghost, mut transform) in &mut ghosts {
+        if let Some(position) = ghost.0.at(run.time.elapsed_secs()) {
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    player::Player,
+    state::{AppState, InGame},
+};
+
+/// The end of a level, touching it completes the level.
+#[derive(Component, Default)]
+pub(crate) struct Goal;
+
+pub(crate) fn make_goal(
+    x: f32,
+    y: f32,
+) -> (SpriteBundle, Goal, Collider, Sensor, StateScoped<InGame>) {
+    let (w, h) = (60., 120.);
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(50., 1.0, 0.5),
+                custom_size: Some(Vec2::new(w, h)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, -0.5),
+            ..default()
+        },
+        Goal,
+        Collider::rectangle(w, h),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn reach(
+    mut started: EventReader<CollisionStarted>,
+    players: Query<(), With<Player>>,
+    goals: Query<(), With<Goal>>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let touched = (goals.contains(*a) && players.contains(*b))
+            || (goals.contains(*b) && players.contains(*a));
+
+        if touched {
+            next.set(AppState::LevelComplete);
+        }
+    }
+}
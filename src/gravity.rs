@@ -0,0 +1,243 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{kinematic::KinematicBody, level, player::Player, state::InGame};
+
+const ORB_RADIUS: f32 = 20.;
+
+/// Which way gravity pulls a body. Anything that cares which way is up, like
+/// standing on the ground or jumping, asks this instead of assuming +Y.
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum GravityDirection {
+    #[default]
+    Down,
+    Up,
+}
+
+impl GravityDirection {
+    /// Away from where gravity pulls
+    pub(crate) fn up(self) -> Vec2 {
+        match self {
+            GravityDirection::Down => Vec2::Y,
+            GravityDirection::Up => Vec2::NEG_Y,
+        }
+    }
+
+    pub(crate) fn flipped(self) -> Self {
+        match self {
+            GravityDirection::Down => GravityDirection::Up,
+            GravityDirection::Up => GravityDirection::Down,
+        }
+    }
+}
+
+/// Gravity points up for the player while inside.
+#[derive(Component)]
+pub(crate) struct FlipZone;
+
+/// Touching it turns the player's gravity around.
+#[derive(Component)]
+pub(crate) struct FlipOrb;
+
+/// Scales gravity for bodies inside, below 1 for floaty caves and above 1 for
+/// heavy zones.
+#[derive(Component)]
+pub(crate) struct GravityVolume {
+    scale: f32,
+}
+
+/// On a body inside a [`GravityVolume`], how strongly gravity pulls it there.
+#[derive(Component)]
+pub(crate) struct LocalGravity(f32);
+
+pub(crate) fn make_zone(
+    zone: &level::Zone,
+) -> (
+    SpriteBundle,
+    FlipZone,
+    Collider,
+    Sensor,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsla(270., 0.6, 0.5, 0.15),
+                custom_size: Some(Vec2::new(zone.w, zone.h)),
+                ..default()
+            },
+            transform: Transform::from_xyz(zone.x, zone.y, -1.),
+            ..default()
+        },
+        FlipZone,
+        Collider::rectangle(zone.w, zone.h),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn make_orb(
+    x: f32,
+    y: f32,
+) -> (SpriteBundle, FlipOrb, Collider, Sensor, StateScoped<InGame>) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(270., 0.8, 0.6),
+                custom_size: Some(Vec2::splat(ORB_RADIUS * 2.)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, 0.),
+            ..default()
+        },
+        FlipOrb,
+        Collider::circle(ORB_RADIUS),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn make_volume(
+    placed: &level::GravityVolume,
+) -> (
+    SpriteBundle,
+    GravityVolume,
+    Collider,
+    Sensor,
+    StateScoped<InGame>,
+) {
+    // Light blue for low gravity, red for heavy
+    let hue = if placed.scale < 1. { 200. } else { 0. };
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsla(hue, 0.7, 0.5, 0.15),
+                custom_size: Some(Vec2::new(placed.w, placed.h)),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, -1.),
+            ..default()
+        },
+        GravityVolume {
+            scale: placed.scale,
+        },
+        Collider::rectangle(placed.w, placed.h),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn enter_zones(
+    mut started: EventReader<CollisionStarted>,
+    mut ended: EventReader<CollisionEnded>,
+    zones: Query<(), With<FlipZone>>,
+    mut players: Query<&mut GravityDirection, With<Player>>,
+) {
+    let entered = started
+        .read()
+        .map(|CollisionStarted(a, b)| (*a, *b, GravityDirection::Up));
+    let left = ended
+        .read()
+        .map(|CollisionEnded(a, b)| (*a, *b, GravityDirection::Down));
+
+    for (a, b, direction) in entered.chain(left) {
+        let other = if zones.contains(a) {
+            b
+        } else if zones.contains(b) {
+            a
+        } else {
+            continue;
+        };
+
+        if let Ok(mut gravity) = players.get_mut(other) {
+            gravity.set_if_neq(direction);
+        }
+    }
+}
+
+pub(crate) fn touch_orbs(
+    mut started: EventReader<CollisionStarted>,
+    orbs: Query<(), With<FlipOrb>>,
+    mut players: Query<&mut GravityDirection, With<Player>>,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let other = if orbs.contains(*a) {
+            *b
+        } else if orbs.contains(*b) {
+            *a
+        } else {
+            continue;
+        };
+
+        if let Ok(mut gravity) = players.get_mut(other) {
+            *gravity = gravity.flipped();
+        }
+    }
+}
+
+pub(crate) fn enter_volumes(
+    mut started: EventReader<CollisionStarted>,
+    mut ended: EventReader<CollisionEnded>,
+    volumes: Query<&GravityVolume>,
+    bodies: Query<(
+        &RigidBody,
+        Option<&GravityScale>,
+        Option<&LocalGravity>,
+        Has<KinematicBody>,
+    )>,
+    mut commands: Commands,
+) {
+    let entered = started.read().map(|CollisionStarted(a, b)| (*a, *b, true));
+    let left = ended.read().map(|CollisionEnded(a, b)| (*a, *b, false));
+
+    for (a, b, inside) in entered.chain(left) {
+        let (volume, other) = if volumes.contains(a) { (a, b) } else { (b, a) };
+        let Ok(volume) = volumes.get(volume) else {
+            continue;
+        };
+        let Ok((body, scale, local, kinematic)) = bodies.get(other) else {
+            continue;
+        };
+
+        if inside {
+            // Projectiles and flying enemies ignore gravity wherever they are
+            if (body.is_dynamic() || kinematic) && scale.is_none_or(|scale| scale.0 != 0.) {
+                commands.entity(other).insert(LocalGravity(volume.scale));
+            }
+        } else if local.is_some_and(|local| local.0 == volume.scale) {
+            commands.entity(other).remove::<LocalGravity>();
+        }
+    }
+}
+
+/// Physics only has the one global gravity, so bodies that are flipped or in
+/// a volume get it scaled to make up the difference.
+pub(crate) fn apply(
+    bodies: Query<
+        (Entity, Option<&GravityDirection>, Option<&LocalGravity>),
+        Or<(Changed<GravityDirection>, Changed<LocalGravity>)>,
+    >,
+    directions: Query<&GravityDirection>,
+    mut left: RemovedComponents<LocalGravity>,
+    mut commands: Commands,
+) {
+    for (entity, direction, local) in &bodies {
+        let scale = gravity_scale(direction, local);
+        commands.entity(entity).insert(GravityScale(scale));
+    }
+
+    for entity in left.read() {
+        let scale = gravity_scale(directions.get(entity).ok(), None);
+        if let Some(mut body) = commands.get_entity(entity) {
+            body.insert(GravityScale(scale));
+        }
+    }
+}
+
+fn gravity_scale(direction: Option<&GravityDirection>, local: Option<&LocalGravity>) -> f32 {
+    let scale = local.map_or(1., |local| local.0);
+    match direction {
+        Some(GravityDirection::Up) => -scale,
+        _ => scale,
+    }
+}
@@ -0,0 +1,121 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    cutscene::Cutscene, kinematic::KinematicBody, player::Player, settings::AssistSettings,
+};
+
+/// Seconds after a hit during which further hits are ignored
+const INVULNERABLE: f32 = 1.0;
+/// Seconds after a hit during which the player can't steer
+const HITSTUN: f32 = 0.3;
+/// Speed a hit sends its target flying away with
+const KNOCKBACK: f32 = 700.;
+
+#[derive(Component, Clone)]
+pub(crate) struct Health {
+    pub(crate) current: u32,
+    pub(crate) max: u32,
+    invulnerable: Timer,
+}
+
+impl Health {
+    pub(crate) fn new(max: u32) -> Self {
+        let mut invulnerable = Timer::from_seconds(INVULNERABLE, TimerMode::Once);
+        invulnerable.tick(invulnerable.duration());
+
+        Self {
+            current: max,
+            max,
+            invulnerable,
+        }
+    }
+
+    pub(crate) fn is_invulnerable(&self) -> bool {
+        !self.invulnerable.finished()
+    }
+}
+
+/// Sent to hurt something. `source` is where the hit came from, the target
+/// is knocked away from it.
+#[derive(Event)]
+pub(crate) struct Damage {
+    pub(crate) target: Entity,
+    pub(crate) amount: u32,
+    pub(crate) source: Vec2,
+}
+
+/// The player takes no damage while on, for testing levels.
+#[derive(Resource, Default)]
+pub(crate) struct GodMode(pub(crate) bool);
+
+/// Briefly takes away control after being hit.
+#[derive(Component, Clone)]
+pub(crate) struct Hitstun(Timer);
+
+pub(crate) fn tick(
+    mut healths: Query<&mut Health>,
+    mut stunned: Query<(Entity, &mut Hitstun)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    // Skip idle timers, touching them would mark every Health as changed
+    for mut health in &mut healths {
+        if health.is_invulnerable() {
+            health.invulnerable.tick(time.delta());
+        }
+    }
+
+    for (entity, mut hitstun) in &mut stunned {
+        if hitstun.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Hitstun>();
+        }
+    }
+}
+
+/// Apply damage, knockback and hitstun, unless the target was hit recently.
+/// The player is spared while a cutscene has taken their controls away.
+pub(crate) fn apply_damage(
+    mut damage: EventReader<Damage>,
+    mut targets: Query<(
+        &mut Health,
+        &RigidBody,
+        &Position,
+        &mut LinearVelocity,
+        Has<Player>,
+        Has<KinematicBody>,
+    )>,
+    god: Res<GodMode>,
+    assist: Res<AssistSettings>,
+    cutscene: Res<Cutscene>,
+    mut commands: Commands,
+) {
+    for hit in damage.read() {
+        let Ok((mut health, body, position, mut velocity, is_player, kinematic)) =
+            targets.get_mut(hit.target)
+        else {
+            continue;
+        };
+        if health.is_invulnerable()
+            || (is_player && (god.0 || assist.invincible() || cutscene.is_locking()))
+        {
+            continue;
+        }
+
+        health.current = health.current.saturating_sub(hit.amount);
+        health.invulnerable.reset();
+
+        // Scripted bodies like bosses stand their ground
+        if !body.is_dynamic() && !kinematic {
+            continue;
+        }
+
+        // Always pop up a little so the target doesn't get pinned to the floor
+        let away = (position.0 - hit.source).normalize_or(Vec2::Y);
+        velocity.0 = (away + Vec2::Y * 0.5).normalize() * KNOCKBACK;
+
+        commands
+            .entity(hit.target)
+            .insert(Hitstun(Timer::from_seconds(HITSTUN, TimerMode::Once)));
+    }
+}
@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+
+use crate::{
+    checkpoint::SplitRecorded,
+    health::Health,
+    inventory::{self, Inventory, Stack},
+    level::Levels,
+    locale::Locale,
+    pickup::Coins,
+    player::{Hooks, Player, Remote, Slide},
+    run::{self, Run},
+    save::SaveData,
+    score::Score,
+    state::InGame,
+};
+
+const FONT_SIZE: f32 = 24.;
+/// Seconds a split stays on screen
+const SPLIT_DURATION: f32 = 3.;
+
+/// Health, coins, score, ability status and the run timer in the top left
+/// corner, and the hotbar along the bottom.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), setup).add_systems(
+            Update,
+            (
+                update_health,
+                update_coins,
+                update_score,
+                update_slide,
+                update_hook,
+                update_time,
+                update_hotbar,
+                show_split,
+            )
+                .run_if(in_state(InGame)),
+        );
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct HealthText;
+
+#[derive(Component)]
+pub(crate) struct CoinText;
+
+#[derive(Component)]
+pub(crate) struct ScoreText;
+
+#[derive(Component)]
+pub(crate) struct SlideText;
+
+#[derive(Component)]
+pub(crate) struct TimeText;
+
+#[derive(Component)]
+pub(crate) struct SplitText;
+
+#[derive(Component)]
+pub(crate) struct HookText;
+
+/// Shows what's in one slot of the inventory.
+#[derive(Component)]
+pub(crate) struct HotbarSlot(usize);
+
+fn hud_text(text: &str) -> TextBundle {
+    TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: FONT_SIZE,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+fn setup(mut commands: Commands, locale: Res<Locale>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.),
+                    left: Val::Px(10.),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn((hud_text(""), HealthText));
+            parent.spawn((hud_text(""), CoinText));
+            parent.spawn((hud_text(""), ScoreText));
+            parent.spawn((hud_text(locale.get("hud-slide-ready")), SlideText));
+            parent.spawn((hud_text(locale.get("hud-hook-ready")), HookText));
+            parent.spawn((hud_text(""), TimeText));
+            parent.spawn((hud_text(""), SplitText));
+        });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(10.),
+                    width: Val::Percent(100.),
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(8.),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(InGame),
+        ))
+        .with_children(|parent| {
+            for slot in 0..inventory::SLOTS {
+                parent.spawn((
+                    hud_text("").with_style(Style {
+                        padding: UiRect::all(Val::Px(6.)),
+                        ..default()
+                    }),
+                    HotbarSlot(slot),
+                ));
+            }
+        });
+}
+
+fn update_health(
+    health: Query<&Health, (With<Player>, Without<Remote>, Changed<Health>)>,
+    mut text: Query<&mut Text, With<HealthText>>,
+    locale: Res<Locale>,
+) {
+    let Some(health) = health.iter().next() else {
+        return;
+    };
+
+    for mut text in &mut text {
+        text.sections[0].value = locale.format(
+            "hud-health",
+            &[("current", &health.current), ("max", &health.max)],
+        );
+    }
+}
+
+fn update_coins(
+    coins: Res<Coins>,
+    mut text: Query<&mut Text, With<CoinText>>,
+    locale: Res<Locale>,
+) {
+    if !coins.is_changed() {
+        return;
+    }
+
+    for mut text in &mut text {
+        text.sections[0].value = locale.format("hud-coins", &[("count", &coins.0)]);
+    }
+}
+
+fn update_score(
+    score: Res<Score>,
+    mut text: Query<&mut Text, With<ScoreText>>,
+    locale: Res<Locale>,
+) {
+    if !score.is_changed() {
+        return;
+    }
+
+    let value = if score.combo > 1 {
+        locale.format(
+            "hud-score-combo",
+            &[("points", &score.points), ("combo", &score.combo)],
+        )
+    } else {
+        locale.format("hud-score", &[("points", &score.points)])
+    };
+
+    for mut text in &mut text {
+        text.sections[0].value.clone_from(&value);
+    }
+}
+
+fn update_slide(
+    slide: Query<&Slide, (Without<Remote>, Changed<Slide>)>,
+    mut text: Query<&mut Text, With<SlideText>>,
+    locale: Res<Locale>,
+) {
+    let Some(slide) = slide.iter().next() else {
+        return;
+    };
+
+    let value = if slide.cooldown.finished() {
+        locale.get("hud-slide-ready").to_string()
+    } else {
+        let seconds = format!("{:.1}", slide.cooldown.remaining_secs());
+        locale.format("hud-slide-cooldown", &[("seconds", &seconds)])
+    };
+
+    for mut text in &mut text {
+        text.sections[0].value.clone_from(&value);
+    }
+}
+
+fn update_hook(
+    players: Query<&Hooks, (With<Player>, Without<Remote>, Changed<Hooks>)>,
+    mut text: Query<&mut Text, With<HookText>>,
+    locale: Res<Locale>,
+) {
+    let Some(hooks) = players.iter().next() else {
+        return;
+    };
+
+    let value = locale.get(match hooks.0.len() {
+        0 => "hud-hook-ready",
+        1 => "hud-hook-attached",
+        _ => "hud-hooks-attached",
+    });
+
+    // The ropes change every tick while wrapping, the text rarely does
+    for mut text in &mut text {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.to_string();
+        }
+    }
+}
+
+fn update_hotbar(
+    players: Query<&Inventory, (With<Player>, Without<Remote>, Changed<Inventory>)>,
+    mut slots: Query<(&HotbarSlot, &mut Text, &mut BackgroundColor)>,
+    locale: Res<Locale>,
+) {
+    let Some(inventory) = players.iter().next() else {
+        return;
+    };
+
+    for (slot, mut text, mut background) in &mut slots {
+        let key = slot.0 + 1;
+        text.sections[0].value = match inventory.slots[slot.0] {
+            Some(Stack {
+                item,
+                count: Some(count),
+            }) => format!("{key} {} x{count}", locale.get(item.message())),
+            Some(Stack { item, count: None }) => format!("{key} {}", locale.get(item.message())),
+            None => key.to_string(),
+        };
+        background.0 = if slot.0 == inventory.selected {
+            Color::srgba(1., 1., 1., 0.3)
+        } else {
+            Color::srgba(0., 0., 0., 0.4)
+        };
+    }
+}
+
+fn update_time(run: Res<Run>, mut text: Query<&mut Text, With<TimeText>>, locale: Res<Locale>) {
+    if !run.is_changed() {
+        return;
+    }
+
+    for mut text in &mut text {
+        let time = run::format_time(run.time.elapsed_secs());
+        text.sections[0].value = locale.format("hud-time", &[("time", &time)]);
+    }
+}
+
+/// Briefly show each split, compared against the best run's.
+fn show_split(
+    mut splits: EventReader<SplitRecorded>,
+    mut text: Query<&mut Text, With<SplitText>>,
+    save: Res<SaveData>,
+    levels: Res<Levels>,
+    time: Res<Time>,
+    locale: Res<Locale>,
+    mut hide: Local<Option<Timer>>,
+) {
+    let best_splits = levels
+        .names
+        .get(levels.current)
+        .and_then(|name| save.levels.get(name))
+        .map(|progress| progress.best_splits.as_slice())
+        .unwrap_or_default();
+
+    for split in splits.read() {
+        let mut value = locale.format(
+            "hud-split",
+            &[
+                ("index", &(split.index + 1)),
+                ("time", &run::format_time(split.time)),
+            ],
+        );
+        if let Some(best) = best_splits.get(split.index).copied().flatten() {
+            value += &format!("  ({})", run::format_delta(split.time - best));
+        }
+
+        for mut text in &mut text {
+            text.sections[0].value.clone_from(&value);
+        }
+        *hide = Some(Timer::from_seconds(SPLIT_DURATION, TimerMode::Once));
+    }
+
+    if let Some(timer) = hide.as_mut() {
+        if timer.tick(time.delta()).finished() {
+            for mut text in &mut text {
+                text.sections[0].value.clear();
+            }
+            *hide = None;
+        }
+    }
+}
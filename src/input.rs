@@ -0,0 +1,900 @@
+use std::time::Duration;
+
+use avian2d::prelude::*;
+use bevy::{
+    ecs::system::SystemParam,
+    input::gamepad::{
+        GamepadConnection, GamepadConnectionEvent, GamepadRumbleIntensity, GamepadRumbleRequest,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explosion::Exploded,
+    health::Hitstun,
+    inventory,
+    locale::Locale,
+    player::{HookAttached, Landed, Player, Remote},
+    state::InputSet,
+};
+
+/// How many bindings each action can have
+pub(crate) const SLOTS: usize = 3;
+/// Stick deflection needed before it moves the player
+const MOVE_DEADZONE: f32 = 0.2;
+/// Stick deflection needed before it aims, so letting go doesn't flick the aim
+const AIM_DEADZONE: f32 = 0.5;
+/// Landings slower than this don't rumble
+const MIN_LAND_SPEED: f32 = 300.;
+/// Landing speed that rumbles at full strength
+const HARD_LANDING: f32 = 1500.;
+/// Swinging speed when the hook catches that rumbles at full strength
+const HARD_CATCH: f32 = 1500.;
+/// Explosions further than this many of their radius away aren't felt
+const FELT_EXPLOSION: f32 = 3.;
+
+/// Turns keyboard, mouse and gamepad input into [`Action`]s.
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<ActiveGamepad>()
+            .init_resource::<InputDevice>()
+            .init_resource::<HoldSettings>()
+            .init_resource::<RumbleSettings>()
+            .add_event::<Rumble>()
+            .configure_sets(
+                PreUpdate,
+                ActionSources
+                    .after(begin_actions)
+                    .before(end_actions)
+                    .in_set(InputSet),
+            )
+            .configure_sets(
+                PreUpdate,
+                ActionFilters
+                    .after(ActionSources)
+                    .before(end_actions)
+                    .in_set(InputSet),
+            )
+            .add_systems(
+                PreUpdate,
+                (
+                    hot_plug.before(ActionSources),
+                    begin_actions,
+                    (read_bindings, read_sticks).chain().in_set(ActionSources),
+                    apply_holds.after(ActionSources).before(ActionFilters),
+                    end_actions,
+                    track_device,
+                )
+                    .in_set(InputSet),
+            )
+            .add_systems(FixedLast, clear_latched)
+            .add_systems(
+                Update,
+                (
+                    (landing_rumble, hook_rumble, damage_rumble, explosion_rumble),
+                    rumble,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Systems that feed [`ActionState`] by calling [`ActionState::hold`] and
+/// friends. Anything that can drive the player each frame (touch) goes here,
+/// replays overwrite the whole state per fixed tick with [`ActionFrame`]s.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ActionSources;
+
+/// Systems that take input away from the player after the sources are read,
+/// see [`ActionState::block`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ActionFilters;
+
+/// Something the player can do that has a configurable binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub(crate) enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Slide,
+    Hook,
+    Bomb,
+    /// Reels in an object the hook caught
+    Yank,
+    /// Fires a hook of its own alongside the first
+    SecondHook,
+    /// Pick a hotbar slot for the bomb button to use
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Dash,
+    /// Talk to someone nearby
+    Interact,
+    /// Held to use the debug tools below
+    DebugModifier,
+    DebugPlace,
+    DebugTeleport,
+}
+
+impl Action {
+    pub(crate) const ALL: [Action; 18] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Slide,
+        Action::Hook,
+        Action::Bomb,
+        Action::Yank,
+        Action::SecondHook,
+        Action::Hotbar1,
+        Action::Hotbar2,
+        Action::Hotbar3,
+        Action::Hotbar4,
+        Action::Hotbar5,
+        Action::Dash,
+        Action::Interact,
+        Action::DebugModifier,
+        Action::DebugPlace,
+        Action::DebugTeleport,
+    ];
+
+    /// The hotbar slots in order, one per [`inventory::SLOTS`]
+    pub(crate) const HOTBAR: [Action; inventory::SLOTS] = [
+        Action::Hotbar1,
+        Action::Hotbar2,
+        Action::Hotbar3,
+        Action::Hotbar4,
+        Action::Hotbar5,
+    ];
+
+    /// Id of the action's name in the locale files.
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "action-move-left",
+            Action::MoveRight => "action-move-right",
+            Action::Jump => "action-jump",
+            Action::Slide => "action-slide",
+            Action::Hook => "action-hook",
+            Action::Bomb => "action-bomb",
+            Action::Yank => "action-yank",
+            Action::SecondHook => "action-second-hook",
+            Action::Hotbar1 => "action-hotbar-1",
+            Action::Hotbar2 => "action-hotbar-2",
+            Action::Hotbar3 => "action-hotbar-3",
+            Action::Hotbar4 => "action-hotbar-4",
+            Action::Hotbar5 => "action-hotbar-5",
+            Action::Dash => "action-dash",
+            Action::Interact => "action-interact",
+            Action::DebugModifier => "action-debug-modifier",
+            Action::DebugPlace => "action-debug-place",
+            Action::DebugTeleport => "action-debug-teleport",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on whichever gamepad is active
+    Pad(GamepadButtonType),
+}
+
+impl Binding {
+    pub(crate) fn name(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Mouse(button) => format!("Mouse {button:?}"),
+            Binding::Pad(button) => format!("Pad {button:?}"),
+        }
+    }
+
+    /// Short label for on-screen prompts in the player's language, with pad
+    /// buttons named the way they're printed on the controller.
+    pub(crate) fn prompt(self, locale: &Locale) -> String {
+        use GamepadButtonType as Pad;
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name
+                    .strip_prefix("Key")
+                    .or(name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                // Letters and digits read the same in any language
+                locale
+                    .find(&format!("key-{}", name.to_lowercase()))
+                    .unwrap_or(name)
+                    .to_string()
+            }
+            Binding::Mouse(MouseButton::Left) => locale.get("key-lmb").to_string(),
+            Binding::Mouse(MouseButton::Right) => locale.get("key-rmb").to_string(),
+            Binding::Mouse(MouseButton::Middle) => locale.get("key-mmb").to_string(),
+            Binding::Mouse(button) => {
+                locale.format("key-mouse", &[("button", &format!("{button:?}"))])
+            }
+            Binding::Pad(button) => match button {
+                Pad::South => "A".to_string(),
+                Pad::East => "B".to_string(),
+                Pad::West => "X".to_string(),
+                Pad::North => "Y".to_string(),
+                Pad::LeftTrigger => "LB".to_string(),
+                Pad::RightTrigger => "RB".to_string(),
+                Pad::LeftTrigger2 => "LT".to_string(),
+                Pad::RightTrigger2 => "RT".to_string(),
+                Pad::LeftThumb => "L3".to_string(),
+                Pad::RightThumb => "R3".to_string(),
+                Pad::DPadUp => locale.get("key-dpad-up").to_string(),
+                Pad::DPadDown => locale.get("key-dpad-down").to_string(),
+                Pad::DPadLeft => locale.get("key-dpad-left").to_string(),
+                Pad::DPadRight => locale.get("key-dpad-right").to_string(),
+                button => format!("{button:?}"),
+            },
+        }
+    }
+
+    /// Whether the binding is on `device`, mouse buttons count as keyboard.
+    pub(crate) fn is_on(self, device: InputDevice) -> bool {
+        matches!(self, Binding::Pad(_)) == (device == InputDevice::Gamepad)
+    }
+
+    fn pressed(self, buttons: &Buttons) -> bool {
+        match self {
+            Binding::Key(key) => buttons.keys.pressed(key),
+            Binding::Mouse(button) => buttons.mouse.pressed(button),
+            Binding::Pad(button) => buttons
+                .pad_button(button)
+                .is_some_and(|button| buttons.pads.pressed(button)),
+        }
+    }
+}
+
+/// Every source of button presses a binding can refer to.
+#[derive(SystemParam)]
+pub(crate) struct Buttons<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    pads: Res<'w, ButtonInput<GamepadButton>>,
+    active: Res<'w, ActiveGamepad>,
+}
+
+impl Buttons<'_> {
+    fn pad_button(&self, button: GamepadButtonType) -> Option<GamepadButton> {
+        Some(GamepadButton::new(self.active.0?, button))
+    }
+}
+
+/// Up to three bindings for each action, any of them triggers it. The third
+/// is meant for a gamepad button.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct KeyBindings {
+    pub(crate) move_left: [Option<Binding>; SLOTS],
+    pub(crate) move_right: [Option<Binding>; SLOTS],
+    pub(crate) jump: [Option<Binding>; SLOTS],
+    pub(crate) slide: [Option<Binding>; SLOTS],
+    pub(crate) hook: [Option<Binding>; SLOTS],
+    pub(crate) bomb: [Option<Binding>; SLOTS],
+    pub(crate) yank: [Option<Binding>; SLOTS],
+    pub(crate) second_hook: [Option<Binding>; SLOTS],
+    pub(crate) hotbar_1: [Option<Binding>; SLOTS],
+    pub(crate) hotbar_2: [Option<Binding>; SLOTS],
+    pub(crate) hotbar_3: [Option<Binding>; SLOTS],
+    pub(crate) hotbar_4: [Option<Binding>; SLOTS],
+    pub(crate) hotbar_5: [Option<Binding>; SLOTS],
+    pub(crate) dash: [Option<Binding>; SLOTS],
+    pub(crate) interact: [Option<Binding>; SLOTS],
+    pub(crate) debug_modifier: [Option<Binding>; SLOTS],
+    pub(crate) debug_place: [Option<Binding>; SLOTS],
+    pub(crate) debug_teleport: [Option<Binding>; SLOTS],
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use Binding::*;
+        use GamepadButtonType as Pad;
+
+        Self {
+            move_left: [
+                Some(Key(KeyCode::KeyA)),
+                Some(Key(KeyCode::ArrowLeft)),
+                Some(Binding::Pad(Pad::DPadLeft)),
+            ],
+            move_right: [
+                Some(Key(KeyCode::KeyD)),
+                Some(Key(KeyCode::ArrowRight)),
+                Some(Binding::Pad(Pad::DPadRight)),
+            ],
+            jump: [
+                Some(Key(KeyCode::Space)),
+                None,
+                Some(Binding::Pad(Pad::South)),
+            ],
+            slide: [
+                Some(Key(KeyCode::ShiftLeft)),
+                None,
+                Some(Binding::Pad(Pad::West)),
+            ],
+            hook: [
+                Some(Mouse(MouseButton::Right)),
+                None,
+                Some(Binding::Pad(Pad::RightTrigger2)),
+            ],
+            bomb: [
+                Some(Key(KeyCode::KeyQ)),
+                None,
+                Some(Binding::Pad(Pad::RightTrigger)),
+            ],
+            yank: [
+                Some(Key(KeyCode::KeyE)),
+                None,
+                Some(Binding::Pad(Pad::LeftTrigger2)),
+            ],
+            // Left click places cubes in debug mode
+            second_hook: [
+                Some(Key(KeyCode::KeyR)),
+                Some(Mouse(MouseButton::Back)),
+                Some(Binding::Pad(Pad::LeftTrigger)),
+            ],
+            hotbar_1: [
+                Some(Key(KeyCode::Digit1)),
+                None,
+                Some(Binding::Pad(Pad::DPadDown)),
+            ],
+            hotbar_2: [
+                Some(Key(KeyCode::Digit2)),
+                None,
+                Some(Binding::Pad(Pad::DPadUp)),
+            ],
+            hotbar_3: [
+                Some(Key(KeyCode::Digit3)),
+                None,
+                Some(Binding::Pad(Pad::North)),
+            ],
+            hotbar_4: [
+                Some(Key(KeyCode::Digit4)),
+                None,
+                Some(Binding::Pad(Pad::East)),
+            ],
+            hotbar_5: [Some(Key(KeyCode::Digit5)), None, None],
+            dash: [
+                Some(Key(KeyCode::KeyF)),
+                None,
+                Some(Binding::Pad(Pad::RightThumb)),
+            ],
+            // Pushing the left stick up counts too, see `read_sticks`
+            interact: [Some(Key(KeyCode::KeyW)), Some(Key(KeyCode::ArrowUp)), None],
+            debug_modifier: [Some(Key(KeyCode::ControlLeft)), None, None],
+            debug_place: [Some(Mouse(MouseButton::Left)), None, None],
+            debug_teleport: [Some(Mouse(MouseButton::Right)), None, None],
+        }
+    }
+}
+
+impl KeyBindings {
+    pub(crate) fn get(&self, action: Action) -> &[Option<Binding>; SLOTS] {
+        match action {
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::Jump => &self.jump,
+            Action::Slide => &self.slide,
+            Action::Hook => &self.hook,
+            Action::Bomb => &self.bomb,
+            Action::Yank => &self.yank,
+            Action::SecondHook => &self.second_hook,
+            Action::Hotbar1 => &self.hotbar_1,
+            Action::Hotbar2 => &self.hotbar_2,
+            Action::Hotbar3 => &self.hotbar_3,
+            Action::Hotbar4 => &self.hotbar_4,
+            Action::Hotbar5 => &self.hotbar_5,
+            Action::Dash => &self.dash,
+            Action::Interact => &self.interact,
+            Action::DebugModifier => &self.debug_modifier,
+            Action::DebugPlace => &self.debug_place,
+            Action::DebugTeleport => &self.debug_teleport,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, action: Action) -> &mut [Option<Binding>; SLOTS] {
+        match action {
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Jump => &mut self.jump,
+            Action::Slide => &mut self.slide,
+            Action::Hook => &mut self.hook,
+            Action::Bomb => &mut self.bomb,
+            Action::Yank => &mut self.yank,
+            Action::SecondHook => &mut self.second_hook,
+            Action::Hotbar1 => &mut self.hotbar_1,
+            Action::Hotbar2 => &mut self.hotbar_2,
+            Action::Hotbar3 => &mut self.hotbar_3,
+            Action::Hotbar4 => &mut self.hotbar_4,
+            Action::Hotbar5 => &mut self.hotbar_5,
+            Action::Dash => &mut self.dash,
+            Action::Interact => &mut self.interact,
+            Action::DebugModifier => &mut self.debug_modifier,
+            Action::DebugPlace => &mut self.debug_place,
+            Action::DebugTeleport => &mut self.debug_teleport,
+        }
+    }
+
+    fn pressed(&self, action: Action, buttons: &Buttons) -> bool {
+        self.get(action)
+            .iter()
+            .flatten()
+            .any(|binding| binding.pressed(buttons))
+    }
+}
+
+/// Ways to play without keeping buttons held down.
+#[derive(Resource, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct HoldSettings {
+    /// Tap slide to start sliding and again to stop early
+    pub(crate) toggle_slide: bool,
+    /// Tap a hook button to fire and again to let go
+    pub(crate) toggle_hook: bool,
+    /// A tap of the debug modifier stays down until the next debug action,
+    /// like the sticky keys of most desktops
+    pub(crate) sticky_debug_modifier: bool,
+}
+
+/// How the gamepad vibrates in response to what happens in the game.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct RumbleSettings {
+    pub(crate) enabled: bool,
+    /// Scales every rumble, 0.0 to 1.0
+    pub(crate) strength: f32,
+}
+
+impl Default for RumbleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            strength: 1.,
+        }
+    }
+}
+
+/// What the player last used, for showing prompts that match it.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum InputDevice {
+    #[default]
+    KeyboardMouse,
+    Gamepad,
+}
+
+/// The gamepad that pad bindings and the sticks are read from, the first one
+/// connected.
+#[derive(Resource, Default)]
+pub(crate) struct ActiveGamepad(pub(crate) Option<Gamepad>);
+
+/// What the player is doing this frame, merged from every input source.
+///
+/// Gameplay in `FixedUpdate` uses [`just_pressed`](Self::just_pressed), which
+/// is latched until a tick has run so a frame with several ticks doesn't see
+/// a press twice and a frame without any doesn't drop it. Systems in `Update`
+/// use [`just_pressed_this_frame`](Self::just_pressed_this_frame) instead.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct ActionState {
+    held: [bool; Action::ALL.len()],
+    previous: [bool; Action::ALL.len()],
+    latched: [bool; Action::ALL.len()],
+    /// What the sources held last frame, before [`HoldSettings`] were applied
+    sourced: [bool; Action::ALL.len()],
+    /// Actions tapped on and not yet tapped off, see [`HoldSettings`]
+    toggled: [bool; Action::ALL.len()],
+    /// Analog stick position, overrides the move buttons when pushed
+    stick: Option<f32>,
+    /// Direction the hook and bombs are aimed with a stick
+    aim: Option<Vec2>,
+}
+
+impl ActionState {
+    /// Marks `action` as held this frame, for use by [`ActionSources`].
+    pub(crate) fn hold(&mut self, action: Action) {
+        self.held[action as usize] = true;
+    }
+
+    /// Drops everything held this frame, for use by [`ActionFilters`].
+    pub(crate) fn block(&mut self) {
+        self.held = default();
+        self.toggled = default();
+        self.stick = None;
+        self.aim = None;
+    }
+
+    /// Sets analog horizontal movement, for use by [`ActionSources`].
+    pub(crate) fn set_stick(&mut self, x: f32) {
+        self.stick = Some(x.clamp(-1., 1.));
+    }
+
+    /// Sets the aim direction, for use by [`ActionSources`].
+    pub(crate) fn set_aim(&mut self, direction: Vec2) {
+        self.aim = Some(direction.normalize_or_zero()).filter(|aim| *aim != Vec2::ZERO);
+    }
+
+    /// Swaps left and right, for use by [`ActionFilters`].
+    pub(crate) fn mirror(&mut self) {
+        self.held
+            .swap(Action::MoveLeft as usize, Action::MoveRight as usize);
+        self.stick = self.stick.map(|x| -x);
+        self.aim = self.aim.map(|aim| Vec2::new(-aim.x, aim.y));
+    }
+
+    /// Lets go of a toggled action, for gameplay that ends on its own while
+    /// the button would still be held.
+    pub(crate) fn release(&mut self, action: Action) {
+        self.toggled[action as usize] = false;
+    }
+
+    /// Whether the sources started holding `action` this frame, and
+    /// remembers what they held for the next one.
+    fn tapped(&mut self, action: Action) -> bool {
+        let i = action as usize;
+        let tapped = self.held[i] && !self.sourced[i];
+        self.sourced[i] = self.held[i];
+        tapped
+    }
+
+    /// Holds `action` from one tap to the next instead of while pressed.
+    fn toggle(&mut self, action: Action) {
+        let i = action as usize;
+        if self.tapped(action) {
+            self.toggled[i] = !self.toggled[i];
+        }
+        self.held[i] = self.toggled[i];
+    }
+
+    /// Keeps `modifier` held after a tap until one of `uses` is pressed, or
+    /// it's tapped again. Holding it down works as usual.
+    fn stick_down(&mut self, modifier: Action, uses: &[Action]) {
+        let i = modifier as usize;
+        if self.tapped(modifier) {
+            self.toggled[i] = !self.toggled[i];
+        } else if self.toggled[i]
+            && uses
+                .iter()
+                .any(|&action| self.just_pressed_this_frame(action))
+        {
+            // Still held for this frame, so the action sees it
+            self.toggled[i] = false;
+            self.held[i] = true;
+        }
+        self.held[i] |= self.toggled[i];
+    }
+
+    pub(crate) fn pressed(&self, action: Action) -> bool {
+        self.held[action as usize]
+    }
+
+    /// Pressed since the last fixed tick.
+    pub(crate) fn just_pressed(&self, action: Action) -> bool {
+        self.latched[action as usize]
+    }
+
+    pub(crate) fn just_pressed_this_frame(&self, action: Action) -> bool {
+        self.held[action as usize] && !self.previous[action as usize]
+    }
+
+    /// Horizontal movement from -1 (left) to 1 (right).
+    pub(crate) fn move_x(&self) -> f32 {
+        if let Some(x) = self.stick {
+            return x;
+        }
+
+        let left = self.pressed(Action::MoveLeft) as i32 as f32;
+        let right = self.pressed(Action::MoveRight) as i32 as f32;
+        right - left
+    }
+
+    /// Aim direction from a stick, `None` means aim at the mouse cursor.
+    pub(crate) fn aim(&self) -> Option<Vec2> {
+        self.aim
+    }
+
+    /// Everything gameplay can read right now, to be played back later.
+    pub(crate) fn frame(&self) -> ActionFrame {
+        let bits = |actions: &[bool; Action::ALL.len()]| {
+            actions
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (i, &on)| bits | (on as u32) << i)
+        };
+
+        ActionFrame {
+            held: bits(&self.held),
+            latched: bits(&self.latched),
+            stick: self.stick,
+            aim: self.aim,
+        }
+    }
+
+    /// Replace the state with a recorded frame.
+    pub(crate) fn play(&mut self, frame: &ActionFrame) {
+        for action in Action::ALL {
+            let i = action as usize;
+            self.held[i] = frame.held & (1 << i) != 0;
+            self.latched[i] = frame.latched & (1 << i) != 0;
+        }
+        self.stick = frame.stick;
+        self.aim = frame.aim;
+    }
+}
+
+/// A compact copy of [`ActionState`] for one fixed tick, with one bit per
+/// action.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ActionFrame {
+    held: u32,
+    latched: u32,
+    stick: Option<f32>,
+    aim: Option<Vec2>,
+}
+
+impl ActionFrame {
+    /// The same buttons still held a tick later, without pressing any again.
+    pub(crate) fn repeated(&self) -> Self {
+        Self {
+            latched: 0,
+            ..*self
+        }
+    }
+}
+
+fn begin_actions(mut state: ResMut<ActionState>) {
+    state.previous = state.held;
+    state.held = default();
+    state.stick = None;
+    state.aim = None;
+}
+
+fn end_actions(mut state: ResMut<ActionState>) {
+    for action in Action::ALL {
+        if state.just_pressed_this_frame(action) {
+            state.latched[action as usize] = true;
+        }
+    }
+}
+
+fn clear_latched(mut state: ResMut<ActionState>) {
+    state.latched = default();
+}
+
+fn read_bindings(mut state: ResMut<ActionState>, bindings: Res<KeyBindings>, buttons: Buttons) {
+    for action in Action::ALL {
+        if bindings.pressed(action, &buttons) {
+            state.hold(action);
+        }
+    }
+}
+
+fn apply_holds(mut state: ResMut<ActionState>, settings: Res<HoldSettings>) {
+    if settings.toggle_slide {
+        state.toggle(Action::Slide);
+    }
+    if settings.toggle_hook {
+        state.toggle(Action::Hook);
+        state.toggle(Action::SecondHook);
+    }
+    if settings.sticky_debug_modifier {
+        state.stick_down(
+            Action::DebugModifier,
+            &[Action::DebugPlace, Action::DebugTeleport],
+        );
+    }
+}
+
+fn stick(
+    axes: &Axis<GamepadAxis>,
+    gamepad: Gamepad,
+    x: GamepadAxisType,
+    y: GamepadAxisType,
+) -> Vec2 {
+    Vec2::new(
+        axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.),
+        axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.),
+    )
+}
+
+fn read_sticks(
+    mut state: ResMut<ActionState>,
+    active: Res<ActiveGamepad>,
+    axes: Res<Axis<GamepadAxis>>,
+) {
+    let Some(gamepad) = active.0 else {
+        return;
+    };
+
+    let left = stick(
+        &axes,
+        gamepad,
+        GamepadAxisType::LeftStickX,
+        GamepadAxisType::LeftStickY,
+    );
+    let right = stick(
+        &axes,
+        gamepad,
+        GamepadAxisType::RightStickX,
+        GamepadAxisType::RightStickY,
+    );
+
+    if left.x.abs() > MOVE_DEADZONE {
+        state.set_stick(left.x);
+    }
+    // Up on the stick is up, there's no button to spare for it
+    if left.y > AIM_DEADZONE && left.y > left.x.abs() {
+        state.hold(Action::Interact);
+    }
+
+    // The right stick aims, or the left one while holding a hook button
+    let hooking = state.pressed(Action::Hook) || state.pressed(Action::SecondHook);
+    if right.length() > AIM_DEADZONE {
+        state.set_aim(right);
+    } else if hooking && left.length() > AIM_DEADZONE {
+        state.set_aim(left);
+    }
+}
+
+/// Switches [`InputDevice`] to whatever was pressed or pushed last.
+fn track_device(
+    mut device: ResMut<InputDevice>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    pads: Res<ButtonInput<GamepadButton>>,
+    active: Res<ActiveGamepad>,
+    axes: Res<Axis<GamepadAxis>>,
+) {
+    let pushed = active.0.is_some_and(|gamepad| {
+        [GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY]
+            .into_iter()
+            .any(|axis| {
+                axes.get(GamepadAxis::new(gamepad, axis))
+                    .is_some_and(|value| value.abs() > AIM_DEADZONE)
+            })
+    });
+    if pads.get_just_pressed().next().is_some() || pushed {
+        device.set_if_neq(InputDevice::Gamepad);
+    } else if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some()
+    {
+        device.set_if_neq(InputDevice::KeyboardMouse);
+    }
+}
+
+/// Picks up newly connected gamepads and falls back to another one when the
+/// active pad is unplugged.
+fn hot_plug(
+    mut events: EventReader<GamepadConnectionEvent>,
+    mut active: ResMut<ActiveGamepad>,
+    gamepads: Res<Gamepads>,
+) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                if active.0.is_none() {
+                    info!("Using gamepad {}", info.name);
+                    active.0 = Some(event.gamepad);
+                }
+            }
+            GamepadConnection::Disconnected => {
+                if active.0 == Some(event.gamepad) {
+                    active.0 = gamepads.iter().find(|&gamepad| gamepad != event.gamepad);
+                }
+            }
+        }
+    }
+}
+
+/// Asks the active gamepad to vibrate, does nothing without one, while it
+/// isn't what the player is using or with rumble turned off.
+#[derive(Event)]
+pub(crate) struct Rumble {
+    /// 0.0 to 1.0
+    pub(crate) strength: f32,
+    pub(crate) duration: Duration,
+}
+
+fn rumble(
+    mut events: EventReader<Rumble>,
+    active: Res<ActiveGamepad>,
+    device: Res<InputDevice>,
+    settings: Res<RumbleSettings>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    for event in events.read() {
+        let Some(gamepad) = active.0 else {
+            continue;
+        };
+        if !settings.enabled || *device != InputDevice::Gamepad {
+            continue;
+        }
+
+        let strength = (event.strength * settings.strength).clamp(0., 1.);
+        if strength <= 0. {
+            continue;
+        }
+        requests.send(GamepadRumbleRequest::Add {
+            gamepad,
+            duration: event.duration,
+            intensity: GamepadRumbleIntensity::strong_motor(strength),
+        });
+    }
+}
+
+/// Harder the faster the player hits the ground.
+fn landing_rumble(
+    mut landed: EventReader<Landed>,
+    players: Query<(), (With<Player>, Without<Remote>)>,
+    mut rumble: EventWriter<Rumble>,
+) {
+    for ev in landed.read() {
+        if ev.impact_speed < MIN_LAND_SPEED || !players.contains(ev.player) {
+            continue;
+        }
+        rumble.send(Rumble {
+            strength: (ev.impact_speed / HARD_LANDING).clamp(0.2, 1.),
+            duration: Duration::from_millis(150),
+        });
+    }
+}
+
+/// Harder the faster the player was going when the rope caught them.
+fn hook_rumble(
+    mut attached: EventReader<HookAttached>,
+    players: Query<&LinearVelocity, (With<Player>, Without<Remote>)>,
+    mut rumble: EventWriter<Rumble>,
+) {
+    for ev in attached.read() {
+        let Ok(velocity) = players.get(ev.player) else {
+            continue;
+        };
+        let speed = velocity.length();
+        rumble.send(Rumble {
+            strength: (speed / HARD_CATCH).clamp(0.3, 1.),
+            duration: Duration::from_millis(120),
+        });
+    }
+}
+
+fn damage_rumble(
+    hit: Query<(), (With<Player>, Without<Remote>, Added<Hitstun>)>,
+    mut rumble: EventWriter<Rumble>,
+) {
+    if !hit.is_empty() {
+        rumble.send(Rumble {
+            strength: 0.8,
+            duration: Duration::from_millis(250),
+        });
+    }
+}
+
+/// Harder the closer the blast is to the player.
+fn explosion_rumble(
+    mut exploded: EventReader<Exploded>,
+    players: Query<&Transform, (With<Player>, Without<Remote>)>,
+    mut rumble: EventWriter<Rumble>,
+) {
+    let Some(player) = players.iter().next() else {
+        exploded.clear();
+        return;
+    };
+
+    for ev in exploded.read() {
+        let distance = player.translation.truncate().distance(ev.position);
+        let falloff = 1. - distance / (ev.radius * FELT_EXPLOSION);
+        if falloff <= 0. {
+            continue;
+        }
+        rumble.send(Rumble {
+            strength: falloff,
+            duration: Duration::from_millis(300),
+        });
+    }
+}
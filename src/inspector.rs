@@ -0,0 +1,246 @@
+use bevy::{ecs::component::ComponentId, prelude::*, reflect::ReflectMut, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContext};
+
+/// Whether the inspector is showing and what's picked in it, F12 toggles it.
+#[derive(Resource, Default)]
+pub(crate) struct Inspector {
+    open: bool,
+    selected: Option<Entity>,
+}
+
+pub(crate) fn toggle(keys: Res<ButtonInput<KeyCode>>, mut inspector: ResMut<Inspector>) {
+    if keys.just_pressed(KeyCode::F12) {
+        inspector.open = !inspector.open;
+    }
+}
+
+/// Every entity as a tree next to the components of the selected one.
+/// Reflected components can be edited in place, the rest only show their
+/// name.
+pub(crate) fn window(world: &mut World) {
+    if !world.resource::<Inspector>().open {
+        return;
+    }
+    let Some(ctx) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .iter_mut(world)
+        .next()
+        .map(|mut context| context.get_mut().clone())
+    else {
+        return;
+    };
+
+    let mut open = true;
+    let mut selected = world.resource::<Inspector>().selected;
+    egui::Window::new("Inspector")
+        .open(&mut open)
+        .default_size([600., 500.])
+        .show(&ctx, |ui| {
+            ui.columns(2, |columns| {
+                egui::ScrollArea::vertical()
+                    .id_salt("entities")
+                    .show(&mut columns[0], |ui| {
+                        let mut roots = world.query_filtered::<Entity, Without<Parent>>();
+                        let mut roots = roots.iter(world).collect::<Vec<_>>();
+                        roots.sort();
+                        for entity in roots {
+                            tree(ui, world, entity, &mut selected);
+                        }
+                    });
+
+                egui::ScrollArea::vertical()
+                    .id_salt("components")
+                    .show(&mut columns[1], |ui| match selected {
+                        Some(entity) if world.get_entity(entity).is_some() => {
+                            components(ui, world, entity);
+                        }
+                        _ => {
+                            ui.label("Select an entity");
+                        }
+                    });
+            });
+        });
+
+    let mut inspector = world.resource_mut::<Inspector>();
+    inspector.open = open;
+    inspector.selected = selected;
+}
+
+/// A name for the entity, its [`Name`] or else its first component from
+/// this game.
+fn label(world: &World, entity: Entity) -> String {
+    if let Some(name) = world.get::<Name>(entity) {
+        return format!("{name} ({entity})");
+    }
+
+    let ours = world
+        .inspect_entity(entity)
+        .into_iter()
+        .map(|info| info.name())
+        .find(|name| name.starts_with(env!("CARGO_CRATE_NAME")));
+    match ours {
+        Some(name) => format!("{} ({entity})", short(name)),
+        None => entity.to_string(),
+    }
+}
+
+fn tree(ui: &mut egui::Ui, world: &World, entity: Entity, selected: &mut Option<Entity>) {
+    let label = label(world, entity);
+    let is_selected = *selected == Some(entity);
+
+    match world.get::<Children>(entity) {
+        Some(children) => {
+            let response = egui::CollapsingHeader::new(label)
+                .id_salt(entity)
+                .show(ui, |ui| {
+                    for &child in children {
+                        tree(ui, world, child, selected);
+                    }
+                });
+            if response.header_response.clicked() {
+                *selected = Some(entity);
+            }
+            if is_selected {
+                response.header_response.highlight();
+            }
+        }
+        None => {
+            if ui.selectable_label(is_selected, label).clicked() {
+                *selected = Some(entity);
+            }
+        }
+    }
+}
+
+fn components(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    ui.heading(label(world, entity));
+
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    let mut infos = world
+        .inspect_entity(entity)
+        .into_iter()
+        .map(|info| (info.id(), info.name().to_string(), info.type_id()))
+        .collect::<Vec<(ComponentId, String, _)>>();
+    infos.sort_by(|a, b| short(&a.1).cmp(short(&b.1)));
+
+    for (id, name, type_id) in infos {
+        let reflect = type_id
+            .and_then(|type_id| registry.get(type_id))
+            .and_then(|registration| registration.data::<ReflectComponent>());
+
+        let Some(reflect) = reflect else {
+            ui.label(egui::RichText::new(short(&name)).weak());
+            continue;
+        };
+
+        egui::CollapsingHeader::new(short(&name))
+            .id_salt((entity, id))
+            .show(ui, |ui| {
+                if let Some(mut value) = reflect.reflect_mut(world.entity_mut(entity)) {
+                    // Only mark the component changed when it was edited
+                    let mut copy = value.clone_value();
+                    if edit(ui, copy.as_mut()) {
+                        value.apply(copy.as_ref());
+                    }
+                }
+            });
+    }
+}
+
+/// The type name without its module path, keeping any generics readable.
+fn short(name: &str) -> &str {
+    let end = name.find('<').unwrap_or(name.len());
+    let start = name[..end].rfind("::").map_or(0, |i| i + 2);
+    &name[start..]
+}
+
+/// Widgets for a reflected value, returns whether anything was changed.
+fn edit(ui: &mut egui::Ui, value: &mut dyn Reflect) -> bool {
+    macro_rules! number {
+        ($($ty:ty),*) => {
+            $(if let Some(number) = value.downcast_mut::<$ty>() {
+                return ui.add(egui::DragValue::new(number)).changed();
+            })*
+        };
+    }
+    number!(f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+    if let Some(flag) = value.downcast_mut::<bool>() {
+        return ui.checkbox(flag, "").changed();
+    }
+    if let Some(text) = value.downcast_mut::<String>() {
+        return ui.text_edit_singleline(text).changed();
+    }
+    if let Some(entity) = value.downcast_ref::<Entity>() {
+        ui.label(entity.to_string());
+        return false;
+    }
+
+    let mut changed = false;
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => {
+            egui::Grid::new(ui.next_auto_id()).show(ui, |ui| {
+                for i in 0..value.field_len() {
+                    let name = value.name_at(i).unwrap_or_default().to_string();
+                    if let Some(field) = value.field_at_mut(i) {
+                        ui.label(name);
+                        changed |= edit(ui, field);
+                        ui.end_row();
+                    }
+                }
+            });
+        }
+        ReflectMut::TupleStruct(value) => {
+            ui.horizontal(|ui| {
+                for i in 0..value.field_len() {
+                    if let Some(field) = value.field_mut(i) {
+                        changed |= edit(ui, field);
+                    }
+                }
+            });
+        }
+        ReflectMut::Tuple(value) => {
+            ui.horizontal(|ui| {
+                for i in 0..value.field_len() {
+                    if let Some(field) = value.field_mut(i) {
+                        changed |= edit(ui, field);
+                    }
+                }
+            });
+        }
+        ReflectMut::List(value) => {
+            ui.vertical(|ui| {
+                for i in 0..value.len() {
+                    if let Some(item) = value.get_mut(i) {
+                        changed |= edit(ui, item);
+                    }
+                }
+            });
+        }
+        ReflectMut::Array(value) => {
+            ui.vertical(|ui| {
+                for i in 0..value.len() {
+                    if let Some(item) = value.get_mut(i) {
+                        changed |= edit(ui, item);
+                    }
+                }
+            });
+        }
+        ReflectMut::Enum(value) => {
+            ui.vertical(|ui| {
+                ui.label(value.variant_name());
+                for i in 0..value.field_len() {
+                    if let Some(field) = value.field_at_mut(i) {
+                        changed |= edit(ui, field);
+                    }
+                }
+            });
+        }
+        _ => {
+            ui.label(egui::RichText::new(short(value.reflect_type_path())).weak());
+        }
+    }
+    changed
+}
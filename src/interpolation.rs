@@ -0,0 +1,54 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+/// Draws a physics body's children between its positions from the last two
+/// fixed ticks, so it moves smoothly when the display runs faster than the
+/// simulation.
+///
+/// Only the children are moved, the body itself stays where physics put it.
+/// They are expected to sit at the body's origin.
+#[derive(Component, Default)]
+pub(crate) struct Interpolated {
+    previous: Vec2,
+    current: Option<Vec2>,
+}
+
+impl Interpolated {
+    /// Jump straight to the next position instead of sliding over to it,
+    /// after a body was moved somewhere far away.
+    pub(crate) fn snap(&mut self) {
+        self.current = None;
+    }
+}
+
+/// Runs after every physics step.
+pub(crate) fn record(mut bodies: Query<(&mut Interpolated, &Position)>) {
+    for (mut interpolated, position) in &mut bodies {
+        interpolated.previous = interpolated.current.unwrap_or(position.0);
+        interpolated.current = Some(position.0);
+    }
+}
+
+/// Runs once per frame before transforms are propagated.
+pub(crate) fn offset_children(
+    bodies: Query<(&Interpolated, &Transform, &Children)>,
+    mut children: Query<&mut Transform, Without<Interpolated>>,
+    time: Res<Time<Fixed>>,
+) {
+    let alpha = time.overstep_fraction();
+
+    for (interpolated, transform, body_children) in &bodies {
+        let Some(current) = interpolated.current else {
+            continue;
+        };
+
+        let drawn_at = interpolated.previous.lerp(current, alpha);
+        let offset = transform.rotation.inverse() * (drawn_at - current).extend(0.);
+
+        let mut children = children.iter_many_mut(body_children);
+        while let Some(mut child) = children.fetch_next() {
+            child.translation.x = offset.x;
+            child.translation.y = offset.y;
+        }
+    }
+}
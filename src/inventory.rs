@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    health::Health,
+    input::Action,
+    player::{BombKind, Controls, Player},
+};
+
+/// Slots on the hotbar, each picked with its own number key
+pub(crate) const SLOTS: usize = 5;
+
+/// Something the player can carry.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect, Deserialize)]
+pub(crate) enum Item {
+    Bomb(BombKind),
+    /// Kept for whatever needs unlocking
+    Key,
+    /// Heals one health when used
+    Medkit,
+}
+
+impl Item {
+    /// Id of the item's name in the locale files.
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Item::Bomb(BombKind::Timed) => "item-bomb",
+            Item::Bomb(BombKind::Sticky) => "item-sticky",
+            Item::Bomb(BombKind::Remote) => "item-remote",
+            Item::Bomb(BombKind::Grenade) => "item-grenade",
+            Item::Key => "item-key",
+            Item::Medkit => "item-medkit",
+        }
+    }
+
+    pub(crate) fn color(self) -> Color {
+        match self {
+            Item::Bomb(kind) => kind.color(),
+            Item::Key => Color::hsl(45., 0.9, 0.55),
+            Item::Medkit => Color::hsl(0., 0.7, 0.85),
+        }
+    }
+}
+
+/// A pile of one item in a slot.
+#[derive(Clone, Copy, Reflect)]
+pub(crate) struct Stack {
+    pub(crate) item: Item,
+    /// None for items that never run out
+    pub(crate) count: Option<u32>,
+}
+
+/// What the player carries, shown on the hotbar. The bomb button uses the
+/// selected slot.
+#[derive(Component, Clone, Reflect)]
+pub(crate) struct Inventory {
+    pub(crate) slots: [Option<Stack>; SLOTS],
+    pub(crate) selected: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        let bomb = |kind| {
+            Some(Stack {
+                item: Item::Bomb(kind),
+                count: None,
+            })
+        };
+
+        Self {
+            slots: [
+                bomb(BombKind::Timed),
+                bomb(BombKind::Sticky),
+                bomb(BombKind::Remote),
+                bomb(BombKind::Grenade),
+                None,
+            ],
+            selected: 0,
+        }
+    }
+}
+
+impl Inventory {
+    pub(crate) fn selected(&self) -> Option<Item> {
+        self.slots[self.selected].map(|stack| stack.item)
+    }
+
+    /// Adds to the stack of `item` if there is one, otherwise to the first
+    /// free slot. False when there's no room.
+    pub(crate) fn add(&mut self, item: Item, count: u32) -> bool {
+        let slot = self
+            .slots
+            .iter()
+            .position(|slot| slot.is_some_and(|stack| stack.item == item))
+            .or_else(|| self.slots.iter().position(Option::is_none));
+        let Some(slot) = slot else {
+            return false;
+        };
+
+        let stack = self.slots[slot].get_or_insert(Stack {
+            item,
+            count: Some(0),
+        });
+        if let Some(total) = &mut stack.count {
+            *total += count;
+        }
+        true
+    }
+
+    /// Takes one of the selected item, emptying the slot once the last one
+    /// goes.
+    pub(crate) fn use_selected(&mut self) -> Option<Item> {
+        let slot = &mut self.slots[self.selected];
+        let stack = (*slot)?;
+        match stack.count {
+            Some(1) => *slot = None,
+            Some(count) => {
+                slot.as_mut()?.count = Some(count - 1);
+            }
+            None => (),
+        }
+        Some(stack.item)
+    }
+}
+
+/// Picks a hotbar slot with the number keys.
+pub(crate) fn select(mut inventories: Query<(&mut Inventory, &Controls)>) {
+    for (mut inventory, controls) in &mut inventories {
+        if let Some(slot) = Action::HOTBAR
+            .iter()
+            .position(|action| controls.actions.just_pressed(*action))
+        {
+            inventory.selected = slot;
+        }
+    }
+}
+
+/// Uses up the selected item when it isn't a bomb, which are thrown instead.
+pub(crate) fn consume(mut players: Query<(&mut Inventory, &mut Health, &Controls), With<Player>>) {
+    for (mut inventory, mut health, controls) in &mut players {
+        if !controls.actions.just_pressed(Action::Bomb)
+            || inventory.selected() != Some(Item::Medkit)
+            || health.current >= health.max
+        {
+            continue;
+        }
+        inventory.use_selected();
+        health.current += 1;
+    }
+}
@@ -0,0 +1,218 @@
+use std::f32::consts::FRAC_PI_2;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    gravity::GravityDirection,
+    player::{Hooks, Landed, Player, WALL_DOT},
+};
+
+/// Gap kept between the player and whatever they slide along, so the next
+/// cast doesn't start touching it
+const SKIN: f32 = 1.;
+/// Surfaces one move can slide along before the rest of it is dropped
+const SLIDE_ITERATIONS: usize = 4;
+/// How far below the player the ground is still stood on
+const GROUND_PROBE: f32 = 2.;
+
+/// On the player when they're a kinematic body moved by [`move_and_slide`]
+/// instead of a dynamic one pushed around by the physics solver. Every move
+/// then goes exactly where the controls and tuning say, without the solver's
+/// jitter.
+///
+/// The rest of the physics world still sees a kinematic body, so it shoves
+/// crates and enemies aside however heavy they are, and ropes to
+/// [`Hookable`](crate::player::Hookable) objects or made of links drag them
+/// along without holding the player back.
+#[derive(Component)]
+pub(crate) struct KinematicBody;
+
+/// Collider entities the player is stopped by: solid static and kinematic
+/// ones, dynamic bodies get pushed out of the way by the solver instead.
+fn blocks(
+    entity: Entity,
+    colliders: &Query<(Has<Sensor>, Option<&ColliderParent>)>,
+    bodies: &Query<&RigidBody>,
+) -> bool {
+    let Ok((sensor, parent)) = colliders.get(entity) else {
+        return false;
+    };
+    let body = parent.and_then(|parent| bodies.get(parent.get()).ok());
+    !sensor && !body.is_some_and(RigidBody::is_dynamic)
+}
+
+/// The nearest blocking hit moving `collider` from `origin` along `dir` for
+/// `distance`, with the normal of the surface pointing back at it.
+fn cast(
+    spatial_query: &SpatialQuery,
+    collider: &Collider,
+    origin: Vec2,
+    dir: Dir2,
+    distance: f32,
+    filter: &SpatialQueryFilter,
+    is_blocking: impl Fn(Entity) -> bool,
+) -> Option<(Entity, f32, Vec2)> {
+    spatial_query
+        .shape_hits(collider, origin, 0., dir, distance, 8, true, filter.clone())
+        .into_iter()
+        .filter(|hit| is_blocking(hit.entity))
+        .min_by(|a, b| a.time_of_impact.total_cmp(&b.time_of_impact))
+        // Cast unrotated, so the cast shape's normal is already in world space
+        .map(|hit| (hit.entity, hit.time_of_impact, -hit.normal2))
+}
+
+/// Falls, keeps to the rope and rubs against the ground by hand, then moves
+/// the player as far as they go this tick, sliding along whatever they run
+/// into. Physics is left to carry out the move, the velocity is set to cover
+/// exactly that distance over the tick.
+pub(crate) fn move_and_slide(
+    mut players: Query<
+        (
+            Entity,
+            &Position,
+            &Collider,
+            &CollisionLayers,
+            &mut LinearVelocity,
+            &mut Player,
+            &Friction,
+            &GravityDirection,
+            &GravityScale,
+            &Hooks,
+        ),
+        With<KinematicBody>,
+    >,
+    grounds: Query<(Option<&LinearVelocity>, Option<&Friction>), Without<Player>>,
+    joints: Query<&DistanceJoint>,
+    anchors: Query<&Position, Without<Player>>,
+    colliders: Query<(Has<Sensor>, Option<&ColliderParent>)>,
+    bodies: Query<&RigidBody>,
+    spatial_query: SpatialQuery,
+    gravity: Res<Gravity>,
+    time: Res<Time>,
+    mut landed: EventWriter<Landed>,
+    mut commands: Commands,
+) {
+    let dt = time.delta_seconds();
+    if dt <= 0. {
+        return;
+    }
+
+    for (
+        entity,
+        position,
+        collider,
+        layers,
+        mut velocity,
+        mut player,
+        friction,
+        direction,
+        scale,
+        hooks,
+    ) in &mut players
+    {
+        let up = direction.up();
+        velocity.0 += gravity.0 * scale.0 * dt;
+
+        // Friction against the ground, towards whatever speed it moves at
+        if let Some(ground) = player.ground.filter(|_| player.is_grounded) {
+            let (ground_velocity, ground_friction) = grounds.get(ground).unwrap_or_default();
+            let coefficient = friction
+                .combine(ground_friction.copied().unwrap_or_default())
+                .dynamic_coefficient;
+            let along = up.perp();
+            let relative = (velocity.0 - ground_velocity.map_or(Vec2::ZERO, |v| v.0)).dot(along);
+            let slowed = relative.abs() - coefficient * gravity.0.length() * dt;
+            velocity.0 -= along * (relative - relative.signum() * slowed.max(0.));
+        }
+
+        // The rope only holds the player in, never pushes them away
+        for hooked in hooks.0.iter().filter(|hooked| !hooked.object) {
+            let Ok(rope) = joints.get(hooked.rope) else {
+                continue;
+            };
+            if rope.entity1 != entity || !hooked.segments.is_empty() {
+                continue;
+            }
+            let Ok(anchor) = anchors.get(rope.entity2) else {
+                continue;
+            };
+            let offset = position.0 - anchor.0;
+            let Ok(out) = Dir2::new(offset) else {
+                continue;
+            };
+            let outward = velocity.dot(*out).max(0.);
+            velocity.0 -= *out * outward;
+            let stretched = offset.length() - rope.rest_length;
+            if stretched > 0. {
+                velocity.0 -= *out * stretched / dt;
+            }
+        }
+
+        player.clear_contacts();
+        let filter = SpatialQueryFilter::from_mask(layers.filters).with_excluded_entities([entity]);
+        let is_blocking = |hit: Entity| blocks(hit, &colliders, &bodies);
+        // Set through commands, the spatial query reads every rotation
+        let mut upright = None;
+        let mut touch = |player: &mut Player, hit: Entity, normal: Vec2| {
+            if normal.dot(up) > 0.5 {
+                player.is_grounded = true;
+                player.ground = Some(hit);
+                // Stood on like any other ground, upside down on the ceiling
+                upright = Some(Rotation::radians(normal.to_angle() - FRAC_PI_2));
+            } else if normal.dot(up).abs() < WALL_DOT {
+                player.wall = Some(normal);
+            }
+        };
+
+        let mut at = position.0;
+        let mut remaining = velocity.0 * dt;
+        for _ in 0..SLIDE_ITERATIONS {
+            let Ok(dir) = Dir2::new(remaining) else {
+                break;
+            };
+            let distance = remaining.length();
+            let Some((hit, toi, normal)) = cast(
+                &spatial_query,
+                collider,
+                at,
+                dir,
+                distance + SKIN,
+                &filter,
+                is_blocking,
+            ) else {
+                at += remaining;
+                break;
+            };
+
+            let moved = (toi - SKIN).clamp(0., distance);
+            at += dir * moved;
+            remaining -= dir * moved;
+            // Keep only the part of the move and speed along the surface
+            remaining -= normal * remaining.dot(normal).min(0.);
+            let into = velocity.dot(normal).min(0.);
+            velocity.0 -= normal * into;
+            touch(&mut player, hit, normal);
+        }
+
+        if !player.is_grounded && velocity.dot(up) <= 0. {
+            if let Some((hit, _, normal)) = cast(
+                &spatial_query,
+                collider,
+                at,
+                Dir2::new_unchecked(-up),
+                SKIN + GROUND_PROBE,
+                &filter,
+                is_blocking,
+            ) {
+                touch(&mut player, hit, normal);
+            }
+        }
+        player.settle_contacts(entity, velocity.0, up, &mut landed);
+        if let Some(rotation) = upright {
+            commands.entity(entity).insert(rotation);
+        }
+
+        velocity.0 = (at - position.0) / dt;
+    }
+}
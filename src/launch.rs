@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+use crate::{
+    debug::DebugTools,
+    deterministic::GameRng,
+    level::{Level, Levels},
+    state::AppState,
+};
+
+/// How the game starts, usually from the command line. Add it after
+/// [`GamePlugins`](crate::GamePlugins).
+pub struct LaunchPlugin {
+    /// Level to start in instead of the main menu, by its file name
+    pub level: Option<String>,
+    /// Start in the editor, on the first level unless `level` says otherwise
+    pub editor: bool,
+    pub debug_render: bool,
+    pub seed: Option<u64>,
+}
+
+impl Default for LaunchPlugin {
+    fn default() -> Self {
+        Self {
+            level: None,
+            editor: false,
+            debug_render: true,
+            seed: None,
+        }
+    }
+}
+
+impl LaunchPlugin {
+    pub const USAGE: &'static str = "\
+Usage: glatformer [OPTIONS]
+
+Options:
+  --level <FILE>      Start playing a level from the index, like levels/03.ron
+  --editor            Start in the editor
+  --no-debug-render   Start with the collider outlines and debug tools off
+  --seed <SEED>       Seed the random number generator
+  -h, --help          Print this help";
+
+    /// Options from the arguments, without the program name.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+            match arg.as_str() {
+                "--level" => {
+                    let path = value("--level")?;
+                    // The index lists file names inside levels/
+                    let name = path.rsplit(['/', '\\']).next().unwrap_or(&path);
+                    options.level = Some(name.to_string());
+                }
+                "--editor" => options.editor = true,
+                "--no-debug-render" => options.debug_render = false,
+                "--seed" => {
+                    let seed = value("--seed")?;
+                    options.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("--seed needs a number, not {seed}"))?,
+                    );
+                }
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+impl Plugin for LaunchPlugin {
+    fn build(&self, app: &mut App) {
+        if !self.debug_render {
+            app.insert_resource(DebugTools(false));
+        }
+        if let Some(seed) = self.seed {
+            app.insert_resource(GameRng::new(seed));
+        }
+
+        if self.level.is_some() || self.editor {
+            app.insert_resource(Launch {
+                level: self.level.clone(),
+                state: if self.editor {
+                    AppState::Editor
+                } else {
+                    AppState::Playing
+                },
+            })
+            .add_systems(
+                Update,
+                start
+                    .run_if(resource_exists::<Launch>)
+                    .run_if(not(in_state(AppState::Loading))),
+            );
+        }
+    }
+}
+
+/// Where to go once the levels have loaded.
+#[derive(Resource)]
+struct Launch {
+    level: Option<String>,
+    state: AppState,
+}
+
+fn start(
+    mut commands: Commands,
+    launch: Res<Launch>,
+    mut levels: ResMut<Levels>,
+    assets: Res<Assets<Level>>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    // Still waiting for the index
+    if levels.names.is_empty() {
+        return;
+    }
+
+    let index = match &launch.level {
+        Some(level) => levels.names.iter().position(|name| name == level),
+        None => Some(0),
+    };
+    let Some(index) = index else {
+        error!(
+            "No level {} in the index, try one of {:?}",
+            launch.level.as_deref().unwrap_or_default(),
+            levels.names
+        );
+        commands.remove_resource::<Launch>();
+        return;
+    };
+
+    if assets.get(&levels.handles[index]).is_none() {
+        return;
+    }
+
+    levels.current = index;
+    next.set(launch.state);
+    commands.remove_resource::<Launch>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<LaunchPlugin, String> {
+        LaunchPlugin::from_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parses_every_option() {
+        let options =
+            parse("--level levels/03.ron --editor --no-debug-render --seed 1234").unwrap();
+        assert_eq!(options.level.as_deref(), Some("03.ron"));
+        assert!(options.editor);
+        assert!(!options.debug_render);
+        assert_eq!(options.seed, Some(1234));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse("--seed").is_err());
+        assert!(parse("--seed many").is_err());
+        assert!(parse("--fly").is_err());
+    }
+}
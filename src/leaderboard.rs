@@ -0,0 +1,395 @@
+use std::{
+    collections::HashMap,
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use bevy::{prelude::*, tasks::AsyncComputeTaskPool, time::common_conditions::on_timer};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    daily::Daily,
+    level::Levels,
+    mutators::{Mutator, Mutators},
+    replay::Replay,
+    run::Run,
+    state::AppState,
+};
+
+/// How many of the fastest times are fetched for each level
+const TOP: usize = 10;
+/// Seconds between attempts to send queued times while offline
+const RETRY: u64 = 30;
+#[cfg(not(target_arch = "wasm32"))]
+const QUEUE_PATH: &str = "leaderboard_queue.ron";
+#[cfg(target_arch = "wasm32")]
+const QUEUE_KEY: &str = "glatformer_leaderboard_queue";
+
+/// Submits completion times to an online leaderboard and fetches the fastest
+/// ones for the menus. Does nothing without an endpoint, see
+/// [`LeaderboardConfig`].
+///
+/// The server is expected to answer `GET {endpoint}/levels/{level}?limit=n`
+/// with a JSON array of `{"name", "time", "mutators"}` entries, fastest
+/// first, and to take one such entry per `POST {endpoint}/levels/{level}`.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LeaderboardConfig>()
+            .init_resource::<Leaderboard>()
+            .add_systems(Startup, load_queue)
+            .add_systems(OnEnter(AppState::LevelComplete), (submit, fetch_current))
+            .add_systems(OnEnter(AppState::LevelSelect), fetch_all)
+            .add_systems(
+                Update,
+                (receive, flush.run_if(on_timer(Duration::from_secs(RETRY)))),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct LeaderboardConfig {
+    /// Base URL of the leaderboard server, taken from `GLATFORMER_LEADERBOARD`
+    /// when running or else when building
+    pub(crate) endpoint: Option<String>,
+    /// Shown next to submitted times
+    pub(crate) name: String,
+}
+
+impl Default for LeaderboardConfig {
+    fn default() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let endpoint = std::env::var("GLATFORMER_LEADERBOARD").ok();
+        #[cfg(target_arch = "wasm32")]
+        let endpoint = None;
+
+        Self {
+            endpoint: endpoint
+                .or(option_env!("GLATFORMER_LEADERBOARD").map(String::from))
+                .filter(|endpoint| !endpoint.is_empty())
+                .map(|endpoint| endpoint.trim_end_matches('/').to_string()),
+            name: "Anonymous".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    /// Seconds
+    pub(crate) time: f32,
+    /// What the time was played with, older servers leave it out
+    #[serde(default)]
+    pub(crate) mutators: Vec<Mutator>,
+}
+
+/// A completion waiting to be sent.
+#[derive(Serialize, Deserialize)]
+struct Submission {
+    /// File name of the level
+    level: String,
+    entry: Entry,
+}
+
+enum Reply {
+    Submitted(Result<(), String>),
+    Fetched {
+        level: String,
+        result: Result<Vec<Entry>, String>,
+    },
+}
+
+#[derive(Resource)]
+pub(crate) struct Leaderboard {
+    /// Fastest times by level file name, as last fetched
+    pub(crate) top: HashMap<String, Vec<Entry>>,
+    /// Oldest first, kept across sessions until the server has them
+    queue: Vec<Submission>,
+    /// Only one submission is in flight at a time so they arrive in order
+    sending: bool,
+    /// The last request failed
+    pub(crate) offline: bool,
+    sender: mpsc::Sender<Reply>,
+    receiver: Mutex<mpsc::Receiver<Reply>>,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            top: HashMap::new(),
+            queue: Vec::new(),
+            sending: false,
+            offline: false,
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl Leaderboard {
+    /// Times finished while offline that are still to be sent.
+    pub(crate) fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    fn send_next(&mut self, config: &LeaderboardConfig) {
+        let (Some(endpoint), Some(submission)) = (&config.endpoint, self.queue.first()) else {
+            return;
+        };
+        if self.sending {
+            return;
+        }
+        self.sending = true;
+
+        let url = format!("{endpoint}/levels/{}", submission.level);
+        let body = serde_json::to_string(&submission.entry).unwrap_or_default();
+        let sender = self.sender.clone();
+        spawn(async move {
+            let result = http::request("POST", &url, Some(body)).await.map(|_| ());
+            let _ = sender.send(Reply::Submitted(result));
+        });
+    }
+
+    fn fetch(&self, config: &LeaderboardConfig, level: &str) {
+        let Some(endpoint) = &config.endpoint else {
+            return;
+        };
+
+        let url = format!("{endpoint}/levels/{level}?limit={TOP}");
+        let level = level.to_string();
+        let sender = self.sender.clone();
+        spawn(async move {
+            let result = http::request("GET", &url, None).await.and_then(|text| {
+                serde_json::from_str::<Vec<Entry>>(&text).map_err(|err| err.to_string())
+            });
+            let _ = sender.send(Reply::Fetched { level, result });
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_queue() -> Option<Vec<Submission>> {
+        let text = std::fs::read_to_string(QUEUE_PATH).ok()?;
+        ron::from_str(&text).ok()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write_queue(&self) {
+        let result = ron::to_string(&self.queue)
+            .map_err(|err| err.to_string())
+            .and_then(|text| std::fs::write(QUEUE_PATH, text).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            error!("Could not write leaderboard queue: {err}");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read_queue() -> Option<Vec<Submission>> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        let text = storage.get_item(QUEUE_KEY).ok()??;
+        ron::from_str(&text).ok()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn write_queue(&self) {
+        let Some(storage) = web_sys::window().and_then(|window| window.local_storage().ok()?)
+        else {
+            return;
+        };
+
+        if let Ok(text) = ron::to_string(&self.queue) {
+            let _ = storage.set_item(QUEUE_KEY, &text);
+        }
+    }
+}
+
+/// Run a request in the background, the reply comes back through the channel.
+/// Outside the browser requests block while waiting on the server, so they
+/// go on the pool meant for long running work rather than the IO one.
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn(request: impl std::future::Future<Output = ()> + Send + 'static) {
+    AsyncComputeTaskPool::get().spawn(request).detach();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn(request: impl std::future::Future<Output = ()> + 'static) {
+    AsyncComputeTaskPool::get().spawn(request).detach();
+}
+
+fn load_queue(mut leaderboard: ResMut<Leaderboard>, config: Res<LeaderboardConfig>) {
+    leaderboard.queue = Leaderboard::read_queue().unwrap_or_default();
+    leaderboard.send_next(&config);
+}
+
+fn submit(
+    mut leaderboard: ResMut<Leaderboard>,
+    config: Res<LeaderboardConfig>,
+    levels: Res<Levels>,
+    run: Res<Run>,
+    replay: Res<Replay>,
+    daily: Res<Daily>,
+    mutators: Res<Mutators>,
+) {
+    // Watching a replay finishes the level too, and times sped up by
+    // mutators would crowd out everyone else's
+    if config.endpoint.is_none() || replay.is_playing() || mutators.speed_up() {
+        return;
+    }
+    let Some(level) = board(&levels, &daily) else {
+        return;
+    };
+
+    leaderboard.queue.push(Submission {
+        level,
+        entry: Entry {
+            name: config.name.clone(),
+            time: run.time.elapsed_secs(),
+            mutators: mutators.0.iter().copied().collect(),
+        },
+    });
+    leaderboard.write_queue();
+    leaderboard.send_next(&config);
+}
+
+fn fetch_current(
+    leaderboard: Res<Leaderboard>,
+    config: Res<LeaderboardConfig>,
+    levels: Res<Levels>,
+    daily: Res<Daily>,
+) {
+    if let Some(level) = board(&levels, &daily) {
+        leaderboard.fetch(&config, &level);
+    }
+}
+
+/// Name of the board the level being played goes on, daily challenges
+/// have their own.
+pub(crate) fn board(levels: &Levels, daily: &Daily) -> Option<String> {
+    match &daily.0 {
+        Some(challenge) => Some(challenge.board()),
+        None => levels.names.get(levels.current).cloned(),
+    }
+}
+
+fn fetch_all(leaderboard: Res<Leaderboard>, config: Res<LeaderboardConfig>, levels: Res<Levels>) {
+    for level in &levels.names {
+        leaderboard.fetch(&config, level);
+    }
+}
+
+/// Try the queue again, in case the connection came back.
+fn flush(mut leaderboard: ResMut<Leaderboard>, config: Res<LeaderboardConfig>) {
+    leaderboard.send_next(&config);
+}
+
+fn receive(mut leaderboard: ResMut<Leaderboard>, config: Res<LeaderboardConfig>) {
+    let replies = match leaderboard.receiver.lock() {
+        Ok(receiver) => receiver.try_iter().collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+
+    for reply in replies {
+        match reply {
+            Reply::Submitted(Ok(())) => {
+                leaderboard.sending = false;
+                leaderboard.offline = false;
+                let sent = leaderboard.queue.remove(0);
+                leaderboard.write_queue();
+                // Now including the new time
+                leaderboard.fetch(&config, &sent.level);
+                leaderboard.send_next(&config);
+            }
+            Reply::Submitted(Err(err)) => {
+                warn!("Could not submit time, will retry: {err}");
+                leaderboard.sending = false;
+                leaderboard.offline = true;
+            }
+            Reply::Fetched {
+                level,
+                result: Ok(entries),
+            } => {
+                leaderboard.offline = false;
+                leaderboard.top.insert(level, entries);
+            }
+            Reply::Fetched {
+                result: Err(err), ..
+            } => {
+                warn!("Could not fetch leaderboard: {err}");
+                leaderboard.offline = true;
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod http {
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// The body of a successful response.
+    pub(super) async fn request(
+        method: &str,
+        url: &str,
+        body: Option<String>,
+    ) -> Result<String, String> {
+        let request = ureq::request(method, url)
+            .timeout(TIMEOUT)
+            .set("Content-Type", "application/json");
+        let response = match body {
+            Some(body) => request.send_string(&body),
+            None => request.call(),
+        }
+        .map_err(|err| match err {
+            ureq::Error::Status(status, _) => format!("server answered {status}"),
+            err => err.to_string(),
+        })?;
+
+        response.into_string().map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod http {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{Request, RequestInit, Response};
+
+    /// The body of a successful response.
+    pub(super) async fn request(
+        method: &str,
+        url: &str,
+        body: Option<String>,
+    ) -> Result<String, String> {
+        let error = |err: JsValue| format!("{err:?}");
+
+        let init = RequestInit::new();
+        init.set_method(method);
+        if let Some(body) = &body {
+            init.set_body(&JsValue::from_str(body));
+        }
+        let request = Request::new_with_str_and_init(url, &init).map_err(error)?;
+        request
+            .headers()
+            .set("Content-Type", "application/json")
+            .map_err(error)?;
+
+        let window = web_sys::window().ok_or("no window")?;
+        let response: Response = JsFuture::from(window.fetch_with_request(&request))
+            .await
+            .map_err(error)?
+            .dyn_into()
+            .map_err(error)?;
+        if !response.ok() {
+            return Err(format!("server answered {}", response.status()));
+        }
+
+        let text = JsFuture::from(response.text().map_err(error)?)
+            .await
+            .map_err(error)?;
+        text.as_string()
+            .ok_or_else(|| "response is not text".to_string())
+    }
+}
@@ -0,0 +1,842 @@
+use std::{f32::consts::PI, marker::PhantomData};
+
+use avian2d::prelude::*;
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    abilities::{self, Ability},
+    anchor::{self, AimAssist},
+    atlas::AtlasSprite,
+    boss,
+    camera::CameraLock,
+    checkpoint, crates, crumble, cutscene, dialogue,
+    difficulty::Difficulty,
+    elevator,
+    enemy::{self, EnemyKind},
+    geometry, goal, gravity,
+    input::Action,
+    inventory, lighting,
+    locale::Locale,
+    netplay::Session,
+    pendulum, pickup, plate,
+    player::HookRules,
+    portal,
+    props::{self, PropKind},
+    run,
+    save::SaveData,
+    score, shop, sign, spawner,
+    state::{AppState, InGame},
+    surface::SurfaceMaterial,
+    timed, tutorial,
+    weather::Weather,
+    zipline,
+};
+
+/// Lists the level files in play order, relative to `assets/levels`
+const INDEX_FILE: &str = "levels/index.ron";
+
+/// Which levels exist, so new ones can be added without touching the code.
+#[derive(Asset, TypePath, Deserialize)]
+pub(crate) struct LevelIndex {
+    pub(crate) levels: Vec<String>,
+}
+
+/// Everything placed in a level, loaded from a RON file in `assets/levels`.
+#[derive(Asset, TypePath, Deserialize)]
+pub(crate) struct Level {
+    pub(crate) name: String,
+    pub(crate) spawn: Vec2,
+    /// Boss levels are completed by beating the boss instead
+    #[serde(default)]
+    pub(crate) goal: Option<Vec2>,
+    pub(crate) medals: Medals,
+    pub(crate) cubes: Vec<Cube>,
+    #[serde(default)]
+    pub(crate) coins: Vec<Vec2>,
+    #[serde(default)]
+    pub(crate) checkpoints: Vec<Vec2>,
+    /// More checkpoints, only placed on easy
+    #[serde(default)]
+    pub(crate) easy_checkpoints: Vec<Vec2>,
+    #[serde(default)]
+    pub(crate) lights: Vec<Light>,
+    #[serde(default)]
+    pub(crate) anchors: Vec<Vec2>,
+    #[serde(default)]
+    pub(crate) chasers: Vec<Vec2>,
+    #[serde(default)]
+    pub(crate) turrets: Vec<Vec2>,
+    #[serde(default)]
+    pub(crate) spawners: Vec<Spawner>,
+    #[serde(default)]
+    pub(crate) crumbling: Vec<Crumbling>,
+    #[serde(default)]
+    pub(crate) timed: Vec<Timed>,
+    #[serde(default)]
+    pub(crate) plates: Vec<Plate>,
+    #[serde(default)]
+    pub(crate) portals: Vec<PortalPair>,
+    /// Gravity is reversed for the player inside these
+    #[serde(default)]
+    pub(crate) flip_zones: Vec<Zone>,
+    /// Touching one reverses the player's gravity
+    #[serde(default)]
+    pub(crate) flip_orbs: Vec<Vec2>,
+    #[serde(default)]
+    pub(crate) gravity_volumes: Vec<GravityVolume>,
+    #[serde(default)]
+    pub(crate) crates: Vec<Crate>,
+    #[serde(default)]
+    pub(crate) pendulums: Vec<Pendulum>,
+    #[serde(default)]
+    pub(crate) elevators: Vec<Elevator>,
+    #[serde(default)]
+    pub(crate) ziplines: Vec<ZipLine>,
+    #[serde(default)]
+    pub(crate) items: Vec<ItemPickup>,
+    #[serde(default)]
+    pub(crate) abilities: Vec<AbilityPickup>,
+    #[serde(default)]
+    pub(crate) tutorials: Vec<Tutorial>,
+    #[serde(default)]
+    pub(crate) npcs: Vec<Npc>,
+    #[serde(default)]
+    pub(crate) signs: Vec<Sign>,
+    #[serde(default)]
+    pub(crate) props: Vec<Prop>,
+    #[serde(default)]
+    pub(crate) cutscenes: Vec<Cutscene>,
+    #[serde(default)]
+    pub(crate) boss: Option<Boss>,
+    /// Angle in degrees within which the hook snaps to an anchor
+    #[serde(default = "default_aim_assist")]
+    pub(crate) aim_assist: f32,
+    #[serde(default)]
+    pub(crate) hook: HookRules,
+    /// Everywhere outside the `weather_zones`
+    #[serde(default)]
+    pub(crate) weather: Weather,
+    #[serde(default)]
+    pub(crate) weather_zones: Vec<WeatherZone>,
+}
+
+fn default_aim_assist() -> f32 {
+    anchor::DEFAULT_AIM_ASSIST
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Cube {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) w: f32,
+    pub(crate) h: f32,
+    pub(crate) hue: f32,
+    /// Counter clockwise, in degrees
+    #[serde(default)]
+    pub(crate) rotation: f32,
+    #[serde(default)]
+    pub(crate) material: SurfaceMaterial,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Light {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) radius: f32,
+    pub(crate) intensity: f32,
+    pub(crate) color: Color,
+}
+
+/// Emits enemies, see [`spawner::Spawner`].
+#[derive(Deserialize)]
+pub(crate) struct Spawner {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) kind: EnemyKind,
+    /// Seconds between spawns
+    #[serde(default = "default_spawn_interval")]
+    pub(crate) interval: f32,
+    #[serde(default = "default_max_alive")]
+    pub(crate) max_alive: usize,
+    /// Sizes of each wave in order, spawns endlessly when empty
+    #[serde(default)]
+    pub(crate) waves: Vec<u32>,
+    /// Only starts spawning once the player enters this area
+    #[serde(default)]
+    pub(crate) trigger: Option<Zone>,
+}
+
+fn default_spawn_interval() -> f32 {
+    3.
+}
+
+fn default_max_alive() -> usize {
+    3
+}
+
+/// A platform that gives way under the player, see [`crumble::Crumbling`].
+#[derive(Deserialize)]
+pub(crate) struct Crumbling {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) w: f32,
+    pub(crate) h: f32,
+    /// Seconds it shakes for once stood on
+    #[serde(default = "default_crumble_delay")]
+    pub(crate) delay: f32,
+    /// Seconds until it's back in place
+    #[serde(default = "default_crumble_respawn")]
+    pub(crate) respawn: f32,
+    /// Drops down instead of disappearing
+    #[serde(default)]
+    pub(crate) fall: bool,
+}
+
+fn default_crumble_delay() -> f32 {
+    0.6
+}
+
+fn default_crumble_respawn() -> f32 {
+    3.
+}
+
+/// A platform that switches on and off, see [`timed::Timed`].
+#[derive(Deserialize)]
+pub(crate) struct Timed {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) w: f32,
+    pub(crate) h: f32,
+    /// Seconds it stays solid each cycle
+    #[serde(default = "default_timed_duration")]
+    pub(crate) on: f32,
+    /// Seconds it stays gone each cycle
+    #[serde(default = "default_timed_duration")]
+    pub(crate) off: f32,
+    /// Seconds into the cycle it starts at
+    #[serde(default)]
+    pub(crate) offset: f32,
+}
+
+fn default_timed_duration() -> f32 {
+    2.
+}
+
+/// Held down by heavy bodies, see [`plate::PressurePlate`].
+#[derive(Deserialize)]
+pub(crate) struct Plate {
+    /// Center of the top of whatever it's placed on
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    #[serde(default = "default_plate_width")]
+    pub(crate) w: f32,
+    /// Whatever listens on the same channel reacts to it
+    pub(crate) channel: u32,
+    /// Lightest body that holds it down, the player is around 7800
+    #[serde(default = "default_plate_mass")]
+    pub(crate) min_mass: f32,
+}
+
+fn default_plate_width() -> f32 {
+    80.
+}
+
+fn default_plate_mass() -> f32 {
+    2000.
+}
+
+/// Two linked portals, see [`portal::Portal`].
+#[derive(Deserialize)]
+pub(crate) struct PortalPair {
+    pub(crate) a: Portal,
+    pub(crate) b: Portal,
+    /// Also takes dynamic bodies other than the player
+    #[serde(default)]
+    pub(crate) bodies: bool,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Portal {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    /// Direction it faces, counter clockwise from the right in degrees
+    #[serde(default)]
+    pub(crate) angle: f32,
+}
+
+/// Scales gravity inside, see [`gravity::GravityVolume`].
+#[derive(Deserialize)]
+pub(crate) struct GravityVolume {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) w: f32,
+    pub(crate) h: f32,
+    /// Multiplies gravity, 0.5 is half as strong
+    pub(crate) scale: f32,
+}
+
+/// A pushable box, see [`crates::Crate`].
+#[derive(Deserialize)]
+pub(crate) struct Crate {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    #[serde(default = "default_crate_size")]
+    pub(crate) size: f32,
+}
+
+fn default_crate_size() -> f32 {
+    80.
+}
+
+/// A platform swinging from a pivot, see [`pendulum::Pendulum`].
+#[derive(Deserialize)]
+pub(crate) struct Pendulum {
+    /// Where it hangs from
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) length: f32,
+    #[serde(default = "default_pendulum_width")]
+    pub(crate) w: f32,
+    /// Counter clockwise from hanging straight down, in degrees, where the
+    /// swing starts
+    #[serde(default)]
+    pub(crate) angle: f32,
+    /// Seconds for a full swing when driven, left to gravity without one
+    #[serde(default)]
+    pub(crate) period: Option<f32>,
+}
+
+fn default_pendulum_width() -> f32 {
+    200.
+}
+
+/// A platform moving between two points, see [`elevator::Elevator`].
+#[derive(Deserialize)]
+pub(crate) struct Elevator {
+    /// Where it waits until triggered
+    pub(crate) from: Vec2,
+    pub(crate) to: Vec2,
+    #[serde(default = "default_elevator_width")]
+    pub(crate) w: f32,
+    /// Travels to `to` while a trigger on this channel is active
+    pub(crate) channel: u32,
+    #[serde(default = "default_elevator_speed")]
+    pub(crate) speed: f32,
+    #[serde(default)]
+    pub(crate) easing: elevator::Easing,
+    #[serde(default)]
+    pub(crate) on_crush: elevator::OnCrush,
+}
+
+fn default_elevator_width() -> f32 {
+    200.
+}
+
+fn default_elevator_speed() -> f32 {
+    250.
+}
+
+/// A line to slide down, see [`zipline::ZipLine`].
+#[derive(Deserialize)]
+pub(crate) struct ZipLine {
+    pub(crate) a: Vec2,
+    pub(crate) b: Vec2,
+}
+
+/// Something to carry, see [`pickup::ItemPickup`].
+#[derive(Deserialize)]
+pub(crate) struct ItemPickup {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) item: inventory::Item,
+    #[serde(default = "default_item_count")]
+    pub(crate) count: u32,
+}
+
+fn default_item_count() -> u32 {
+    1
+}
+
+/// An ability to find, see [`abilities::AbilityPickup`].
+#[derive(Deserialize)]
+pub(crate) struct AbilityPickup {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) ability: Ability,
+}
+
+/// An input prompt shown inside an area, see [`tutorial::Tutorial`].
+#[derive(Deserialize)]
+pub(crate) struct Tutorial {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) w: f32,
+    pub(crate) h: f32,
+    pub(crate) action: Action,
+    /// Ask to hold the button rather than press it
+    #[serde(default)]
+    pub(crate) hold: bool,
+    /// Locale id of what the action does, as in "Press Space to jump"
+    pub(crate) text: String,
+}
+
+/// Someone to talk to, see [`dialogue::Npc`].
+#[derive(Deserialize)]
+pub(crate) struct Npc {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    /// Locale id of what they're called
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) hue: f32,
+    /// Locale ids of their lines, shown one at a time as the player pages
+    /// through them
+    pub(crate) pages: Vec<String>,
+}
+
+/// Hints and lore shown when standing by, see [`sign::Sign`].
+#[derive(Deserialize)]
+pub(crate) struct Sign {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    /// Locale id of what's written on it
+    pub(crate) text: String,
+}
+
+/// Decoration, see [`props::Prop`].
+#[derive(Deserialize)]
+pub(crate) struct Prop {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) kind: PropKind,
+    /// Each kind has its own size otherwise
+    #[serde(default)]
+    pub(crate) size: Option<Vec2>,
+}
+
+/// A scripted sequence, see [`cutscene::Timeline`].
+#[derive(Deserialize)]
+pub(crate) struct Cutscene {
+    pub(crate) trigger: cutscene::Trigger,
+    pub(crate) steps: Vec<cutscene::Step>,
+    /// Keep the player from moving while it plays
+    #[serde(default = "default_lock_input")]
+    pub(crate) lock_input: bool,
+}
+
+fn default_lock_input() -> bool {
+    true
+}
+
+/// A boss and the arena it fights in, see [`boss::Boss`].
+#[derive(Deserialize)]
+pub(crate) struct Boss {
+    /// Where it rests on the arena floor
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) health: u32,
+    /// Entering this wakes the boss up and locks the camera
+    pub(crate) arena: Zone,
+    /// In order, each starting at a lower fraction of health than the last.
+    /// There has to be at least one
+    #[serde(deserialize_with = "boss::deserialize_phases")]
+    pub(crate) phases: Vec<boss::Phase>,
+}
+
+/// An axis aligned area, centered on `x` and `y`.
+#[derive(Deserialize)]
+pub(crate) struct Zone {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) w: f32,
+    pub(crate) h: f32,
+}
+
+/// An area with weather of its own, like a cave out of the rain.
+#[derive(Deserialize)]
+pub(crate) struct WeatherZone {
+    pub(crate) area: Zone,
+    pub(crate) weather: Weather,
+}
+
+/// Completion times in seconds needed for each medal.
+#[derive(Deserialize)]
+pub(crate) struct Medals {
+    pub(crate) gold: f32,
+    pub(crate) silver: f32,
+    pub(crate) bronze: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl Medals {
+    pub(crate) fn award(&self, seconds: f32) -> Option<Medal> {
+        if seconds <= self.gold {
+            Some(Medal::Gold)
+        } else if seconds <= self.silver {
+            Some(Medal::Silver)
+        } else if seconds <= self.bronze {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn thresholds(&self) -> [(Medal, f32); 3] {
+        [
+            (Medal::Gold, self.gold),
+            (Medal::Silver, self.silver),
+            (Medal::Bronze, self.bronze),
+        ]
+    }
+}
+
+impl Medal {
+    /// Id of the medal's name in the locale files.
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Medal::Gold => "medal-gold",
+            Medal::Silver => "medal-silver",
+            Medal::Bronze => "medal-bronze",
+        }
+    }
+}
+
+/// Loads any asset stored as RON. Both levels and the index use `.ron`, the
+/// asset server picks the loader from the requested asset type.
+pub(crate) struct RonLoader<A>(PhantomData<A>);
+
+impl<A> Default for RonLoader<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RonLoaderError {
+    #[error("could not read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse file: {0}")]
+    Ron(#[from] ron::de::SpannedError),
+}
+
+impl<A> AssetLoader for RonLoader<A>
+where
+    A: Asset + for<'de> Deserialize<'de>,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = RonLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<A, RonLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// All levels and which one is being played.
+#[derive(Resource, Default)]
+pub(crate) struct Levels {
+    pub(crate) index: Handle<LevelIndex>,
+    /// File names from the index, also used to identify levels in the save
+    pub(crate) names: Vec<String>,
+    pub(crate) handles: Vec<Handle<Level>>,
+    pub(crate) current: usize,
+}
+
+impl Levels {
+    pub(crate) fn current<'a>(&self, assets: &'a Assets<Level>) -> Option<&'a Level> {
+        assets.get(self.handles.get(self.current)?)
+    }
+
+    pub(crate) fn has_next(&self) -> bool {
+        self.current + 1 < self.handles.len()
+    }
+}
+
+pub(crate) fn load_index(mut levels: ResMut<Levels>, asset_server: Res<AssetServer>) {
+    levels.index = asset_server.load(INDEX_FILE);
+}
+
+/// Loads the levels named in the index once it's available.
+pub(crate) fn load_levels(
+    mut events: EventReader<AssetEvent<LevelIndex>>,
+    mut levels: ResMut<Levels>,
+    indices: Res<Assets<LevelIndex>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(&levels.index) && !event.is_modified(&levels.index) {
+            continue;
+        }
+        let Some(index) = indices.get(&levels.index) else {
+            continue;
+        };
+
+        levels.names = index.levels.clone();
+        levels.handles = index
+            .levels
+            .iter()
+            .map(|name| asset_server.load(format!("levels/{name}")))
+            .collect();
+    }
+}
+
+/// A solid piece of level geometry, see [`make_cube`].
+#[derive(Bundle)]
+pub(crate) struct CubeBundle {
+    pub(crate) sprite: SpriteBundle,
+    pub(crate) atlas: AtlasSprite,
+    pub(crate) body: RigidBody,
+    pub(crate) collider: Collider,
+    pub(crate) friction: Friction,
+    pub(crate) restitution: Restitution,
+    pub(crate) material: SurfaceMaterial,
+    pub(crate) scoped: StateScoped<InGame>,
+}
+
+pub(crate) fn make_cube(x: f32, y: f32, w: f32, h: f32, hue: f32) -> CubeBundle {
+    CubeBundle {
+        sprite: SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(hue, 0.8, 0.4),
+                custom_size: Some(Vec2::new(w, h)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, 0.),
+            ..default()
+        },
+        atlas: AtlasSprite("tile"),
+        body: RigidBody::Static,
+        collider: Collider::rectangle(w, h),
+        friction: Friction::new(1.),
+        restitution: Restitution::new(0.).with_combine_rule(CoefficientCombine::Multiply),
+        material: SurfaceMaterial::default(),
+        scoped: StateScoped(InGame),
+    }
+}
+
+pub(crate) fn setup(
+    mut commands: Commands,
+    levels: Res<Levels>,
+    assets: Res<Assets<Level>>,
+    save: Res<SaveData>,
+    locale: Res<Locale>,
+    difficulty: Res<Difficulty>,
+    session: Option<Res<Session>>,
+) {
+    // Online both players have to play by the same rules, not the ones
+    // either save would set
+    let online = session.as_deref().and_then(Session::players).is_some();
+    commands.insert_resource(pickup::Coins::default());
+    commands.insert_resource(score::Score::default());
+
+    let Some(level) = levels.current(&assets) else {
+        error!("Entered a level before it finished loading");
+        return;
+    };
+
+    // Straight pieces share colliders, each is still drawn on its own
+    let mut straight = Vec::new();
+    for cube in &level.cubes {
+        let mut bundle = make_cube(cube.x, cube.y, cube.w, cube.h, cube.hue);
+        bundle.sprite.transform.rotate_z(cube.rotation * PI / 180.);
+        bundle.material = cube.material;
+
+        let quarter_turns = cube.rotation / 90.;
+        if quarter_turns.fract() != 0. {
+            commands.spawn(bundle);
+            continue;
+        }
+        let size = if quarter_turns as i32 % 2 == 0 {
+            Vec2::new(cube.w, cube.h)
+        } else {
+            Vec2::new(cube.h, cube.w)
+        };
+        straight.push((
+            cube.material,
+            Rect::from_center_size(Vec2::new(cube.x, cube.y), size),
+        ));
+        // Only drawn, the merged collider stands in for its physics
+        commands.spawn((
+            bundle.sprite,
+            bundle.atlas,
+            lighting::make_occluder(size),
+            bundle.scoped,
+        ));
+    }
+    geometry::spawn_merged(&mut commands, straight);
+
+    for coin in &level.coins {
+        commands.spawn(pickup::make_coin(coin.x, coin.y));
+    }
+
+    // The easy ones are numbered after the rest so splits stay comparable
+    let easy = match *difficulty {
+        Difficulty::Easy => level.easy_checkpoints.as_slice(),
+        _ => &[],
+    };
+    for (i, point) in level.checkpoints.iter().chain(easy).enumerate() {
+        let count = level.checkpoints.len();
+        if i < count && !difficulty.keeps_checkpoint(i, count) {
+            continue;
+        }
+        commands.spawn(checkpoint::make_checkpoint(i, point.x, point.y));
+    }
+
+    commands.insert_resource(CameraLock::default());
+    if let Some(goal) = level.goal {
+        commands.spawn(goal::make_goal(goal.x, goal.y));
+    }
+
+    commands.insert_resource(AimAssist(level.aim_assist.to_radians()));
+    commands.insert_resource(if online {
+        level.hook.clone()
+    } else {
+        shop::upgrade_hook(level.hook.clone(), &save)
+    });
+    for point in &level.anchors {
+        commands.spawn(anchor::make_anchor(point.x, point.y));
+    }
+
+    for point in &level.chasers {
+        commands.spawn(enemy::make_chaser(point.x, point.y));
+    }
+
+    for point in &level.turrets {
+        commands.spawn(enemy::make_turret(point.x, point.y));
+    }
+
+    for placed in &level.spawners {
+        spawner::spawn(&mut commands, placed, *difficulty);
+    }
+
+    for placed in &level.crumbling {
+        commands.spawn(crumble::make_crumbling(placed, *difficulty));
+    }
+
+    for placed in &level.timed {
+        commands.spawn(timed::make_timed(placed, *difficulty));
+    }
+
+    for placed in &level.plates {
+        commands.spawn(plate::make_plate(placed));
+    }
+
+    for placed in &level.portals {
+        portal::spawn(&mut commands, placed);
+    }
+
+    for zone in &level.flip_zones {
+        commands.spawn(gravity::make_zone(zone));
+    }
+
+    for point in &level.flip_orbs {
+        commands.spawn(gravity::make_orb(point.x, point.y));
+    }
+
+    for placed in &level.gravity_volumes {
+        commands.spawn(gravity::make_volume(placed));
+    }
+
+    for placed in &level.crates {
+        commands.spawn(crates::make_crate(placed));
+    }
+
+    for placed in &level.pendulums {
+        pendulum::spawn(&mut commands, placed);
+    }
+
+    for placed in &level.elevators {
+        commands.spawn(elevator::make_elevator(placed));
+    }
+
+    for placed in &level.ziplines {
+        commands.spawn(zipline::make_zipline(placed));
+    }
+
+    for placed in &level.items {
+        commands.spawn(pickup::make_item(placed));
+    }
+
+    for placed in &level.tutorials {
+        commands.spawn(tutorial::make_tutorial(placed));
+    }
+    for placed in &level.npcs {
+        commands.spawn(dialogue::make_npc(placed));
+    }
+    for placed in &level.signs {
+        sign::spawn(&mut commands, placed, &locale);
+    }
+    for placed in &level.props {
+        props::spawn(&mut commands, placed);
+    }
+    for placed in &level.cutscenes {
+        commands.spawn(cutscene::make_timeline(placed));
+    }
+
+    // Ones already found stay gone
+    for placed in &level.abilities {
+        if online || !save.abilities.contains(&placed.ability) {
+            commands.spawn(abilities::make_pickup(placed));
+        }
+    }
+
+    if let Some(placed) = &level.boss {
+        boss::spawn(&mut commands, placed);
+    }
+
+    for light in &level.lights {
+        commands.spawn((
+            lighting::make_light(light.x, light.y, light.radius, light.intensity, light.color),
+            StateScoped(InGame),
+        ));
+    }
+}
+
+/// Console command restarting into a level by its name or file name.
+pub(crate) fn load_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let wanted = args.join(" ");
+    if wanted.is_empty() {
+        return Err("load which level?".into());
+    }
+
+    let levels = world.resource::<Levels>();
+    let assets = world.resource::<Assets<Level>>();
+    let index = (0..levels.names.len())
+        .find(|&i| {
+            let name = assets
+                .get(&levels.handles[i])
+                .map(|level| level.name.as_str());
+            levels.names[i] == wanted
+                || levels.names[i].trim_end_matches(".ron") == wanted
+                || name.is_some_and(|name| name.eq_ignore_ascii_case(&wanted))
+        })
+        .ok_or(format!("no level {wanted}, try one of {:?}", levels.names))?;
+
+    world.resource_mut::<Levels>().current = index;
+    *world.resource_mut::<run::Run>() = run::Run::default();
+    world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Restarting);
+    Ok(String::new())
+}
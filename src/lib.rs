@@ -0,0 +1,262 @@
+// Bevy systems naturally have complex queries and many parameters
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
+mod abilities;
+mod achievements;
+mod anchor;
+mod atlas;
+mod audio;
+mod boss;
+mod camera;
+mod capture;
+mod checkpoint;
+mod console;
+mod crates;
+mod crumble;
+mod cull;
+mod cutscene;
+mod daily;
+mod death;
+mod debug;
+mod deterministic;
+mod dialogue;
+mod difficulty;
+mod elevator;
+mod enemy;
+mod explosion;
+mod frame_step;
+mod geometry;
+mod ghost;
+mod goal;
+mod gravity;
+mod health;
+mod hud;
+mod input;
+mod inspector;
+mod interpolation;
+mod inventory;
+mod kinematic;
+mod launch;
+mod leaderboard;
+mod level;
+mod lighting;
+mod loading;
+mod locale;
+mod map;
+mod menu;
+mod music;
+mod mutators;
+mod netplay;
+mod overlay;
+mod palette;
+mod pendulum;
+mod photo;
+mod pickup;
+mod plate;
+mod player;
+mod pool;
+mod portal;
+mod projectile;
+mod props;
+mod replay;
+mod reticle;
+mod rollback;
+mod rope;
+mod run;
+mod save;
+mod score;
+mod settings;
+mod shop;
+mod sign;
+mod skins;
+mod spawner;
+mod squash;
+mod state;
+mod stats;
+mod substeps;
+mod surface;
+mod time_scale;
+mod timed;
+mod touch;
+mod trail;
+mod tuning;
+mod tutorial;
+mod weather;
+mod world;
+mod wrap;
+mod zipline;
+
+use std::time::Duration;
+
+use avian2d::prelude::*;
+use bevy::{
+    app::PluginGroupBuilder,
+    asset::AssetMetaCheck,
+    audio::{AudioPlugin, SpatialScale},
+    gizmos::GizmoPlugin,
+    input::InputPlugin,
+    prelude::*,
+    render::render_resource::Shader,
+    scene::ScenePlugin,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use bevy_egui::EguiPlugin;
+use bevy_light_2d::prelude::*;
+
+pub use achievements::AchievementsPlugin;
+pub use atlas::AtlasPlugin;
+pub use audio::SfxPlugin;
+pub use boss::BossPlugin;
+pub use camera::CameraPlugin;
+pub use capture::CapturePlugin;
+pub use console::ConsolePlugin;
+pub use cutscene::CutscenePlugin;
+pub use debug::DebugPlugin;
+pub use deterministic::DeterministicPlugin;
+pub use dialogue::DialoguePlugin;
+pub use enemy::EnemyPlugin;
+pub use hud::HudPlugin;
+pub use input::ActionsPlugin;
+pub use launch::LaunchPlugin;
+pub use leaderboard::LeaderboardPlugin;
+pub use loading::LoadingPlugin;
+pub use map::MapPlugin;
+pub use menu::MenuPlugin;
+pub use music::MusicPlugin;
+pub use netplay::NetplayPlugin;
+pub use photo::PhotoPlugin;
+pub use player::PlayerPlugin;
+pub use projectile::ProjectilePlugin;
+pub use replay::ReplayPlugin;
+pub use rollback::SyncTestPlugin;
+pub use settings::SettingsPlugin;
+pub use state::StatePlugin;
+pub use time_scale::TimeScalePlugin;
+pub use touch::TouchPlugin;
+pub use tutorial::TutorialPlugin;
+pub use world::WorldPlugin;
+
+/// The whole game, minus Bevy's `DefaultPlugins` which the caller sets up.
+pub struct GamePlugins;
+
+impl PluginGroup for GamePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(PhysicsPlugins::new(FixedPostUpdate))
+            .add(Light2dPlugin)
+            .add(EguiPlugin)
+            .add(StatePlugin)
+            .add(AtlasPlugin)
+            .add(LoadingPlugin)
+            .add(SettingsPlugin)
+            .add(ActionsPlugin)
+            .add(TouchPlugin)
+            .add(CameraPlugin)
+            .add(WorldPlugin)
+            .add(PlayerPlugin)
+            .add(EnemyPlugin)
+            .add(ProjectilePlugin)
+            .add(BossPlugin)
+            .add(ReplayPlugin)
+            .add(NetplayPlugin)
+            .add(TimeScalePlugin)
+            .add(LeaderboardPlugin)
+            .add(HudPlugin)
+            .add(MapPlugin)
+            .add(TutorialPlugin)
+            .add(DialoguePlugin)
+            .add(CutscenePlugin)
+            .add(AchievementsPlugin)
+            .add(MenuPlugin)
+            .add(PhotoPlugin)
+            .add(SfxPlugin)
+            .add(MusicPlugin)
+            .add(CapturePlugin)
+            .add(DebugPlugin)
+            .add(ConsolePlugin)
+    }
+}
+
+/// The game ready to run.
+///
+/// Headless leaves out the window, rendering, audio and menus and keeps the
+/// simulation: levels, physics, input, the player and enemies. Each
+/// [`App::update`] is then exactly one fixed tick, for tests and tools that
+/// press keys through `ButtonInput`.
+pub fn build_app(headless: bool) -> App {
+    if headless {
+        return headless_app(AssetPlugin::default());
+    }
+
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    fit_canvas_to_parent: true,
+                    prevent_default_event_handling: false,
+                    ..default()
+                }),
+                ..default()
+            })
+            .set(AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            })
+            .set(AudioPlugin {
+                // World units are pixels, sounds fade out over a few screens
+                default_spatial_scale: SpatialScale::new_2d(1. / 800.),
+                ..default()
+            }),
+        GamePlugins,
+    ));
+    app
+}
+
+pub(crate) fn headless_app(assets: AssetPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        assets,
+        StatesPlugin,
+        TransformPlugin,
+        HierarchyPlugin,
+        InputPlugin,
+        ScenePlugin,
+        PhysicsPlugins::new(FixedPostUpdate),
+        StatePlugin,
+        ActionsPlugin,
+        WorldPlugin,
+        PlayerPlugin,
+        EnemyPlugin,
+        ProjectilePlugin,
+    ))
+    // What the renderer would otherwise provide for sprites and gizmos
+    .init_asset::<Shader>()
+    .init_asset::<Image>()
+    .init_asset::<Mesh>()
+    .init_asset::<ColorMaterial>()
+    .add_plugins(GizmoPlugin)
+    // Normally loaded at Startup, which comes after the first state transition
+    .init_resource::<save::SaveData>()
+    .init_resource::<camera::MyWorldCoords>()
+    .init_resource::<settings::GameplaySettings>()
+    .init_resource::<settings::AssistSettings>()
+    .init_resource::<cutscene::Cutscene>()
+    .init_resource::<replay::Replay>()
+    .init_resource::<difficulty::Difficulty>()
+    .init_resource::<skins::SkinSheets>()
+    .init_resource::<locale::Locale>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1. / world::FIXED_HZ,
+    )));
+    app
+}
+
+// fn sprite_movement(time: Res<Time>, mut sprite_position: Query<&mut Transform, With<Sprite>>) {
+//     for mut transform in &mut sprite_position {
+//         transform.rotate_z(3. * time.delta_seconds());
+//     }
+// }
@@ -0,0 +1,86 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_light_2d::prelude::*;
+
+/// How bright the level is without any point lights. 1.0 is fully lit.
+const DEFAULT_AMBIENT: f32 = 0.6;
+
+/// Helper for placing a point light in a level, analogous to `make_cube`.
+pub(crate) fn make_light(
+    x: f32,
+    y: f32,
+    radius: f32,
+    intensity: f32,
+    color: Color,
+) -> PointLight2dBundle {
+    PointLight2dBundle {
+        point_light: PointLight2d {
+            color,
+            intensity,
+            radius,
+            falloff: 2.0,
+            cast_shadows: true,
+        },
+        transform: Transform::from_xyz(x, y, 0.),
+        ..default()
+    }
+}
+
+/// The light carried around by the player.
+pub(crate) fn player_light() -> PointLight2dBundle {
+    make_light(0., 0., 400., 1.5, Color::srgb(1.0, 0.9, 0.7))
+}
+
+pub(crate) fn ambient_light(brightness: f32) -> AmbientLight2d {
+    AmbientLight2d {
+        color: Color::WHITE,
+        brightness,
+    }
+}
+
+pub(crate) fn default_ambient() -> AmbientLight2d {
+    ambient_light(DEFAULT_AMBIENT)
+}
+
+/// Turn static geometry into light occluders as it gets spawned.
+///
+/// Occluders can only be axis aligned rectangles, so rotated geometry is left
+/// alone rather than casting a shadow of the wrong shape.
+pub(crate) fn add_occluders(
+    mut commands: Commands,
+    new_geo: Query<(Entity, &Transform, &Sprite, &RigidBody), Added<Collider>>,
+) {
+    for (entity, transform, sprite, body) in &new_geo {
+        if !body.is_static() {
+            continue;
+        }
+
+        let Some(size) = sprite.custom_size else {
+            continue;
+        };
+
+        let (_, angle) = transform.rotation.to_axis_angle();
+        let quarter_turns = angle / std::f32::consts::FRAC_PI_2;
+        if (quarter_turns - quarter_turns.round()).abs() > 0.001 {
+            continue;
+        }
+
+        // Rotated by 90 degrees swaps width and height
+        let size = if quarter_turns.round() as i32 % 2 == 0 {
+            size
+        } else {
+            Vec2::new(size.y, size.x)
+        };
+
+        commands.entity(entity).insert(make_occluder(size));
+    }
+}
+
+/// Casts a shadow the shape of an axis aligned rectangle.
+pub(crate) fn make_occluder(size: Vec2) -> LightOccluder2d {
+    LightOccluder2d {
+        shape: LightOccluder2dShape::Rectangle {
+            half_size: size / 2.0,
+        },
+    }
+}
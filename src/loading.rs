@@ -0,0 +1,109 @@
+use bevy::{
+    asset::{RecursiveDependencyLoadState, UntypedAssetId},
+    prelude::*,
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    atlas::Atlas, audio::Sounds, level::Levels, locale::Locale, skins::SkinSheets, state::AppState,
+};
+
+/// Music only loaded once the menu or a level starts
+const MUSIC: [&str; 4] = [
+    "music/menu.wav",
+    "music/gameplay.wav",
+    "music/gameplay_percussion.wav",
+    "music/gameplay_tension.wav",
+];
+
+/// Starts the game on a loading screen until the sounds, music, sprite sheets
+/// and levels are all in memory, so nothing pops in or stays silent the first
+/// time it's needed. On the web each file is a separate download. Add it
+/// after [`StatePlugin`](crate::StatePlugin).
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_state(AppState::Loading)
+            .add_systems(OnEnter(AppState::Loading), preload)
+            .add_systems(
+                Update,
+                (track_loading, loading_screen)
+                    .chain()
+                    .run_if(in_state(AppState::Loading)),
+            );
+    }
+}
+
+/// Handles to what nothing else loads until it's needed, keeping it loaded
+/// for when it is.
+#[derive(Resource)]
+struct Preloaded(Vec<UntypedHandle>);
+
+/// Share of the files loaded so far, from 0.0 to 1.0.
+#[derive(Resource, Default)]
+struct Progress(f32);
+
+fn preload(asset_server: Res<AssetServer>, mut commands: Commands) {
+    let music = MUSIC
+        .iter()
+        .map(|path| asset_server.load::<AudioSource>(*path).untyped())
+        .collect();
+
+    commands.insert_resource(Preloaded(music));
+    commands.init_resource::<Progress>();
+}
+
+/// Goes on to the menu once everything has loaded. Files that failed count as
+/// done, the game carries on without them like it would have anyway.
+fn track_loading(
+    asset_server: Res<AssetServer>,
+    preloaded: Res<Preloaded>,
+    atlas: Res<Atlas>,
+    skins: Res<SkinSheets>,
+    sounds: Res<Sounds>,
+    levels: Res<Levels>,
+    mut progress: ResMut<Progress>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    let done = |id: UntypedAssetId| {
+        matches!(
+            asset_server.get_recursive_dependency_load_state(id),
+            Some(RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed)
+        )
+    };
+
+    // The levels aren't known until the index is in
+    let index_read = !levels.names.is_empty()
+        || asset_server.get_recursive_dependency_load_state(levels.index.id())
+            == Some(RecursiveDependencyLoadState::Failed);
+    let ids: Vec<_> = preloaded
+        .0
+        .iter()
+        .map(UntypedHandle::id)
+        .chain([atlas.0.id().untyped()])
+        .chain(skins.all().map(|sheet| sheet.id().untyped()))
+        .chain(sounds.all().map(|sound| sound.id().untyped()))
+        .chain(levels.handles.iter().map(|level| level.id().untyped()))
+        .collect();
+    let loaded = ids.iter().filter(|&&id| done(id)).count();
+
+    progress.0 = loaded as f32 / ids.len().max(1) as f32;
+    if index_read && loaded == ids.len() {
+        next.set(AppState::Menu);
+    }
+}
+
+fn loading_screen(mut contexts: EguiContexts, progress: Res<Progress>, locale: Res<Locale>) {
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 2. - 30.);
+            ui.heading(locale.get("loading"));
+            ui.add(
+                egui::ProgressBar::new(progress.0)
+                    .desired_width(300.)
+                    .show_percentage(),
+            );
+        });
+    });
+}
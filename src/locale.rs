@@ -0,0 +1,113 @@
+use std::{collections::HashMap, fmt::Display};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The languages the game has been translated to. Their messages are built
+/// in so the menus have text before any asset has loaded.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub(crate) const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// What the language calls itself, so it can be found from any other.
+    pub(crate) fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../assets/locale/en.lang"),
+            Language::German => include_str!("../assets/locale/de.lang"),
+        }
+    }
+}
+
+/// Reads the game's own locale files: one `id = text` per line, `#`
+/// comments, and `{ $name }` placeables filled in by [`Locale::format`].
+/// Messages that depend on a number come as `id.one` and `id.other`, see
+/// [`Locale::plural`].
+fn parse(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (id, text) = line.split_once('=')?;
+            Some((id.trim(), text.trim()))
+        })
+        .collect()
+}
+
+/// The text shown to the player in the chosen [`Language`].
+#[derive(Resource)]
+pub(crate) struct Locale {
+    language: Language,
+    messages: HashMap<&'static str, &'static str>,
+    /// English, for anything the language is missing
+    fallback: HashMap<&'static str, &'static str>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new(Language::default())
+    }
+}
+
+impl Locale {
+    pub(crate) fn new(language: Language) -> Self {
+        Self {
+            language,
+            messages: parse(language.source()),
+            fallback: parse(Language::English.source()),
+        }
+    }
+
+    /// The message with this id. Ids that aren't in any locale file come
+    /// back as they are, so level text that isn't translated still shows.
+    pub(crate) fn get<'a>(&self, id: &'a str) -> &'a str {
+        self.find(id).unwrap_or(id)
+    }
+
+    /// The message with this id, if any locale file has it.
+    pub(crate) fn find(&self, id: &str) -> Option<&'static str> {
+        self.messages
+            .get(id)
+            .or_else(|| self.fallback.get(id))
+            .copied()
+    }
+
+    /// [`get`](Self::get) with each `{ $name }` replaced by its argument.
+    pub(crate) fn format(&self, id: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(id).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{ ${name} }}"), &value.to_string());
+        }
+        text
+    }
+
+    /// [`format`](Self::format) with the form of the message for `count`,
+    /// which is also its `{ $count }`. Both languages only tell one apart
+    /// from any other number.
+    pub(crate) fn plural(&self, id: &str, count: usize, args: &[(&str, &dyn Display)]) -> String {
+        let form = if count == 1 { "one" } else { "other" };
+        let mut args = args.to_vec();
+        args.push(("count", &count));
+        self.format(&format!("{id}.{form}"), &args)
+    }
+}
+
+/// Switches the messages over when the language setting changes.
+pub(crate) fn apply(language: Res<Language>, mut locale: ResMut<Locale>) {
+    if locale.language != *language {
+        *locale = Locale::new(*language);
+    }
+}
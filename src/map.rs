@@ -0,0 +1,221 @@
+use bevy::{color::palettes::css::GOLD, prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    camera::MainCamera,
+    cull::Bounds,
+    level::{Level, Levels},
+    locale::Locale,
+    netplay,
+    player::{Player, Remote},
+    save::SaveData,
+    state::{AppState, GameplaySet, InGame},
+};
+
+/// Side of a square of the level that's explored all at once
+const CELL: f32 = 250.;
+/// Cells around the player's that are explored along with it
+const REVEAL: i32 = 2;
+/// Room left around the level when it's fitted to the screen
+const PADDING: f32 = 1.1;
+/// In front of everything in the level
+const FOG_Z: f32 = 500.;
+const FOG_COLOR: Color = Color::srgb(0.04, 0.04, 0.06);
+
+/// Tab brings up the whole level with the parts never visited hidden. What's
+/// been explored is kept in the save for each level.
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapView>()
+            // The level keeps going online, under the map
+            .add_systems(Update, toggle_map.run_if(not(netplay::online)))
+            .add_systems(Update, explore.in_set(GameplaySet))
+            .add_systems(OnEnter(AppState::Map), (save_explored, show_map))
+            .add_systems(OnExit(AppState::Map), hide_map)
+            .add_systems(OnExit(InGame), save_explored)
+            .add_systems(
+                Update,
+                (map_panel, mark_player).run_if(in_state(AppState::Map)),
+            );
+    }
+}
+
+/// Covers a cell of the map that hasn't been explored.
+#[derive(Component)]
+struct Fog;
+
+#[derive(Resource, Default)]
+struct MapView {
+    /// Where the camera was before the map took it over
+    camera: Option<Transform>,
+    /// Cells were explored since the save was last written
+    unsaved: bool,
+}
+
+fn cell(position: Vec2) -> IVec2 {
+    (position / CELL).floor().as_ivec2()
+}
+
+/// Every cell touching `area`.
+fn cells(area: Rect) -> impl Iterator<Item = IVec2> {
+    let (min, max) = (cell(area.min), cell(area.max));
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+}
+
+fn toggle_map(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<AppState>>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    match state.get() {
+        AppState::Playing if keys.just_pressed(KeyCode::Tab) => next.set(AppState::Map),
+        AppState::Map if keys.any_just_pressed([KeyCode::Tab, KeyCode::Escape]) => {
+            next.set(AppState::Playing)
+        }
+        _ => (),
+    }
+}
+
+/// Marks the cells around the player as explored.
+fn explore(
+    players: Query<&Transform, (With<Player>, Without<Remote>)>,
+    levels: Res<Levels>,
+    mut save: ResMut<SaveData>,
+    mut view: ResMut<MapView>,
+    mut last: Local<Option<IVec2>>,
+) {
+    let Some(player) = players.iter().next() else {
+        *last = None;
+        return;
+    };
+    let here = cell(player.translation.truncate());
+    // Only once per cell entered, instead of every frame
+    if *last == Some(here) {
+        return;
+    }
+    *last = Some(here);
+    let Some(name) = levels.names.get(levels.current) else {
+        return;
+    };
+
+    let explored = save.explored.entry(name.clone()).or_default();
+    for y in -REVEAL..=REVEAL {
+        for x in -REVEAL..=REVEAL {
+            view.unsaved |= explored.insert(here + IVec2::new(x, y));
+        }
+    }
+}
+
+fn save_explored(mut view: ResMut<MapView>, save: Res<SaveData>) {
+    if view.unsaved {
+        view.unsaved = false;
+        save.write();
+    }
+}
+
+/// Fits the level on screen and covers the parts not explored yet.
+fn show_map(
+    mut view: ResMut<MapView>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    bounds: Res<Bounds>,
+    levels: Res<Levels>,
+    save: Res<SaveData>,
+    mut commands: Commands,
+) {
+    let area = bounds.level();
+    let explored = levels
+        .names
+        .get(levels.current)
+        .and_then(|name| save.explored.get(name));
+
+    for cell in cells(area) {
+        if explored.is_some_and(|explored| explored.contains(&cell)) {
+            continue;
+        }
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: FOG_COLOR,
+                    custom_size: Some(Vec2::splat(CELL)),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    ((cell.as_vec2() + 0.5) * CELL).extend(FOG_Z),
+                ),
+                ..default()
+            },
+            Fog,
+            StateScoped(InGame),
+        ));
+    }
+
+    let (Some(mut transform), Some(window)) = (cameras.iter_mut().next(), windows.iter().next())
+    else {
+        return;
+    };
+    view.camera = Some(*transform);
+    let scale = PADDING * (area.size() / window.size()).max_element();
+    transform.translation = area.center().extend(transform.translation.z);
+    // Keeps the sign, the level may be mirrored
+    transform.scale = Vec3::new(scale.copysign(transform.scale.x), scale, 1.);
+}
+
+fn hide_map(
+    mut view: ResMut<MapView>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+    fog: Query<Entity, With<Fog>>,
+    mut commands: Commands,
+) {
+    for entity in &fog {
+        commands.entity(entity).despawn();
+    }
+    if let Some(camera) = view.camera.take() {
+        for mut transform in &mut cameras {
+            *transform = camera;
+        }
+    }
+}
+
+/// The player can be hard to spot with the whole level on screen.
+fn mark_player(mut gizmos: Gizmos, players: Query<&Transform, With<Player>>) {
+    for player in &players {
+        gizmos.circle_2d(player.translation.truncate(), CELL / 2., GOLD);
+    }
+}
+
+/// The level's name and how much of it has been explored.
+fn map_panel(
+    mut contexts: EguiContexts,
+    levels: Res<Levels>,
+    assets: Res<Assets<Level>>,
+    bounds: Res<Bounds>,
+    save: Res<SaveData>,
+    locale: Res<Locale>,
+) {
+    let Some(level) = levels.current(&assets) else {
+        return;
+    };
+    let area = bounds.level();
+    let explored = levels
+        .names
+        .get(levels.current)
+        .and_then(|name| save.explored.get(name));
+    let total = cells(area).count();
+    let seen = cells(area)
+        .filter(|cell| explored.is_some_and(|explored| explored.contains(cell)))
+        .count();
+    let percent = 100 * seen / total.max(1);
+
+    egui::Area::new(egui::Id::new("map"))
+        .anchor(egui::Align2::CENTER_TOP, [0., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading(&level.name);
+                ui.label(locale.format("map-explored", &[("percent", &percent)]));
+                ui.label(egui::RichText::new(locale.get("map-close")).weak());
+            });
+        });
+}
@@ -0,0 +1,721 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    daily::{self, Challenge, Daily},
+    leaderboard::{self, Leaderboard, LeaderboardConfig},
+    level::{Level, Levels, Medal},
+    locale::Locale,
+    mutators::{self, Mutator, Mutators},
+    netplay::{self, Lobby, Session},
+    pickup::Coins,
+    replay::Replay,
+    run::{self, Run},
+    save::SaveData,
+    score::Score,
+    settings::{self, SettingsMenu},
+    shop::{self, ShopMenu},
+    skins::{self, SkinMenu},
+    state::{self, AppState, InGame},
+    stats::{self, StatsMenu},
+};
+
+const BUTTON_SIZE: [f32; 2] = [200., 40.];
+
+/// The egui screens: main menu, level select, pause, death, level complete,
+/// settings and the shop.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsMenu>()
+            .init_resource::<ShopMenu>()
+            .init_resource::<StatsMenu>()
+            .init_resource::<SkinMenu>()
+            .add_systems(Update, main_menu.run_if(in_state(AppState::Menu)))
+            .add_systems(Update, level_select.run_if(in_state(AppState::LevelSelect)))
+            .add_systems(Update, pause_menu.run_if(in_state(AppState::Paused)))
+            .add_systems(Update, death_screen.run_if(in_state(AppState::Dead)))
+            .add_systems(
+                Update,
+                level_complete.run_if(in_state(AppState::LevelComplete)),
+            )
+            .add_systems(
+                Update,
+                (
+                    // Escape closes the settings before it unpauses
+                    // Nothing stops for the other player online
+                    state::toggle_pause
+                        .run_if(settings::settings_closed)
+                        .run_if(not(netplay::online)),
+                    settings::settings_input,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, settings::settings_window)
+            .add_systems(Update, shop::shop_window)
+            .add_systems(Update, stats::stats_window)
+            .add_systems(Update, skins::skin_window)
+            .add_systems(Update, netplay::lobby_window)
+            .add_systems(
+                Update,
+                netplay::status.run_if(resource_exists::<Session>.and_then(in_state(InGame))),
+            );
+    }
+}
+
+/// Offers to watch the last attempt, whichever screen it's on. Not online,
+/// where the other side would have to watch along.
+fn watch_replay_button(
+    ui: &mut egui::Ui,
+    locale: &Locale,
+    replay: &mut Replay,
+    levels: &mut Levels,
+    run: &mut Run,
+    next: &mut NextState<AppState>,
+    online: bool,
+) {
+    if ui
+        .add_enabled(
+            replay.last.is_some() && !online,
+            egui::Button::new(locale.get("menu-watch-replay")).min_size(BUTTON_SIZE.into()),
+        )
+        .clicked()
+    {
+        if let Some(recording) = replay.last.clone() {
+            replay.watch(recording, levels, run, next);
+        }
+    }
+}
+
+/// Starts today's challenge, or says how it went once it's been played.
+fn daily_button(
+    ui: &mut egui::Ui,
+    locale: &Locale,
+    daily: &mut Daily,
+    mutators: &mut Mutators,
+    save: &mut SaveData,
+    levels: &mut Levels,
+    assets: &Assets<Level>,
+    run: &mut Run,
+    next: &mut NextState<AppState>,
+) {
+    let Ok(day) = daily::today() else {
+        return;
+    };
+    let challenge = Challenge::new(day, levels.names.len());
+    let played = save.daily.filter(|attempt| attempt.day == day);
+    let loaded = levels
+        .handles
+        .get(challenge.level)
+        .is_some_and(|handle| assets.contains(handle));
+
+    if ui
+        .add_enabled(
+            loaded && played.is_none(),
+            egui::Button::new(locale.get("menu-daily")).min_size(BUTTON_SIZE.into()),
+        )
+        .clicked()
+    {
+        levels.current = challenge.level;
+        daily::start(daily, mutators, save, run, challenge);
+        next.set(AppState::Playing);
+        return;
+    }
+
+    let text = match played {
+        Some(daily::Attempt {
+            time: Some(time), ..
+        }) => locale.format("daily-done", &[("time", &run::format_time(time))]),
+        Some(_) => locale.get("daily-given-up").to_string(),
+        None => locale.format(
+            "daily-modifiers",
+            &[(
+                "modifiers",
+                &mutators::describe(locale, &challenge.mutators),
+            )],
+        ),
+    };
+    ui.label(egui::RichText::new(text).weak());
+}
+
+fn main_menu(
+    mut contexts: EguiContexts,
+    mut next: ResMut<NextState<AppState>>,
+    mut settings: ResMut<SettingsMenu>,
+    mut stats: ResMut<StatsMenu>,
+    mut skins: ResMut<SkinMenu>,
+    mut levels: ResMut<Levels>,
+    assets: Res<Assets<Level>>,
+    mut replay: ResMut<Replay>,
+    mut run: ResMut<Run>,
+    mut daily: ResMut<Daily>,
+    mut mutators: ResMut<Mutators>,
+    mut save: ResMut<SaveData>,
+    mut exit: EventWriter<AppExit>,
+    mut lobby: ResMut<Lobby>,
+    session: Option<Res<Session>>,
+    locale: Res<Locale>,
+) {
+    let following = session.as_deref().is_some_and(Session::following);
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 4.);
+            ui.heading("Glatformer");
+            ui.add_space(40.);
+
+            ui.add_enabled_ui(!following, |ui| {
+                levels.current = 0;
+                let loaded = levels.current(&assets).is_some();
+                if ui
+                    .add_enabled(
+                        loaded,
+                        egui::Button::new(locale.get("menu-play")).min_size(BUTTON_SIZE.into()),
+                    )
+                    .clicked()
+                {
+                    next.set(AppState::Playing);
+                }
+
+                if ui
+                    .add_sized(
+                        BUTTON_SIZE,
+                        egui::Button::new(locale.get("menu-level-select")),
+                    )
+                    .clicked()
+                {
+                    next.set(AppState::LevelSelect);
+                }
+
+                daily_button(
+                    ui,
+                    &locale,
+                    &mut daily,
+                    &mut mutators,
+                    &mut save,
+                    &mut levels,
+                    &assets,
+                    &mut run,
+                    &mut next,
+                );
+            });
+            follow_notice(ui, &locale, following);
+            watch_replay_button(
+                ui,
+                &locale,
+                &mut replay,
+                &mut levels,
+                &mut run,
+                &mut next,
+                session.is_some(),
+            );
+
+            if ui
+                .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-settings")))
+                .clicked()
+            {
+                settings.open = true;
+            }
+
+            if ui
+                .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-stats")))
+                .clicked()
+            {
+                stats.open = true;
+            }
+
+            if ui
+                .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-skins")))
+                .clicked()
+            {
+                skins.open = true;
+            }
+
+            // Browsers can't open sockets
+            if cfg!(not(target_arch = "wasm32"))
+                && ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-online")))
+                    .clicked()
+            {
+                lobby.open = true;
+            }
+
+            // There's nothing to quit to in the browser
+            if cfg!(not(target_arch = "wasm32"))
+                && ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-quit")))
+                    .clicked()
+            {
+                exit.send(AppExit::Success);
+            }
+        });
+    });
+}
+
+fn pause_menu(
+    mut contexts: EguiContexts,
+    mut next: ResMut<NextState<AppState>>,
+    mut settings: ResMut<SettingsMenu>,
+    mut run: ResMut<Run>,
+    daily: Res<Daily>,
+    locale: Res<Locale>,
+) {
+    if settings.open {
+        return;
+    }
+
+    egui::Window::new(locale.get("paused"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                if ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("paused-resume")))
+                    .clicked()
+                {
+                    next.set(AppState::Playing);
+                }
+                // The daily challenge only gets the one attempt
+                if daily.0.is_none()
+                    && ui
+                        .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("paused-restart")))
+                        .clicked()
+                {
+                    // From the very start this time
+                    *run = Run::default();
+                    next.set(AppState::Restarting);
+                }
+
+                if ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("paused-photo")))
+                    .clicked()
+                {
+                    next.set(AppState::PhotoMode);
+                }
+
+                if ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-settings")))
+                    .clicked()
+                {
+                    settings.open = true;
+                }
+
+                if ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("paused-quit")))
+                    .clicked()
+                {
+                    next.set(AppState::Menu);
+                }
+            });
+        });
+}
+
+fn death_screen(
+    mut contexts: EguiContexts,
+    mut next: ResMut<NextState<AppState>>,
+    mut run: ResMut<Run>,
+    mut levels: ResMut<Levels>,
+    mut replay: ResMut<Replay>,
+    session: Option<Res<Session>>,
+    locale: Res<Locale>,
+) {
+    let following = session.as_deref().is_some_and(Session::following);
+    egui::Window::new(locale.get("died"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(locale.format("died-deaths", &[("count", &run.deaths)]));
+                ui.add_space(10.);
+
+                if ui
+                    .add_enabled(
+                        !following,
+                        egui::Button::new(locale.get("died-retry")).min_size(BUTTON_SIZE.into()),
+                    )
+                    .clicked()
+                {
+                    next.set(AppState::Restarting);
+                }
+                follow_notice(ui, &locale, following);
+                watch_replay_button(
+                    ui,
+                    &locale,
+                    &mut replay,
+                    &mut levels,
+                    &mut run,
+                    &mut next,
+                    session.is_some(),
+                );
+                if ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("paused-quit")))
+                    .clicked()
+                {
+                    next.set(AppState::Menu);
+                }
+            });
+        });
+}
+
+fn level_select(
+    mut contexts: EguiContexts,
+    mut next: ResMut<NextState<AppState>>,
+    mut shop: ResMut<ShopMenu>,
+    mut levels: ResMut<Levels>,
+    assets: Res<Assets<Level>>,
+    save: Res<SaveData>,
+    mut mutators: ResMut<Mutators>,
+    leaderboard: Res<Leaderboard>,
+    config: Res<LeaderboardConfig>,
+    session: Option<Res<Session>>,
+    locale: Res<Locale>,
+) {
+    let following = session.as_deref().is_some_and(Session::following);
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(40.);
+            ui.heading(locale.get("menu-level-select"));
+            ui.add_space(20.);
+
+            egui::Grid::new("levels")
+                .striped(true)
+                .min_col_width(120.)
+                .show(ui, |ui| {
+                    for i in 0..levels.handles.len() {
+                        let level = assets.get(&levels.handles[i]);
+                        let progress = save
+                            .levels
+                            .get(&levels.names[i])
+                            .filter(|progress| progress.best_time.is_finite());
+
+                        let unlocked = save.is_unlocked(&levels, i);
+
+                        ui.label(
+                            level.map_or(locale.get("level-loading"), |level| level.name.as_str()),
+                        );
+                        match (level, progress) {
+                            (Some(level), Some(progress)) => {
+                                let medal = level
+                                    .medals
+                                    .award(progress.best_time)
+                                    .map_or("level-no-medal", Medal::message);
+                                let mut text = locale.format(
+                                    "level-progress",
+                                    &[
+                                        ("time", &run::format_time(progress.best_time)),
+                                        ("medal", &locale.get(medal)),
+                                        ("coins", &progress.coins),
+                                        ("total", &level.coins.len()),
+                                    ],
+                                );
+                                if !progress.best_mutators.is_empty() {
+                                    let names =
+                                        mutators::describe(&locale, &progress.best_mutators);
+                                    text += "  ";
+                                    text +=
+                                        &locale.format("mutators-with", &[("mutators", &names)]);
+                                }
+                                ui.label(text);
+                            }
+                            _ if !unlocked => {
+                                ui.label(egui::RichText::new(locale.get("level-locked")).weak());
+                            }
+                            _ => {
+                                ui.label(
+                                    egui::RichText::new(locale.get("level-not-completed")).weak(),
+                                );
+                            }
+                        }
+
+                        if config.endpoint.is_some() {
+                            match leaderboard
+                                .top
+                                .get(&levels.names[i])
+                                .and_then(|top| top.first())
+                            {
+                                Some(record) => ui.label(locale.format(
+                                    "level-record",
+                                    &[
+                                        ("time", &run::format_time(record.time)),
+                                        ("name", &record.name),
+                                    ],
+                                )),
+                                None => ui.label(
+                                    egui::RichText::new(locale.get("level-no-record")).weak(),
+                                ),
+                            };
+                        }
+
+                        if ui
+                            .add_enabled(
+                                level.is_some() && unlocked && !following,
+                                egui::Button::new(locale.get("menu-play")),
+                            )
+                            .clicked()
+                        {
+                            levels.current = i;
+                            next.set(AppState::Playing);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            offline_notice(ui, &locale, &leaderboard);
+            follow_notice(ui, &locale, following);
+
+            ui.add_space(20.);
+            ui.add_enabled_ui(!following, |ui| {
+                mutator_toggles(ui, &locale, &mut mutators);
+            });
+
+            ui.add_space(20.);
+            if ui
+                .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-shop")))
+                .clicked()
+            {
+                shop.open = true;
+            }
+            if ui
+                .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-back")))
+                .clicked()
+            {
+                next.set(AppState::Menu);
+            }
+        });
+    });
+}
+
+/// Checkboxes for the mutators the levels are played with.
+fn mutator_toggles(ui: &mut egui::Ui, locale: &Locale, mutators: &mut Mutators) {
+    ui.label(egui::RichText::new(locale.get("mutators")).strong());
+    ui.horizontal_wrapped(|ui| {
+        for mutator in Mutator::ALL {
+            let mut on = mutators.has(mutator);
+            if ui
+                .checkbox(&mut on, locale.get(mutator.message()))
+                .changed()
+            {
+                if on {
+                    mutators.0.insert(mutator);
+                } else {
+                    mutators.0.remove(&mutator);
+                }
+            }
+        }
+    });
+}
+
+/// The results of a finished level and where to go next.
+fn level_complete(
+    mut contexts: EguiContexts,
+    mut next: ResMut<NextState<AppState>>,
+    mut levels: ResMut<Levels>,
+    assets: Res<Assets<Level>>,
+    mut run: ResMut<Run>,
+    mut shop: ResMut<ShopMenu>,
+    coins: Res<Coins>,
+    score: Res<Score>,
+    save: Res<SaveData>,
+    mut replay: ResMut<Replay>,
+    leaderboard: Res<Leaderboard>,
+    config: Res<LeaderboardConfig>,
+    daily: Res<Daily>,
+    mutators: Res<Mutators>,
+    session: Option<Res<Session>>,
+    locale: Res<Locale>,
+) {
+    if shop.open {
+        return;
+    }
+    let Some(level) = levels.current(&assets) else {
+        return;
+    };
+    let time = run.time.elapsed_secs();
+    let medal = level.medals.award(time);
+    // Already updated with this run, daily runs don't count
+    let best = levels
+        .names
+        .get(levels.current)
+        .filter(|_| daily.0.is_none())
+        .and_then(|name| save.levels.get(name))
+        .map(|progress| progress.best_time)
+        .filter(|best| best.is_finite());
+
+    egui::Window::new(locale.get("complete"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading(&level.name);
+                ui.add_space(10.);
+
+                egui::Grid::new("stats").show(ui, |ui| {
+                    ui.label(locale.get("complete-time"));
+                    ui.label(run::format_time(time));
+                    ui.end_row();
+                    if !mutators.0.is_empty() {
+                        ui.label(locale.get("mutators"));
+                        ui.label(mutators::describe(&locale, &mutators.0));
+                        ui.end_row();
+                    }
+                    if let Some(best) = best {
+                        ui.label(locale.get("complete-best"));
+                        if time <= best {
+                            ui.label(egui::RichText::new(locale.get("complete-new-best")).strong());
+                        } else {
+                            ui.label(format!(
+                                "{}  ({})",
+                                run::format_time(best),
+                                run::format_delta(time - best)
+                            ));
+                        }
+                        ui.end_row();
+                    }
+                    ui.label(locale.get("complete-coins"));
+                    ui.label(format!("{} / {}", coins.0, level.coins.len()));
+                    ui.end_row();
+                    ui.label(locale.get("complete-deaths"));
+                    ui.label(run.deaths.to_string());
+                    ui.end_row();
+                    for (name, value) in run.stats.rows() {
+                        // Already counted above
+                        if name == "stats-deaths" {
+                            continue;
+                        }
+                        ui.label(locale.get(name));
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                    ui.label(locale.get("complete-time-bonus"));
+                    ui.label(score.time_bonus.to_string());
+                    ui.end_row();
+                    ui.label(locale.get("complete-score"));
+                    ui.label(egui::RichText::new(score.points.to_string()).strong());
+                    ui.end_row();
+                });
+
+                ui.separator();
+                egui::Grid::new("medals").show(ui, |ui| {
+                    for (threshold_medal, threshold) in level.medals.thresholds() {
+                        let text = egui::RichText::new(locale.get(threshold_medal.message()));
+                        // Highlight the medal that was earned
+                        if medal == Some(threshold_medal) {
+                            ui.label(text.strong());
+                        } else {
+                            ui.label(text.weak());
+                        }
+                        ui.label(run::format_time(threshold));
+                        ui.end_row();
+                    }
+                });
+
+                if config.endpoint.is_some() {
+                    ui.separator();
+                    let top = leaderboard::board(&levels, &daily)
+                        .and_then(|board| leaderboard.top.get(&board));
+                    match top {
+                        Some(top) if !top.is_empty() => {
+                            egui::Grid::new("leaderboard").show(ui, |ui| {
+                                for (rank, entry) in top.iter().enumerate() {
+                                    ui.label(format!("{}.", rank + 1));
+                                    ui.label(&entry.name);
+                                    ui.label(run::format_time(entry.time));
+                                    ui.label(
+                                        egui::RichText::new(mutators::describe(
+                                            &locale,
+                                            &entry.mutators,
+                                        ))
+                                        .weak(),
+                                    );
+                                    ui.end_row();
+                                }
+                            });
+                        }
+                        Some(_) => {
+                            ui.label(egui::RichText::new(locale.get("complete-no-times")).weak());
+                        }
+                        None => {
+                            ui.label(
+                                egui::RichText::new(locale.get("complete-loading-leaderboard"))
+                                    .weak(),
+                            );
+                        }
+                    }
+                    offline_notice(ui, &locale, &leaderboard);
+                }
+                ui.add_space(10.);
+
+                // Daily challenges end here, there's one attempt at one level
+                let following = session.as_deref().is_some_and(Session::following);
+                if daily.0.is_none() {
+                    let has_next = levels.has_next();
+                    if ui
+                        .add_enabled(
+                            has_next && !following,
+                            egui::Button::new(locale.get("complete-next"))
+                                .min_size(BUTTON_SIZE.into()),
+                        )
+                        .clicked()
+                    {
+                        levels.current += 1;
+                        *run = Run::default();
+                        next.set(AppState::Restarting);
+                    }
+                    if ui
+                        .add_enabled(
+                            !following,
+                            egui::Button::new(locale.get("complete-replay"))
+                                .min_size(BUTTON_SIZE.into()),
+                        )
+                        .clicked()
+                    {
+                        *run = Run::default();
+                        next.set(AppState::Restarting);
+                    }
+                }
+                follow_notice(ui, &locale, following);
+                watch_replay_button(
+                    ui,
+                    &locale,
+                    &mut replay,
+                    &mut levels,
+                    &mut run,
+                    &mut next,
+                    session.is_some(),
+                );
+
+                if ui
+                    .add_sized(BUTTON_SIZE, egui::Button::new(locale.get("menu-shop")))
+                    .clicked()
+                {
+                    shop.open = true;
+                }
+                if ui
+                    .add_sized(
+                        BUTTON_SIZE,
+                        egui::Button::new(locale.get("menu-level-select")),
+                    )
+                    .clicked()
+                {
+                    next.set(AppState::LevelSelect);
+                }
+            });
+        });
+}
+
+/// Tell the player their times haven't reached the leaderboard yet.
+fn offline_notice(ui: &mut egui::Ui, locale: &Locale, leaderboard: &Leaderboard) {
+    if leaderboard.offline && leaderboard.queued() > 0 {
+        ui.label(egui::RichText::new(locale.plural("offline", leaderboard.queued(), &[])).weak());
+    }
+}
+
+/// Tell the guest online why they can't pick what to play.
+fn follow_notice(ui: &mut egui::Ui, locale: &Locale, following: bool) {
+    if following {
+        ui.label(egui::RichText::new(locale.get("online-follow")).weak());
+    }
+}
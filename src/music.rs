@@ -0,0 +1,260 @@
+use avian2d::prelude::*;
+use bevy::{asset::LoadState, audio::Volume, prelude::*};
+
+use crate::{
+    audio::{SoundCategory, VolumeSettings},
+    boss::Boss,
+    enemy::Enemy,
+    player::{Player, Remote},
+    state::{AppState, InGame},
+};
+
+/// How long a crossfade between two tracks takes, in seconds
+const CROSSFADE: f32 = 1.5;
+/// Seconds for a layer to come all the way in or drop all the way out
+const LAYER_FADE: f32 = 2.;
+/// Player speed at which the percussion is all the way in
+const FULL_SPEED: f32 = 1200.;
+/// Enemies closer than this to the player count towards the tension
+const NEARBY: f32 = 900.;
+/// Nearby enemies for the tension to be all the way in
+const FULL_TENSION: f32 = 3.;
+
+/// Keeps one looping music track playing, crossfading when it changes. The
+/// layers of a track come in over its base as the game gets more intense.
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicManager>()
+            .add_systems(OnEnter(AppState::Menu), start_menu_music)
+            .add_systems(OnEnter(AppState::LevelSelect), start_menu_music)
+            .add_systems(OnEnter(InGame), start_gameplay_music)
+            .add_systems(OnEnter(AppState::Paused), pause_music)
+            .add_systems(OnExit(AppState::Paused), resume_music)
+            .add_systems(
+                Update,
+                measure_intensity.run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Update, (switch_tracks, fade, pause).chain());
+    }
+}
+
+/// Parts of a track, recorded to the same length and played in sync.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Stem {
+    /// Always playing
+    Base,
+    /// Comes in with speed and during boss fights
+    Percussion,
+    /// Comes in with enemies close by and as a boss fight goes on
+    Tension,
+}
+
+/// A piece of music, its base and the layers that go over it.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Track {
+    stems: Vec<(Stem, Handle<AudioSource>)>,
+}
+
+impl Track {
+    pub(crate) fn new(base: Handle<AudioSource>) -> Self {
+        Self {
+            stems: vec![(Stem::Base, base)],
+        }
+    }
+
+    pub(crate) fn with_layer(mut self, stem: Stem, layer: Handle<AudioSource>) -> Self {
+        self.stems.push((stem, layer));
+        self
+    }
+}
+
+/// How far in the layers should be, from 0.0 to 1.0.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Intensity {
+    pub(crate) percussion: f32,
+    pub(crate) tension: f32,
+}
+
+impl Intensity {
+    fn of(self, stem: Stem) -> f32 {
+        match stem {
+            Stem::Base => 1.,
+            Stem::Percussion => self.percussion,
+            Stem::Tension => self.tension,
+        }
+    }
+}
+
+/// Which track should be playing. Levels and menus set this and the manager
+/// takes care of fading between the old and new track.
+#[derive(Resource, Default)]
+pub(crate) struct MusicManager {
+    pub(crate) track: Option<Track>,
+    pub(crate) paused: bool,
+    pub(crate) intensity: Intensity,
+    playing: Option<Track>,
+}
+
+impl MusicManager {
+    pub(crate) fn play(&mut self, track: Track) {
+        self.track = Some(track);
+    }
+}
+
+/// One stem of a track.
+#[derive(Component)]
+struct MusicChannel {
+    stem: Stem,
+    /// 0.0 is silent, 1.0 is the full music volume
+    level: f32,
+    /// How far in the stem is mixed, eased towards its [`Intensity`]
+    mix: f32,
+    fading_out: bool,
+}
+
+fn start_menu_music(mut music: ResMut<MusicManager>, asset_server: Res<AssetServer>) {
+    music.play(Track::new(asset_server.load("music/menu.wav")));
+}
+
+fn start_gameplay_music(mut music: ResMut<MusicManager>, asset_server: Res<AssetServer>) {
+    music.intensity = Intensity::default();
+    music.play(
+        Track::new(asset_server.load("music/gameplay.wav"))
+            .with_layer(
+                Stem::Percussion,
+                asset_server.load("music/gameplay_percussion.wav"),
+            )
+            .with_layer(
+                Stem::Tension,
+                asset_server.load("music/gameplay_tension.wav"),
+            ),
+    );
+}
+
+/// How intense things are right now: how fast the player is going, how many
+/// enemies are close and how far a boss fight has got.
+fn measure_intensity(
+    mut music: ResMut<MusicManager>,
+    players: Query<(&Transform, &LinearVelocity), (With<Player>, Without<Remote>)>,
+    enemies: Query<&Transform, With<Enemy>>,
+    bosses: Query<&Boss>,
+) {
+    let Some((player, velocity)) = players.iter().next() else {
+        return;
+    };
+
+    let position = player.translation.truncate();
+    let nearby = enemies
+        .iter()
+        .filter(|enemy| enemy.translation.truncate().distance(position) < NEARBY)
+        .count();
+    let fight = bosses.iter().find_map(Boss::progress);
+
+    let speed = (velocity.length() / FULL_SPEED).min(1.);
+    let crowd = (nearby as f32 / FULL_TENSION).min(1.);
+    music.intensity = Intensity {
+        percussion: if fight.is_some() { 1. } else { speed },
+        tension: crowd.max(fight.unwrap_or(0.)),
+    };
+}
+
+fn pause_music(mut music: ResMut<MusicManager>) {
+    music.paused = true;
+}
+
+fn resume_music(mut music: ResMut<MusicManager>) {
+    music.paused = false;
+}
+
+fn switch_tracks(
+    mut music: ResMut<MusicManager>,
+    mut channels: Query<&mut MusicChannel>,
+    sources: Res<Assets<AudioSource>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    if music.track == music.playing {
+        return;
+    }
+    // Stems that start on different frames would stay out of sync, ones that
+    // failed to load are left out rather than holding up the rest
+    let loading = music.track.as_ref().is_some_and(|track| {
+        track.stems.iter().any(|(_, handle)| {
+            !sources.contains(handle)
+                && !matches!(asset_server.load_state(handle), LoadState::Failed(_))
+        })
+    });
+    if loading {
+        return;
+    }
+
+    for mut channel in &mut channels {
+        channel.fading_out = true;
+    }
+
+    if let Some(track) = &music.track {
+        for (stem, source) in &track.stems {
+            if !sources.contains(source) {
+                continue;
+            }
+            commands.spawn((
+                AudioBundle {
+                    source: source.clone(),
+                    settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+                },
+                MusicChannel {
+                    stem: *stem,
+                    level: 0.0,
+                    mix: music.intensity.of(*stem),
+                    fading_out: false,
+                },
+            ));
+        }
+    }
+
+    music.playing = music.track.clone();
+}
+
+fn fade(
+    mut channels: Query<(Entity, &mut MusicChannel, Option<&AudioSink>)>,
+    music: Res<MusicManager>,
+    volume: Res<VolumeSettings>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let step = time.delta_seconds() / CROSSFADE;
+    let layer_step = time.delta_seconds() / LAYER_FADE;
+
+    for (entity, mut channel, sink) in &mut channels {
+        if channel.fading_out {
+            channel.level -= step;
+        } else {
+            channel.level += step;
+        }
+        channel.level = channel.level.clamp(0.0, 1.0);
+
+        if channel.fading_out && channel.level <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let mix = music.intensity.of(channel.stem) - channel.mix;
+        channel.mix += mix.clamp(-layer_step, layer_step);
+
+        if let Some(sink) = sink {
+            sink.set_volume(channel.level * channel.mix * volume.get(SoundCategory::Music));
+        }
+    }
+}
+
+fn pause(music: Res<MusicManager>, sinks: Query<&AudioSink, With<MusicChannel>>) {
+    for sink in &sinks {
+        if music.paused && !sink.is_paused() {
+            sink.pause();
+        } else if !music.paused && sink.is_paused() {
+            sink.play();
+        }
+    }
+}
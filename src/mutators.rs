@@ -0,0 +1,189 @@
+use std::collections::BTreeSet;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abilities::{Abilities, Ability},
+    camera::MainCamera,
+    checkpoint::Checkpoint,
+    health::Health,
+    input::ActionState,
+    locale::Locale,
+    player::{HookRules, Movement, Player},
+};
+
+/// How much of its usual reach the hook keeps with [`Mutator::ShortHook`]
+const SHORT_HOOK: f32 = 0.6;
+/// Seconds of slow motion after each shot with [`Mutator::BulletTime`]
+const BULLET_TIME: f32 = 0.4;
+/// Share of the usual gravity left with [`Mutator::LowGravity`]
+const LOW_GRAVITY: f32 = 0.5;
+/// Running speed with [`Mutator::DoubleSpeed`] compared to the usual
+const DOUBLE_SPEED: f32 = 2.;
+
+/// Twists on how a level plays, picked before starting it or by the daily
+/// challenge. They're laid over the usual config when the level starts and
+/// taken off again when it's left.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub(crate) enum Mutator {
+    LowGravity,
+    /// Any hit is fatal
+    OneHit,
+    NoHook,
+    /// Run and accelerate twice as fast
+    DoubleSpeed,
+    /// Left and right swap, on screen and on the controls
+    Mirrored,
+    ShortHook,
+    BulletTime,
+    NoCheckpoints,
+}
+
+impl Mutator {
+    pub(crate) const ALL: [Mutator; 8] = [
+        Mutator::LowGravity,
+        Mutator::OneHit,
+        Mutator::NoHook,
+        Mutator::DoubleSpeed,
+        Mutator::Mirrored,
+        Mutator::ShortHook,
+        Mutator::BulletTime,
+        Mutator::NoCheckpoints,
+    ];
+
+    /// Id of the mutator's name in the locale files.
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Mutator::LowGravity => "mutator-low-gravity",
+            Mutator::OneHit => "mutator-one-hit",
+            Mutator::NoHook => "mutator-no-hook",
+            Mutator::DoubleSpeed => "mutator-double-speed",
+            Mutator::Mirrored => "mutator-mirrored",
+            Mutator::ShortHook => "mutator-short-hook",
+            Mutator::BulletTime => "mutator-bullet-time",
+            Mutator::NoCheckpoints => "mutator-no-checkpoints",
+        }
+    }
+
+    /// Whether the mutator makes levels quicker to finish. Runs played with
+    /// one don't count as personal bests and aren't sent to the leaderboard.
+    pub(crate) fn speeds_up(self) -> bool {
+        matches!(self, Mutator::LowGravity | Mutator::DoubleSpeed)
+    }
+}
+
+/// The mutators the next level is played with.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct Mutators(pub(crate) BTreeSet<Mutator>);
+
+impl Mutators {
+    pub(crate) fn has(&self, mutator: Mutator) -> bool {
+        self.0.contains(&mutator)
+    }
+
+    pub(crate) fn speed_up(&self) -> bool {
+        self.0.iter().any(|mutator| mutator.speeds_up())
+    }
+}
+
+/// Names of `mutators` for showing in a list.
+pub(crate) fn describe<'a>(
+    locale: &Locale,
+    mutators: impl IntoIterator<Item = &'a Mutator>,
+) -> String {
+    let names: Vec<_> = mutators
+        .into_iter()
+        .map(|mutator| locale.get(mutator.message()))
+        .collect();
+    names.join(", ")
+}
+
+/// Config the mutators changed, as it was before, to be put back on leaving
+/// the level.
+#[derive(Resource)]
+pub(crate) struct Unmutated {
+    movement: Movement,
+    gravity: Vec2,
+}
+
+/// Lays the mutators over what the level just set up.
+pub(crate) fn apply(
+    mutators: Res<Mutators>,
+    mut movement: ResMut<Movement>,
+    mut gravity: ResMut<Gravity>,
+    mut rules: ResMut<HookRules>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+    checkpoints: Query<Entity, With<Checkpoint>>,
+    mut commands: Commands,
+) {
+    commands.insert_resource(Unmutated {
+        movement: movement.clone(),
+        gravity: gravity.0,
+    });
+
+    if mutators.has(Mutator::LowGravity) {
+        gravity.0 *= LOW_GRAVITY;
+    }
+    if mutators.has(Mutator::DoubleSpeed) {
+        movement.accel *= DOUBLE_SPEED;
+        movement.max_speed *= DOUBLE_SPEED;
+    }
+    if mutators.has(Mutator::ShortHook) {
+        rules.range *= SHORT_HOOK;
+    }
+    if mutators.has(Mutator::BulletTime) {
+        rules.bullet_time = BULLET_TIME;
+    }
+    if mutators.has(Mutator::Mirrored) {
+        for mut transform in &mut cameras {
+            transform.scale.x = -transform.scale.x.abs();
+        }
+    }
+    if mutators.has(Mutator::NoCheckpoints) {
+        for checkpoint in &checkpoints {
+            commands.entity(checkpoint).despawn();
+        }
+    }
+}
+
+/// Applies the mutators to the player once they're spawned.
+pub(crate) fn apply_to_player(
+    mutators: Res<Mutators>,
+    mut players: Query<(&mut Health, &mut Abilities), Added<Player>>,
+) {
+    for (mut health, mut abilities) in &mut players {
+        if mutators.has(Mutator::OneHit) {
+            *health = Health::new(1);
+        }
+        if mutators.has(Mutator::NoHook) {
+            abilities.0.remove(&Ability::Hook);
+        }
+    }
+}
+
+/// Takes the mutators back off the config.
+pub(crate) fn restore(
+    unmutated: Option<Res<Unmutated>>,
+    mut movement: ResMut<Movement>,
+    mut gravity: ResMut<Gravity>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+    mut commands: Commands,
+) {
+    if let Some(unmutated) = unmutated {
+        *movement = unmutated.movement.clone();
+        gravity.0 = unmutated.gravity;
+        commands.remove_resource::<Unmutated>();
+    }
+    for mut transform in &mut cameras {
+        transform.scale.x = transform.scale.x.abs();
+    }
+}
+
+/// Swaps left and right on the controls to match the mirrored screen.
+pub(crate) fn mirror_input(mutators: Res<Mutators>, mut actions: ResMut<ActionState>) {
+    if mutators.has(Mutator::Mirrored) {
+        actions.mirror();
+    }
+}
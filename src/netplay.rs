@@ -0,0 +1,1183 @@
+use std::{
+    collections::{BTreeSet, HashSet, VecDeque},
+    io::{self, ErrorKind},
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use bevy::{
+    app::FixedMain,
+    ecs::{
+        schedule::{ExecutorKind, ScheduleLabel},
+        system::SystemParam,
+    },
+    prelude::*,
+};
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abilities::Ability,
+    deterministic::GameRng,
+    difficulty::Difficulty,
+    input::{ActionFrame, ActionState},
+    level::{self, Levels},
+    locale::Locale,
+    mutators::{Mutator, Mutators},
+    player::{self, Controls, Remote},
+    rollback::Snapshot,
+    run::Run,
+    save::SaveData,
+    settings::{AssistSettings, GameplaySettings},
+    shop,
+    skins::Skin,
+    state::{AppState, GameplaySet, InGame},
+};
+
+/// How many ticks one side may run ahead of what it knows of the other
+const MAX_ROLLBACK: usize = 8;
+/// Snapshots kept to roll back to, enough for a few frames on top of
+/// `MAX_ROLLBACK`
+const SNAPSHOTS: usize = 32;
+/// Inputs sent in one packet, older ones go first
+const MAX_FRAMES: usize = 32;
+/// Checksums kept to compare with the other side's
+const CHECKSUMS: usize = 64;
+/// Without hearing anything for this long the other side is gone
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Quiet for this long while waiting on the other side is worth showing
+const PATIENCE: Duration = Duration::from_secs(1);
+/// Between repeats of a message that hasn't been answered yet, and between
+/// packets when there's nothing new to send
+const RESEND: Duration = Duration::from_millis(200);
+const DEFAULT_PORT: u16 = 7777;
+const MAX_PACKET: usize = 8192;
+
+/// Two players online, each at their own machine, using rollback. Both
+/// sides simulate the level with both players every tick. Inputs are sent
+/// to the other side as they happen, and until the other side's arrive
+/// they're predicted to stay as they were. When an input turns out
+/// different the game goes back to the [`Snapshot`] from that tick and
+/// simulates up to now again.
+///
+/// One side hosts and picks the levels, the other joins and follows. The
+/// host's rules apply to both: gameplay and assist settings, difficulty and
+/// mutators. A checksum of where everything is gets compared now and then
+/// to notice when the two sides went different ways, the parts that aren't
+/// rollback safe yet are listed on [`Snapshot`].
+pub struct NetplayPlugin;
+
+impl Plugin for NetplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lobby>()
+            .add_systems(PreUpdate, receive.run_if(resource_exists::<Session>))
+            .add_systems(
+                OnEnter(InGame),
+                begin_round
+                    .run_if(resource_exists::<Session>)
+                    .before(level::setup)
+                    .before(player::setup),
+            )
+            .add_systems(
+                OnExit(AppState::Playing),
+                end_round.run_if(resource_exists::<Session>),
+            )
+            .add_systems(FixedFirst, save.run_if(online))
+            .add_systems(
+                FixedUpdate,
+                exchange
+                    .after(player::control)
+                    .before(GameplaySet)
+                    .run_if(online),
+            )
+            .add_systems(FixedLast, advance.run_if(online))
+            .add_systems(
+                Last,
+                (
+                    roll_back.run_if(online),
+                    send.run_if(resource_exists::<Session>),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, throttle);
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Both sides have to go through the tick, and the transitions that
+        // build levels and hand out entity ids, in the same order
+        for schedule in [
+            FixedFirst.intern(),
+            FixedPreUpdate.intern(),
+            FixedUpdate.intern(),
+            FixedPostUpdate.intern(),
+            FixedLast.intern(),
+            OnEnter(InGame).intern(),
+        ] {
+            app.edit_schedule(schedule, |schedule| {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            });
+        }
+    }
+}
+
+/// What a player brings into the game from their own save.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Loadout {
+    pub(crate) abilities: HashSet<Ability>,
+    pub(crate) max_health: u32,
+    pub(crate) skin: Skin,
+}
+
+impl Loadout {
+    pub(crate) fn new(save: &SaveData) -> Self {
+        Self {
+            abilities: save.abilities.clone(),
+            max_health: shop::max_health(save),
+            skin: save.skin,
+        }
+    }
+}
+
+/// One player's input for one tick.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct Frame {
+    actions: ActionFrame,
+    /// Where the cursor was in the world
+    cursor: Vec2,
+}
+
+impl Frame {
+    fn new(controls: &Controls) -> Self {
+        Self {
+            actions: controls.actions.frame(),
+            cursor: controls.cursor,
+        }
+    }
+
+    /// The guess for the next tick while it's still on its way.
+    fn repeated(&self) -> Self {
+        Self {
+            actions: self.actions.repeated(),
+            ..*self
+        }
+    }
+}
+
+/// The host's settings that change how the game plays.
+#[derive(Clone, Serialize, Deserialize)]
+struct Rules {
+    gameplay: GameplaySettings,
+    assist: AssistSettings,
+    difficulty: Difficulty,
+    mutators: BTreeSet<Mutator>,
+}
+
+impl Rules {
+    fn insert(self, commands: &mut Commands) {
+        commands.insert_resource(self.gameplay);
+        commands.insert_resource(self.assist);
+        commands.insert_resource(self.difficulty);
+        commands.insert_resource(Mutators(self.mutators));
+    }
+}
+
+/// The resources [`Rules`] are made of.
+#[derive(SystemParam)]
+struct CurrentRules<'w> {
+    gameplay: Res<'w, GameplaySettings>,
+    assist: Res<'w, AssistSettings>,
+    difficulty: Res<'w, Difficulty>,
+    mutators: Res<'w, Mutators>,
+}
+
+impl CurrentRules<'_> {
+    fn get(&self) -> Rules {
+        Rules {
+            gameplay: self.gameplay.clone(),
+            assist: self.assist.clone(),
+            difficulty: *self.difficulty,
+            mutators: self.mutators.0.clone(),
+        }
+    }
+}
+
+/// Everything the guest needs to play the same round as the host.
+#[derive(Clone, Serialize, Deserialize)]
+struct Start {
+    /// Counts up from 1 over the session
+    round: u32,
+    /// File name of the level
+    level: String,
+    seed: u64,
+    run: Run,
+    rules: Rules,
+    /// The host's and then the guest's
+    loadouts: [Loadout; 2],
+}
+
+#[derive(Serialize, Deserialize)]
+enum Message {
+    /// From the guest until the host has its loadout
+    Hello(Loadout),
+    /// The loadout the host got
+    Welcome(Loadout),
+    Start(Box<Start>),
+    /// Also sent with no frames to show the sender is still there
+    Inputs(Inputs),
+    Leave,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Inputs {
+    round: u32,
+    /// Tick of the first frame
+    first: u32,
+    frames: Vec<Frame>,
+    /// How many ticks of the receiver's inputs the sender has, so it can stop
+    /// sending them
+    received: u32,
+    /// The sender's checksum of the latest tick it knows both inputs for
+    checksum: Option<(u32, u64)>,
+}
+
+/// A level played together, from entering it to dying or reaching the goal.
+struct Round {
+    number: u32,
+    /// Until the state leaves [`AppState::Playing`]
+    active: bool,
+    /// Ticks simulated so far
+    tick: usize,
+    /// Our inputs, by tick
+    local: Vec<Frame>,
+    /// The other side's inputs as far as they've arrived
+    remote: Vec<Option<Frame>>,
+    /// The other side's inputs each tick was simulated with, predicted or not
+    used: Vec<Frame>,
+    /// Ticks from the start the other side's inputs are all known for
+    confirmed: usize,
+    /// Ticks of our inputs the other side has
+    acknowledged: usize,
+    /// The state at the start of each tick
+    snapshots: VecDeque<Saved>,
+    /// Earliest tick that was simulated with a wrong guess
+    rollback: Option<usize>,
+    resimulating: bool,
+    /// The tick after which the state was asked to change
+    requested: Option<usize>,
+    /// A state change waiting until it's certain it happens on both sides
+    held: Option<AppState>,
+    /// Of ticks known to be right, oldest first
+    checksums: VecDeque<(usize, u64)>,
+    peer_checksum: Option<(usize, u64)>,
+    desynced: bool,
+    rollbacks: u32,
+}
+
+impl Round {
+    fn new(number: u32) -> Self {
+        Self {
+            number,
+            active: true,
+            tick: 0,
+            local: Vec::new(),
+            remote: Vec::new(),
+            used: Vec::new(),
+            confirmed: 0,
+            acknowledged: 0,
+            snapshots: VecDeque::new(),
+            rollback: None,
+            resimulating: false,
+            requested: None,
+            held: None,
+            checksums: VecDeque::new(),
+            peer_checksum: None,
+            desynced: false,
+            rollbacks: 0,
+        }
+    }
+
+    /// Too far ahead of the other side, or waiting to change state.
+    fn stalled(&self) -> bool {
+        self.active && (self.held.is_some() || self.tick > self.confirmed + MAX_ROLLBACK)
+    }
+
+    fn receive(&mut self, inputs: Inputs) {
+        self.acknowledged = self.acknowledged.max(inputs.received as usize);
+        for (i, frame) in inputs.frames.into_iter().enumerate() {
+            let tick = inputs.first as usize + i;
+            // Further ahead than the other side can be
+            if tick > self.tick + SNAPSHOTS {
+                break;
+            }
+            if self.remote.len() <= tick {
+                self.remote.resize(tick + 1, None);
+            }
+            if self.remote[tick].is_some() {
+                continue;
+            }
+
+            self.remote[tick] = Some(frame);
+            if self.used.get(tick).is_some_and(|used| *used != frame) {
+                self.rollback = Some(self.rollback.map_or(tick, |from| from.min(tick)));
+            }
+        }
+        while self.remote.get(self.confirmed).is_some_and(Option::is_some) {
+            self.confirmed += 1;
+        }
+
+        if let Some((tick, checksum)) = inputs.checksum {
+            self.peer_checksum = Some((tick as usize, checksum));
+            self.compare();
+        }
+    }
+
+    /// What the other side is guessed to do on a tick whose input is missing.
+    fn predict(&self) -> Frame {
+        self.confirmed
+            .checked_sub(1)
+            .and_then(|last| self.remote[last])
+            .map(|frame| frame.repeated())
+            .unwrap_or_default()
+    }
+
+    fn compare(&mut self) {
+        let Some((tick, theirs)) = self.peer_checksum else {
+            return;
+        };
+        let Some(&(_, ours)) = self.checksums.iter().find(|(at, _)| *at == tick) else {
+            return;
+        };
+
+        self.peer_checksum = None;
+        if ours != theirs && !self.desynced {
+            error!("Out of sync with the other player from tick {tick}");
+            self.desynced = true;
+        }
+    }
+}
+
+/// The state at the start of a tick.
+struct Saved {
+    tick: usize,
+    snapshot: Snapshot,
+    checksum: u64,
+}
+
+/// Connected or trying to connect to the other player. Removing it goes
+/// back to playing alone.
+#[derive(Resource)]
+pub(crate) struct Session {
+    socket: UdpSocket,
+    host: bool,
+    /// Where the other side is, the host learns it from the first hello
+    peer: Option<SocketAddr>,
+    /// The guest's loadout on the host, on the guest the one the host
+    /// confirmed getting
+    loadout: Option<Loadout>,
+    heard: Duration,
+    sent: Duration,
+    /// The latest round, the host repeats it until the guest has it
+    start: Option<Start>,
+    acknowledged: bool,
+    round: Option<Round>,
+    /// What the guest played by before following the host
+    own: Option<Rules>,
+}
+
+impl Session {
+    pub(crate) fn host(port: u16, now: Duration) -> io::Result<Self> {
+        Self::new(UdpSocket::bind(("0.0.0.0", port))?, true, None, now)
+    }
+
+    /// `address` is a host name or IP, with or without a port.
+    pub(crate) fn join(address: &str, now: Duration) -> io::Result<Self> {
+        let peer = address
+            .to_socket_addrs()
+            .or_else(|_| (address, DEFAULT_PORT).to_socket_addrs())?
+            .find(SocketAddr::is_ipv4)
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no IPv4 address"))?;
+        Self::new(UdpSocket::bind(("0.0.0.0", 0))?, false, Some(peer), now)
+    }
+
+    fn new(
+        socket: UdpSocket,
+        host: bool,
+        peer: Option<SocketAddr>,
+        now: Duration,
+    ) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            host,
+            peer,
+            loadout: None,
+            heard: now,
+            sent: Duration::ZERO,
+            start: None,
+            acknowledged: false,
+            round: None,
+            own: None,
+        })
+    }
+
+    /// Whether the host picks what's played.
+    pub(crate) fn following(&self) -> bool {
+        !self.host
+    }
+
+    pub(crate) fn connected(&self) -> bool {
+        self.loadout.is_some()
+    }
+
+    /// Whether this side is the host, its own loadout and the other
+    /// player's, while a round is being played together.
+    pub(crate) fn players(&self) -> Option<(bool, &Loadout, &Loadout)> {
+        let round = self.round.as_ref().filter(|round| round.active)?;
+        let start = self
+            .start
+            .as_ref()
+            .filter(|start| start.round == round.number)?;
+        let [host, guest] = &start.loadouts;
+        Some(if self.host {
+            (true, host, guest)
+        } else {
+            (false, guest, host)
+        })
+    }
+
+    fn send(&mut self, message: &Message, now: Duration) {
+        let Some(peer) = self.peer else {
+            return;
+        };
+        let bytes = match serde_json::to_vec(message) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                error!("Could not encode a message: {err}");
+                return;
+            }
+        };
+        // Lost like any other packet, the next one repeats what's missing
+        if let Err(err) = self.socket.send_to(&bytes, peer) {
+            debug!("Could not send to {peer}: {err}");
+        }
+        self.sent = now;
+    }
+
+    /// Sends anything the other side doesn't have yet.
+    fn send_inputs(&mut self, now: Duration) {
+        let inputs = match &self.round {
+            Some(round) => Inputs {
+                round: round.number,
+                first: round.acknowledged as u32,
+                frames: round
+                    .local
+                    .iter()
+                    .skip(round.acknowledged)
+                    .take(MAX_FRAMES)
+                    .copied()
+                    .collect(),
+                received: round.confirmed as u32,
+                checksum: round
+                    .checksums
+                    .back()
+                    .map(|&(tick, checksum)| (tick as u32, checksum)),
+            },
+            None => Inputs {
+                round: 0,
+                first: 0,
+                frames: Vec::new(),
+                received: 0,
+                checksum: None,
+            },
+        };
+        if !inputs.frames.is_empty() || now >= self.sent + RESEND {
+            self.send(&Message::Inputs(inputs), now);
+        }
+    }
+}
+
+/// Whether this side has the other's inputs to play with.
+pub(crate) fn online(session: Option<Res<Session>>) -> bool {
+    session.is_some_and(|session| session.round.as_ref().is_some_and(|round| round.active))
+}
+
+pub(crate) fn following(session: Option<Res<Session>>) -> bool {
+    session.is_some_and(|session| session.following())
+}
+
+/// Why the last session ended.
+enum Status {
+    Lost,
+    Left,
+    Error(String),
+}
+
+/// The online window, opened from the main menu.
+#[derive(Resource)]
+pub(crate) struct Lobby {
+    pub(crate) open: bool,
+    port: String,
+    address: String,
+    status: Option<Status>,
+}
+
+impl Default for Lobby {
+    fn default() -> Self {
+        Self {
+            open: false,
+            port: DEFAULT_PORT.to_string(),
+            address: String::new(),
+            status: None,
+        }
+    }
+}
+
+/// Leaves the session, going back to the menu from a level.
+fn end(
+    commands: &mut Commands,
+    session: &mut Session,
+    lobby: &mut Lobby,
+    status: Option<Status>,
+    in_game: bool,
+    next: &mut NextState<AppState>,
+    now: Duration,
+) {
+    session.send(&Message::Leave, now);
+    if let Some(own) = session.own.take() {
+        own.insert(commands);
+    }
+    commands.remove_resource::<Session>();
+    lobby.status = status;
+    if in_game {
+        next.set(AppState::Menu);
+    }
+}
+
+fn receive(
+    mut commands: Commands,
+    mut session: ResMut<Session>,
+    mut lobby: ResMut<Lobby>,
+    mut levels: ResMut<Levels>,
+    rules: CurrentRules,
+    in_game: Option<Res<State<InGame>>>,
+    mut next: ResMut<NextState<AppState>>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed();
+    let mut buffer = [0; MAX_PACKET];
+    loop {
+        let (len, from) = match session.socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            // Some platforms report a packet that couldn't be delivered on
+            // the next read, the timeout takes care of a peer that's gone
+            Err(err) => {
+                debug!("Could not receive: {err}");
+                break;
+            }
+        };
+        let message = match serde_json::from_slice::<Message>(&buffer[..len]) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("Ignoring a packet from {from}: {err}");
+                continue;
+            }
+        };
+
+        match message {
+            // One guest at a time
+            Message::Hello(loadout)
+                if session.host && session.peer.is_none_or(|peer| peer == from) =>
+            {
+                session.peer = Some(from);
+                session.send(&Message::Welcome(loadout.clone()), now);
+                session.loadout = Some(loadout);
+            }
+            _ if session.peer != Some(from) => continue,
+            Message::Hello(_) => {}
+            Message::Welcome(loadout) => session.loadout = Some(loadout),
+            Message::Start(start) => {
+                if session.host
+                    || session
+                        .start
+                        .as_ref()
+                        .is_some_and(|current| current.round >= start.round)
+                {
+                    continue;
+                }
+                let Some(level) = levels.names.iter().position(|name| *name == start.level) else {
+                    let status = Status::Error(format!("unknown level {}", start.level));
+                    end(
+                        &mut commands,
+                        &mut session,
+                        &mut lobby,
+                        Some(status),
+                        in_game.is_some(),
+                        &mut next,
+                        now,
+                    );
+                    return;
+                };
+
+                if session.own.is_none() {
+                    session.own = Some(rules.get());
+                }
+                // Whatever was still going on is over for the host
+                if let Some(round) = &mut session.round {
+                    round.active = false;
+                }
+                levels.current = level;
+                start.rules.clone().insert(&mut commands);
+                commands.insert_resource(start.run.clone());
+                commands.insert_resource(GameRng::new(start.seed));
+                next.set(if in_game.is_some() {
+                    AppState::Restarting
+                } else {
+                    AppState::Playing
+                });
+                session.start = Some(*start);
+            }
+            Message::Inputs(inputs) => {
+                if session
+                    .start
+                    .as_ref()
+                    .is_some_and(|start| inputs.round >= start.round)
+                {
+                    session.acknowledged = true;
+                }
+                if let Some(round) = session
+                    .round
+                    .as_mut()
+                    .filter(|round| round.number == inputs.round)
+                {
+                    round.receive(inputs);
+                }
+            }
+            Message::Leave => {
+                end(
+                    &mut commands,
+                    &mut session,
+                    &mut lobby,
+                    Some(Status::Left),
+                    in_game.is_some(),
+                    &mut next,
+                    now,
+                );
+                return;
+            }
+        }
+        session.heard = now;
+    }
+
+    // The host waits for as long as it takes for someone to join
+    if session.peer.is_some() && now > session.heard + TIMEOUT {
+        end(
+            &mut commands,
+            &mut session,
+            &mut lobby,
+            Some(Status::Lost),
+            in_game.is_some(),
+            &mut next,
+            now,
+        );
+    }
+}
+
+/// The host starts a round in every level entered while connected, the
+/// guest in the one it was sent.
+fn begin_round(
+    mut session: ResMut<Session>,
+    save: Res<SaveData>,
+    levels: Res<Levels>,
+    run: Res<Run>,
+    rules: CurrentRules,
+    mut rng: ResMut<GameRng>,
+) {
+    if session.host {
+        let (Some(guest), Some(level)) =
+            (session.loadout.clone(), levels.names.get(levels.current))
+        else {
+            session.round = None;
+            return;
+        };
+
+        let seed = rng.next_u64();
+        *rng = GameRng::new(seed);
+        session.start = Some(Start {
+            round: session.start.as_ref().map_or(1, |start| start.round + 1),
+            level: level.clone(),
+            seed,
+            run: run.clone(),
+            rules: rules.get(),
+            loadouts: [Loadout::new(&save), guest],
+        });
+        session.acknowledged = false;
+        session.sent = Duration::ZERO;
+    }
+
+    let Some(number) = session.start.as_ref().map(|start| start.round) else {
+        return;
+    };
+    // The guest only plays along in levels the host started
+    if session
+        .round
+        .as_ref()
+        .is_some_and(|round| round.number == number)
+    {
+        session.round = None;
+        return;
+    }
+    session.round = Some(Round::new(number));
+}
+
+/// Dying or reaching the goal ends the round on both sides on the same tick.
+/// The round's inputs are still sent until the other side has them.
+fn end_round(mut session: ResMut<Session>) {
+    if let Some(round) = &mut session.round {
+        round.active = false;
+    }
+}
+
+fn save(world: &mut World) {
+    let Some(tick) = world
+        .resource::<Session>()
+        .round
+        .as_ref()
+        .map(|round| round.tick)
+    else {
+        return;
+    };
+    // The level was only just built and gets into place during the first
+    // tick, which both sides play without input so it's never rolled back
+    if tick == 0 {
+        return;
+    }
+
+    let snapshot = Snapshot::take(world);
+    let checksum = snapshot.checksum();
+    let mut session = world.resource_mut::<Session>();
+    let Some(round) = session.round.as_mut() else {
+        return;
+    };
+    round.snapshots.retain(|saved| saved.tick < tick);
+    round.snapshots.push_back(Saved {
+        tick,
+        snapshot,
+        checksum,
+    });
+    while round.snapshots.len() > SNAPSHOTS {
+        round.snapshots.pop_front();
+    }
+}
+
+/// Records this tick's input and hands both players theirs.
+fn exchange(mut session: ResMut<Session>, mut players: Query<(&mut Controls, Has<Remote>)>) {
+    let Some(round) = session.round.as_mut() else {
+        return;
+    };
+    let tick = round.tick;
+
+    let local = if round.resimulating {
+        round.local.get(tick).copied().unwrap_or_default()
+    } else {
+        let frame = players
+            .iter()
+            .find(|(_, remote)| !remote)
+            .filter(|_| tick > 0)
+            .map(|(controls, _)| Frame::new(controls))
+            .unwrap_or_default();
+        round.local.push(frame);
+        frame
+    };
+    let remote = match round.remote.get(tick).copied().flatten() {
+        Some(frame) => frame,
+        None if tick == 0 => Frame::default(),
+        None => round.predict(),
+    };
+    round.used.truncate(tick);
+    round.used.push(remote);
+
+    for (mut controls, is_remote) in &mut players {
+        let frame = if is_remote { remote } else { local };
+        controls.actions.play(&frame.actions);
+        controls.cursor = frame.cursor;
+    }
+}
+
+fn advance(mut session: ResMut<Session>, next: Res<NextState<AppState>>) {
+    let Some(round) = session.round.as_mut() else {
+        return;
+    };
+    round.tick += 1;
+    if matches!(*next, NextState::Pending(_)) && round.requested.is_none() {
+        round.requested = Some(round.tick);
+    }
+}
+
+/// Simulates again from the first tick that was played with a wrong guess,
+/// then lets state changes through once they're certain.
+fn roll_back(world: &mut World) {
+    let mut session = world.resource_mut::<Session>();
+    let Some(round) = session.round.as_mut() else {
+        return;
+    };
+
+    let mut rollback = None;
+    if let Some(from) = round.rollback.take() {
+        match round.snapshots.iter().position(|saved| saved.tick == from) {
+            Some(i) => {
+                rollback = round.snapshots.remove(i).map(|saved| (saved, round.tick));
+                round.resimulating = true;
+                round.tick = from;
+                round.requested = None;
+                round.held = None;
+                round.rollbacks += 1;
+            }
+            None => {
+                error!("Could not roll back to tick {from}, it's too long ago");
+                round.desynced = true;
+            }
+        }
+    }
+    if let Some((saved, to)) = rollback {
+        resimulate(world, &saved.snapshot, saved.tick..to);
+    }
+
+    let pending = match *world.resource::<NextState<AppState>>() {
+        NextState::Pending(state) => Some(state),
+        NextState::Unchanged => None,
+    };
+    let mut session = world.resource_mut::<Session>();
+    let Some(round) = session.round.as_mut() else {
+        return;
+    };
+    let mut release = None;
+    if let Some(state) = pending {
+        let requested = *round.requested.get_or_insert(round.tick);
+        // Might not happen at all once the other side's inputs are in
+        if requested > round.confirmed {
+            round.held = Some(state);
+        }
+    }
+    if round.held.is_some() && round.requested.is_some_and(|tick| tick <= round.confirmed) {
+        release = round.held.take();
+    }
+
+    if let Some(saved) = round
+        .snapshots
+        .iter()
+        .rev()
+        .find(|saved| saved.tick <= round.confirmed)
+    {
+        if round
+            .checksums
+            .back()
+            .is_none_or(|&(tick, _)| tick < saved.tick)
+        {
+            round.checksums.push_back((saved.tick, saved.checksum));
+            if round.checksums.len() > CHECKSUMS {
+                round.checksums.pop_front();
+            }
+            round.compare();
+        }
+    }
+
+    let held = round.held.is_some();
+    let mut next = world.resource_mut::<NextState<AppState>>();
+    if held {
+        *next = NextState::Unchanged;
+    }
+    if let Some(state) = release {
+        next.set(state);
+    }
+}
+
+fn resimulate(world: &mut World, snapshot: &Snapshot, ticks: std::ops::Range<usize>) {
+    let time = *world.resource::<Time>();
+    let fixed = *world.resource::<Time<Fixed>>();
+    let actions = world.resource::<ActionState>().clone();
+    // Anything the guesses led to gets decided again
+    *world.resource_mut::<NextState<AppState>>() = NextState::Unchanged;
+
+    snapshot.restore(world);
+    let first = ticks.start;
+    for tick in ticks {
+        // Snapshots are taken once the tick's time has been added
+        if tick > first {
+            let mut fixed = world.resource_mut::<Time<Fixed>>();
+            let timestep = fixed.timestep();
+            fixed.advance_by(timestep);
+        }
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        world.run_schedule(FixedMain);
+    }
+
+    *world.resource_mut::<Time<Fixed>>() = fixed;
+    *world.resource_mut::<Time>() = time;
+    *world.resource_mut::<ActionState>() = actions;
+    if let Some(round) = world.resource_mut::<Session>().round.as_mut() {
+        round.resimulating = false;
+    }
+}
+
+fn send(mut session: ResMut<Session>, save: Res<SaveData>, time: Res<Time<Real>>) {
+    let now = time.elapsed();
+    if !session.host {
+        // Again whenever the shop or skins change it
+        let loadout = Loadout::new(&save);
+        if session.loadout.as_ref() != Some(&loadout) {
+            if now >= session.sent + RESEND {
+                session.send(&Message::Hello(loadout), now);
+            }
+            return;
+        }
+    }
+    if !session.connected() {
+        return;
+    }
+
+    if session.host && !session.acknowledged && now >= session.sent + RESEND {
+        if let Some(start) = session.start.clone() {
+            session.send(&Message::Start(Box::new(start)), now);
+        }
+    }
+    session.send_inputs(now);
+}
+
+/// Holds the game while it's too far ahead of the other side.
+fn throttle(
+    session: Option<Res<Session>>,
+    mut time: ResMut<Time<Virtual>>,
+    mut stalled: Local<bool>,
+) {
+    let stall = session
+        .and_then(|session| session.round.as_ref().map(Round::stalled))
+        .unwrap_or(false);
+    if stall == *stalled {
+        return;
+    }
+
+    *stalled = stall;
+    if stall {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+}
+
+pub(crate) fn lobby_window(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut lobby: ResMut<Lobby>,
+    mut session: Option<ResMut<Session>>,
+    mut next: ResMut<NextState<AppState>>,
+    time: Res<Time<Real>>,
+    locale: Res<Locale>,
+) {
+    if !lobby.open {
+        return;
+    }
+
+    let now = time.elapsed();
+    let mut open = true;
+    egui::Window::new(locale.get("online"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            if let Some(session) = session.as_deref_mut() {
+                let status = match (session.host, session.connected(), session.peer) {
+                    (true, true, _) => locale.get("online-connected-host").to_string(),
+                    (true, false, _) => locale.format(
+                        "online-waiting",
+                        &[(
+                            "port",
+                            &session.socket.local_addr().map_or(0, |addr| addr.port()),
+                        )],
+                    ),
+                    (false, true, _) => locale.get("online-connected-guest").to_string(),
+                    (false, false, peer) => locale.format(
+                        "online-connecting",
+                        &[(
+                            "address",
+                            &peer.map(|peer| peer.to_string()).unwrap_or_default(),
+                        )],
+                    ),
+                };
+                ui.label(status);
+                if ui.button(locale.get("online-leave")).clicked() {
+                    end(
+                        &mut commands,
+                        session,
+                        &mut lobby,
+                        None,
+                        false,
+                        &mut next,
+                        now,
+                    );
+                }
+                return;
+            }
+
+            let mut started = None;
+            egui::Grid::new("online").show(ui, |ui| {
+                ui.label(locale.get("online-port"));
+                ui.text_edit_singleline(&mut lobby.port);
+                if ui.button(locale.get("online-host")).clicked() {
+                    started = Some(
+                        lobby
+                            .port
+                            .trim()
+                            .parse()
+                            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "bad port"))
+                            .and_then(|port| Session::host(port, now)),
+                    );
+                }
+                ui.end_row();
+
+                ui.label(locale.get("online-address"));
+                ui.text_edit_singleline(&mut lobby.address);
+                if ui.button(locale.get("online-join")).clicked() {
+                    started = Some(Session::join(lobby.address.trim(), now));
+                }
+                ui.end_row();
+            });
+
+            match started {
+                Some(Ok(session)) => {
+                    commands.insert_resource(session);
+                    lobby.status = None;
+                }
+                Some(Err(err)) => lobby.status = Some(Status::Error(err.to_string())),
+                None => {}
+            }
+            let status = match &lobby.status {
+                Some(Status::Lost) => locale.get("online-lost").to_string(),
+                Some(Status::Left) => locale.get("online-left").to_string(),
+                Some(Status::Error(error)) => locale.format("online-error", &[("error", error)]),
+                None => return,
+            };
+            ui.label(egui::RichText::new(status).weak());
+        });
+
+    if !open {
+        lobby.open = false;
+    }
+}
+
+/// Says when the other side is holding things up or the two went out of
+/// sync.
+pub(crate) fn status(
+    mut contexts: EguiContexts,
+    session: Res<Session>,
+    time: Res<Time<Real>>,
+    locale: Res<Locale>,
+) {
+    let Some(round) = session.round.as_ref().filter(|round| round.active) else {
+        return;
+    };
+    let text = if round.desynced {
+        locale.get("online-desync")
+    } else if round.stalled() && time.elapsed() > session.heard + PATIENCE {
+        locale.get("online-stalled")
+    } else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("online status"))
+        .anchor(egui::Align2::CENTER_TOP, [0., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(text);
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use avian2d::prelude::*;
+
+    use super::*;
+    use crate::deterministic::tests::{self, Inputs};
+
+    /// The host runs right and jumps, the guest hops in place now and then
+    /// so the host's guesses keep turning out wrong. Bits are `MoveRight`
+    /// and `Jump`.
+    const HOST: &str = "[
+        (40, (held: 0, latched: 0, stick: None, aim: None)),
+        (30, (held: 2, latched: 0, stick: None, aim: None)),
+        (1, (held: 6, latched: 4, stick: None, aim: None)),
+        (20, (held: 6, latched: 0, stick: None, aim: None)),
+        (100, (held: 2, latched: 0, stick: None, aim: None)),
+    ]";
+    const GUEST: &str = "[
+        (50, (held: 0, latched: 0, stick: None, aim: None)),
+        (1, (held: 4, latched: 4, stick: None, aim: None)),
+        (10, (held: 4, latched: 0, stick: None, aim: None)),
+        (30, (held: 0, latched: 0, stick: None, aim: None)),
+        (1, (held: 4, latched: 4, stick: None, aim: None)),
+        (100, (held: 0, latched: 0, stick: None, aim: None)),
+    ]";
+
+    fn app(inputs: &str, session: Session) -> App {
+        let mut app = tests::app(7);
+        app.add_plugins(NetplayPlugin)
+            .insert_resource(Inputs(tests::frames(inputs)))
+            .insert_resource(session);
+        // The host starts once the guest is there
+        *app.world_mut().resource_mut::<NextState<AppState>>() = NextState::Unchanged;
+        app
+    }
+
+    fn round(app: &App) -> &Round {
+        app.world().resource::<Session>().round.as_ref().unwrap()
+    }
+
+    #[test]
+    fn both_sides_end_up_the_same() {
+        let session = Session::host(0, Duration::ZERO).unwrap();
+        let port = session.socket.local_addr().unwrap().port();
+        let mut host = app(HOST, session);
+        let address = format!("127.0.0.1:{port}");
+        let mut guest = app(GUEST, Session::join(&address, Duration::ZERO).unwrap());
+
+        for _ in 0..100 {
+            host.update();
+            guest.update();
+            if host.world().resource::<Session>().connected()
+                && guest.world().resource::<Session>().connected()
+            {
+                break;
+            }
+        }
+        host.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Playing);
+        for _ in 0..300 {
+            host.update();
+            guest.update();
+        }
+
+        let (ours, theirs) = (round(&host), round(&guest));
+        assert!(ours.rollbacks > 0, "the host never had to roll back");
+        assert!(!ours.desynced && !theirs.desynced);
+
+        // Both have checked the same tick by now
+        let tick = ours
+            .checksums
+            .back()
+            .unwrap()
+            .0
+            .min(theirs.checksums.back().unwrap().0);
+        let at = |round: &Round| round.checksums.iter().find(|(at, _)| *at == tick).copied();
+        assert!(tick > 200, "only got to tick {tick}");
+        assert_eq!(at(ours), at(theirs));
+
+        let world = host.world_mut();
+        let mut players =
+            world.query_filtered::<&Position, (With<player::Player>, Without<Remote>)>();
+        let end = players.single(world).0;
+        assert!(end.x > 500., "the host only got to {end}");
+    }
+}
@@ -0,0 +1,119 @@
+use avian2d::prelude::*;
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    prelude::*,
+    utils::Instant,
+};
+use bevy_egui::{egui, EguiContexts};
+
+const PHYSICS_STEP: DiagnosticPath = DiagnosticPath::const_new("physics/step_time");
+const AWAKE_BODIES: DiagnosticPath = DiagnosticPath::const_new("physics/awake_bodies");
+const CONTACTS: DiagnosticPath = DiagnosticPath::const_new("physics/contacts");
+const VISIBLE: DiagnosticPath = DiagnosticPath::const_new("render/visible_sprites");
+
+/// Frame time, physics cost and what's in the world, F3 toggles it.
+pub(crate) struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((FrameTimeDiagnosticsPlugin, EntityCountDiagnosticsPlugin))
+            .register_diagnostic(Diagnostic::new(PHYSICS_STEP).with_suffix(" ms"))
+            .register_diagnostic(Diagnostic::new(AWAKE_BODIES))
+            .register_diagnostic(Diagnostic::new(CONTACTS))
+            .register_diagnostic(Diagnostic::new(VISIBLE))
+            .init_resource::<Overlay>()
+            .add_systems(
+                FixedPostUpdate,
+                (
+                    start_physics.before(PhysicsSet::Prepare),
+                    end_physics.after(PhysicsSet::Sync),
+                ),
+            )
+            .add_systems(Update, (toggle, count, panel).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+struct Overlay {
+    open: bool,
+    /// When the physics step of this tick began
+    physics_start: Option<Instant>,
+}
+
+fn toggle(keys: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<Overlay>) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.open = !overlay.open;
+    }
+}
+
+fn start_physics(mut overlay: ResMut<Overlay>) {
+    overlay.physics_start = Some(Instant::now());
+}
+
+fn end_physics(mut overlay: ResMut<Overlay>, mut diagnostics: Diagnostics) {
+    if let Some(start) = overlay.physics_start.take() {
+        diagnostics.add_measurement(&PHYSICS_STEP, || start.elapsed().as_secs_f64() * 1000.);
+    }
+}
+
+fn count(
+    bodies: Query<&RigidBody, Without<Sleeping>>,
+    collisions: Res<Collisions>,
+    visible: Query<&ViewVisibility, Or<(With<Sprite>, With<Handle<Mesh>>)>>,
+    mut diagnostics: Diagnostics,
+) {
+    diagnostics.add_measurement(&AWAKE_BODIES, || {
+        bodies.iter().filter(|body| body.is_dynamic()).count() as f64
+    });
+    diagnostics.add_measurement(&CONTACTS, || {
+        collisions
+            .iter()
+            .filter(|contacts| contacts.during_current_frame && !contacts.is_sensor)
+            .count() as f64
+    });
+    diagnostics.add_measurement(&VISIBLE, || {
+        visible.iter().filter(|view| view.get()).count() as f64
+    });
+}
+
+fn panel(mut contexts: EguiContexts, overlay: Res<Overlay>, store: Res<DiagnosticsStore>) {
+    if !overlay.open {
+        return;
+    }
+
+    let value = |path: &DiagnosticPath| store.get(path).and_then(Diagnostic::smoothed);
+
+    egui::Area::new(egui::Id::new("diagnostics"))
+        .anchor(egui::Align2::LEFT_TOP, [10., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                egui::Grid::new("diagnostics").show(ui, |ui| {
+                    for (name, path, decimals) in [
+                        ("FPS", &FrameTimeDiagnosticsPlugin::FPS, 0),
+                        (
+                            "Frame time (ms)",
+                            &FrameTimeDiagnosticsPlugin::FRAME_TIME,
+                            2,
+                        ),
+                        ("Physics step (ms)", &PHYSICS_STEP, 2),
+                        ("Entities", &EntityCountDiagnosticsPlugin::ENTITY_COUNT, 0),
+                        ("Awake bodies", &AWAKE_BODIES, 0),
+                        ("Contacts", &CONTACTS, 0),
+                        // Bevy doesn't count draw calls, sprites get batched
+                        // into far fewer of them
+                        ("Visible sprites", &VISIBLE, 0),
+                    ] {
+                        ui.label(name);
+                        ui.monospace(
+                            value(path)
+                                .map_or("-".to_string(), |value| format!("{value:.decimals$}")),
+                        );
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
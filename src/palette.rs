@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::photo::Photo;
+
+/// Color profiles for players who can't tell some of the default hues apart.
+/// Level geometry and hazards are told apart by hue, so each profile moves
+/// the hues that get confused to ones that don't.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Palette {
+    #[default]
+    Default,
+    /// Red and green look alike
+    Deuteranopia,
+    /// Red and green look alike, and reds look dark
+    Protanopia,
+    /// Blue and green, and yellow and red, look alike
+    Tritanopia,
+    /// Strong, bright colors against the dark background
+    HighContrast,
+}
+
+/// Where each hue goes, as (from, to) points to interpolate between. The
+/// last point wraps around to the first one plus a turn.
+const RED_GREEN: [(f32, f32); 6] = [
+    (0., 20.),
+    (60., 50.),
+    (120., 210.),
+    (180., 190.),
+    (240., 240.),
+    (300., 290.),
+];
+const BLUE_YELLOW: [(f32, f32); 6] = [
+    (0., 0.),
+    (60., 340.),
+    (120., 170.),
+    (180., 180.),
+    (240., 200.),
+    (300., 320.),
+];
+
+fn remap_hue(hue: f32, points: &[(f32, f32)]) -> f32 {
+    let hue = hue.rem_euclid(360.);
+    for (i, &(from, to)) in points.iter().enumerate() {
+        let (next_from, next_to) = match points.get(i + 1) {
+            Some(&next) => next,
+            None => (points[0].0 + 360., points[0].1 + 360.),
+        };
+        if (from..next_from).contains(&hue) {
+            // Go the short way round between the targets
+            let mut span = next_to - to;
+            if span > 180. {
+                span -= 360.;
+            } else if span < -180. {
+                span += 360.;
+            }
+            let t = (hue - from) / (next_from - from);
+            return (to + span * t).rem_euclid(360.);
+        }
+    }
+    hue
+}
+
+impl Palette {
+    pub(crate) const ALL: [Palette; 5] = [
+        Palette::Default,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    /// Id of the profile's name in the locale files.
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            Palette::Default => "palette-default",
+            Palette::Deuteranopia => "palette-deuteranopia",
+            Palette::Protanopia => "palette-protanopia",
+            Palette::Tritanopia => "palette-tritanopia",
+            Palette::HighContrast => "palette-high-contrast",
+        }
+    }
+
+    /// The color to draw in place of `color`. Grays are left alone.
+    pub(crate) fn map(self, color: Color) -> Color {
+        let mut hsla = Hsla::from(color);
+        if self == Palette::Default || hsla.saturation < 0.05 {
+            return color;
+        }
+
+        match self {
+            Palette::Default => {}
+            Palette::Deuteranopia => hsla.hue = remap_hue(hsla.hue, &RED_GREEN),
+            Palette::Protanopia => {
+                hsla.hue = remap_hue(hsla.hue, &RED_GREEN);
+                // Reds are seen darker, lift them back up
+                hsla.lightness = hsla.lightness.max(0.45);
+            }
+            Palette::Tritanopia => hsla.hue = remap_hue(hsla.hue, &BLUE_YELLOW),
+            Palette::HighContrast => {
+                hsla.saturation = 1.;
+                hsla.lightness = hsla.lightness.max(0.6);
+            }
+        }
+        hsla.into()
+    }
+}
+
+/// A sprite's color as the game set it, and as it was last drawn.
+#[derive(Component)]
+pub(crate) struct Paletted {
+    source: Color,
+    shown: Color,
+}
+
+/// Runs every sprite's color through the [`Palette`] and then any photo mode
+/// filter, picking up new colors whenever something other than this changes
+/// one.
+pub(crate) fn recolor(
+    palette: Res<Palette>,
+    photo: Res<Photo>,
+    mut sprites: Query<(Entity, &mut Sprite, Option<&mut Paletted>)>,
+    changed: Query<(), Changed<Sprite>>,
+    mut commands: Commands,
+) {
+    for (entity, mut sprite, paletted) in &mut sprites {
+        let mut paletted = match paletted {
+            Some(paletted) => paletted,
+            None => {
+                let source = sprite.color;
+                let shown = photo.shown().map(palette.map(source));
+                sprite.color = shown;
+                commands.entity(entity).insert(Paletted { source, shown });
+                continue;
+            }
+        };
+        if !palette.is_changed() && !photo.is_changed() && !changed.contains(entity) {
+            continue;
+        }
+
+        if sprite.color != paletted.shown {
+            paletted.source = sprite.color;
+        }
+        let shown = photo.shown().map(palette.map(paletted.source));
+        paletted.shown = shown;
+        if sprite.color != shown {
+            sprite.color = shown;
+        }
+    }
+}
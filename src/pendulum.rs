@@ -0,0 +1,152 @@
+use std::f32::consts::TAU;
+
+use avian2d::prelude::*;
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{level, player::Hookable, state::InGame, surface::SurfaceMaterial};
+
+const PLATFORM_HEIGHT: f32 = 20.;
+/// Heavier than the player, so yanking it pulls the player over instead
+const PLATFORM_DENSITY: f32 = 3.;
+const ARM_MASS: f32 = 100.;
+const ARM_WIDTH: f32 = 6.;
+/// Arms turning slower than this through the bottom are settling down, not
+/// swinging, radians per second
+const MIN_SWING: f32 = 0.5;
+
+/// The arm a swinging platform hangs from, turning around its pivot. Left to
+/// gravity unless it has a `period`, then it's driven back and forth between
+/// `-amplitude` and `amplitude`.
+#[derive(Component)]
+pub(crate) struct Pendulum {
+    amplitude: f32,
+    period: Option<f32>,
+    elapsed: f32,
+    /// The platform hanging from the arm
+    platform: Entity,
+    /// Which side of the pivot the arm was on last tick
+    side: f32,
+}
+
+/// Sent when a pendulum swings through the bottom of its arc.
+#[derive(Event)]
+pub(crate) struct Swung {
+    pub(crate) platform: Entity,
+}
+
+/// Spawn the pivot, the arm and the platform at its end, starting the swing
+/// from `angle`.
+pub(crate) fn spawn(commands: &mut Commands, placed: &level::Pendulum) {
+    let pivot_position = Vec2::new(placed.x, placed.y);
+    let angle = placed.angle.to_radians();
+    let arm_end = Vec2::NEG_Y * placed.length;
+
+    let pivot = commands
+        .spawn((
+            RigidBody::Static,
+            Position(pivot_position),
+            StateScoped(InGame),
+        ))
+        .id();
+
+    let platform_position = pivot_position + Vec2::from_angle(angle).rotate(arm_end);
+    let platform = commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::hsl(210., 0.3, 0.45),
+                    custom_size: Some(Vec2::new(placed.w, PLATFORM_HEIGHT)),
+                    ..default()
+                },
+                transform: Transform::from_translation(platform_position.extend(0.)),
+                ..default()
+            },
+            RigidBody::Dynamic,
+            Collider::rectangle(placed.w, PLATFORM_HEIGHT),
+            ColliderDensity(PLATFORM_DENSITY),
+            // Stays level however the arm turns
+            LockedAxes::ROTATION_LOCKED,
+            Friction::new(1.),
+            SurfaceMaterial::Metal,
+            Hookable,
+            StateScoped(InGame),
+        ))
+        .id();
+
+    let arm = commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::hsl(0., 0., 0.3),
+                    custom_size: Some(Vec2::new(ARM_WIDTH, placed.length)),
+                    anchor: Anchor::TopCenter,
+                    ..default()
+                },
+                transform: Transform::from_translation(pivot_position.extend(-0.6))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+                ..default()
+            },
+            Pendulum {
+                amplitude: angle,
+                period: placed.period,
+                elapsed: 0.,
+                platform,
+                side: angle.signum(),
+            },
+            if placed.period.is_some() {
+                RigidBody::Kinematic
+            } else {
+                RigidBody::Dynamic
+            },
+            // No collider, so it has to be given a mass of its own
+            Mass(ARM_MASS),
+            Inertia(ARM_MASS * placed.length.powi(2) / 3.),
+            StateScoped(InGame),
+        ))
+        .id();
+
+    for joint in [
+        RevoluteJoint::new(pivot, arm),
+        RevoluteJoint::new(arm, platform).with_local_anchor_1(arm_end),
+    ] {
+        commands.spawn((joint, StateScoped(InGame)));
+    }
+}
+
+/// Turns driven arms towards where they should be this tick. Setting the
+/// velocity instead of the rotation lets whatever rides along keep up.
+pub(crate) fn drive(
+    mut arms: Query<(&mut Pendulum, &Rotation, &mut AngularVelocity)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    if delta == 0. {
+        return;
+    }
+
+    for (mut pendulum, rotation, mut velocity) in &mut arms {
+        let Some(period) = pendulum.period else {
+            continue;
+        };
+
+        pendulum.elapsed += delta;
+        let target = pendulum.amplitude * (pendulum.elapsed / period * TAU).cos();
+        velocity.0 = (target - rotation.as_radians()) / delta;
+    }
+}
+
+/// Tells when an arm swings past the bottom, fast enough to be heard.
+pub(crate) fn swing(
+    mut arms: Query<(&mut Pendulum, &Rotation, &AngularVelocity)>,
+    mut swung: EventWriter<Swung>,
+) {
+    for (mut pendulum, rotation, velocity) in &mut arms {
+        let side = rotation.as_radians().signum();
+        if side != pendulum.side && velocity.0.abs() > MIN_SWING {
+            swung.send(Swung {
+                platform: pendulum.platform,
+            });
+        }
+        pendulum.side = side;
+    }
+}
@@ -0,0 +1,261 @@
+use std::any::TypeId;
+
+use bevy::{
+    prelude::*,
+    render::view::{screenshot::ScreenshotManager, VisibilitySystems},
+    window::PrimaryWindow,
+};
+use bevy_egui::{egui, EguiContexts};
+use bevy_light_2d::prelude::*;
+
+use crate::{camera::MainCamera, capture::Screenshots, locale::Locale, state::AppState};
+
+/// Freezes the level and lets the camera go anywhere for taking pictures,
+/// entered from the pause menu. The HUD and gizmos are hidden meanwhile and
+/// a [`Filter`] can be laid over the scene.
+pub struct PhotoPlugin;
+
+impl Plugin for PhotoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Photo>()
+            .add_systems(OnEnter(AppState::PhotoMode), enter)
+            .add_systems(OnExit(AppState::PhotoMode), exit)
+            .add_systems(
+                Update,
+                (photo_window, tint).run_if(in_state(AppState::PhotoMode)),
+            )
+            .add_systems(
+                PostUpdate,
+                hide_hud
+                    .before(VisibilitySystems::VisibilityPropagate)
+                    .run_if(in_state(AppState::PhotoMode)),
+            );
+    }
+}
+
+/// Looks for the scene in photo mode.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub(crate) enum Filter {
+    #[default]
+    None,
+    Grayscale,
+    Sepia,
+    /// Golden light, like late afternoon
+    Warm,
+    Cool,
+    /// Dim and blue
+    Night,
+}
+
+impl Filter {
+    const ALL: [Filter; 6] = [
+        Filter::None,
+        Filter::Grayscale,
+        Filter::Sepia,
+        Filter::Warm,
+        Filter::Cool,
+        Filter::Night,
+    ];
+
+    /// Id of the filter's name in the locale files.
+    fn message(self) -> &'static str {
+        match self {
+            Filter::None => "photo-filter-none",
+            Filter::Grayscale => "photo-filter-grayscale",
+            Filter::Sepia => "photo-filter-sepia",
+            Filter::Warm => "photo-filter-warm",
+            Filter::Cool => "photo-filter-cool",
+            Filter::Night => "photo-filter-night",
+        }
+    }
+
+    /// The color to draw a sprite in place of `color`.
+    pub(crate) fn map(self, color: Color) -> Color {
+        let gray = color.luminance();
+        match self {
+            Filter::Grayscale => Color::linear_rgba(gray, gray, gray, color.alpha()),
+            Filter::Sepia => {
+                Color::linear_rgba(gray * 1.07, gray * 0.74, gray * 0.43, color.alpha())
+            }
+            Filter::None | Filter::Warm | Filter::Cool | Filter::Night => color,
+        }
+    }
+
+    /// The ambient light, from the one the camera had before photo mode.
+    fn ambient(self, ambient: &AmbientLight2d) -> AmbientLight2d {
+        let (color, brightness) = match self {
+            Filter::None | Filter::Grayscale | Filter::Sepia => return ambient.clone(),
+            Filter::Warm => (Color::srgb(1., 0.85, 0.65), ambient.brightness),
+            Filter::Cool => (Color::srgb(0.7, 0.85, 1.), ambient.brightness),
+            Filter::Night => (Color::srgb(0.45, 0.55, 1.), ambient.brightness * 0.5),
+        };
+        AmbientLight2d { color, brightness }
+    }
+}
+
+/// What photo mode put aside, to put back when it's left.
+struct Saved {
+    camera: Transform,
+    ambient: AmbientLight2d,
+    /// UI roots that were showing
+    hud: Vec<Entity>,
+    /// Gizmo groups that were drawing
+    gizmos: Vec<TypeId>,
+}
+
+#[derive(Resource, Default)]
+pub(crate) struct Photo {
+    /// Kept for the next time photo mode is entered
+    filter: Filter,
+    /// Only while in photo mode
+    saved: Option<Saved>,
+}
+
+impl Photo {
+    /// The filter the scene is drawn with, none outside photo mode.
+    pub(crate) fn shown(&self) -> Filter {
+        match self.saved {
+            Some(_) => self.filter,
+            None => Filter::None,
+        }
+    }
+}
+
+fn enter(
+    mut photo: ResMut<Photo>,
+    mut time: ResMut<Time<Virtual>>,
+    cameras: Query<(&Transform, &AmbientLight2d), With<MainCamera>>,
+    nodes: Query<(Entity, &Visibility), (With<Node>, Without<Parent>)>,
+    mut store: ResMut<GizmoConfigStore>,
+) {
+    // Everything that isn't physics stops too, like particles and animations
+    time.pause();
+    let Some((camera, ambient)) = cameras.iter().next() else {
+        return;
+    };
+
+    let hud = nodes
+        .iter()
+        .filter(|(_, visibility)| **visibility != Visibility::Hidden)
+        .map(|(entity, _)| entity)
+        .collect();
+    let mut gizmos = Vec::new();
+    for (id, config, _) in store.iter_mut() {
+        if config.enabled {
+            config.enabled = false;
+            gizmos.push(*id);
+        }
+    }
+
+    photo.saved = Some(Saved {
+        camera: *camera,
+        ambient: ambient.clone(),
+        hud,
+        gizmos,
+    });
+}
+
+fn exit(
+    mut photo: ResMut<Photo>,
+    mut time: ResMut<Time<Virtual>>,
+    mut cameras: Query<(&mut Transform, &mut AmbientLight2d), With<MainCamera>>,
+    mut nodes: Query<&mut Visibility, With<Node>>,
+    mut store: ResMut<GizmoConfigStore>,
+) {
+    time.unpause();
+    let Some(saved) = photo.saved.take() else {
+        return;
+    };
+
+    for (mut transform, mut ambient) in &mut cameras {
+        *transform = saved.camera;
+        *ambient = saved.ambient.clone();
+    }
+    // Some may have been despawned meanwhile
+    let mut hud = nodes.iter_many_mut(&saved.hud);
+    while let Some(mut visibility) = hud.fetch_next() {
+        *visibility = Visibility::Inherited;
+    }
+    for (id, config, _) in store.iter_mut() {
+        if saved.gizmos.contains(id) {
+            config.enabled = true;
+        }
+    }
+}
+
+/// Keeps the UI hidden, some of it shows itself again every frame.
+fn hide_hud(mut nodes: Query<&mut Visibility, (With<Node>, Without<Parent>)>) {
+    for mut visibility in &mut nodes {
+        visibility.set_if_neq(Visibility::Hidden);
+    }
+}
+
+/// Lights the scene for the filter whenever it's picked.
+fn tint(photo: Res<Photo>, mut cameras: Query<&mut AmbientLight2d, With<MainCamera>>) {
+    let Some(saved) = photo.saved.as_ref().filter(|_| photo.is_changed()) else {
+        return;
+    };
+    for mut ambient in &mut cameras {
+        *ambient = photo.filter.ambient(&saved.ambient);
+    }
+}
+
+/// The filter, view and screenshot controls. It hides for the frame a
+/// screenshot is taken of.
+fn photo_window(
+    mut contexts: EguiContexts,
+    mut photo: ResMut<Photo>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut manager: ResMut<ScreenshotManager>,
+    mut screenshots: ResMut<Screenshots>,
+    mut next: ResMut<NextState<AppState>>,
+    mut pending: Local<bool>,
+    locale: Res<Locale>,
+) {
+    if *pending {
+        *pending = false;
+        if let Some(window) = window.iter().next() {
+            screenshots.save(&mut manager, window);
+        }
+        return;
+    }
+
+    egui::Window::new(locale.get("photo"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_TOP, [10., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(egui::RichText::new(locale.get("photo-controls")).weak());
+            ui.add_space(5.);
+
+            let mut filter = photo.filter;
+            egui::ComboBox::from_label(locale.get("photo-filter"))
+                .selected_text(locale.get(filter.message()))
+                .show_ui(ui, |ui| {
+                    for option in Filter::ALL {
+                        ui.selectable_value(&mut filter, option, locale.get(option.message()));
+                    }
+                });
+            if filter != photo.filter {
+                photo.filter = filter;
+            }
+
+            if ui.button(locale.get("photo-reset")).clicked() {
+                if let Some(saved) = &photo.saved {
+                    for mut transform in &mut cameras {
+                        *transform = saved.camera;
+                    }
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button(locale.get("photo-screenshot")).clicked() {
+                    *pending = true;
+                }
+                if ui.button(locale.get("menu-back")).clicked() {
+                    next.set(AppState::Paused);
+                }
+            });
+        });
+}
@@ -0,0 +1,154 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    atlas::AtlasSprite,
+    inventory::{Inventory, Item},
+    level,
+    player::Player,
+    state::InGame,
+};
+
+#[derive(Component, Default)]
+pub(crate) struct Coin;
+
+/// How many coins the player has collected
+#[derive(Resource, Default)]
+pub(crate) struct Coins(pub(crate) u32);
+
+/// Sent when the player collects a pickup.
+#[derive(Event)]
+pub(crate) struct PickedUp {
+    pub(crate) position: Vec2,
+}
+
+pub(crate) fn make_coin(
+    x: f32,
+    y: f32,
+) -> (
+    SpriteBundle,
+    AtlasSprite,
+    Coin,
+    Collider,
+    Sensor,
+    StateScoped<InGame>,
+) {
+    let r = 15.;
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(50., 1.0, 0.6),
+                custom_size: Some(Vec2::new(r * 2., r * 2.)),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, 0.),
+            ..default()
+        },
+        AtlasSprite("coin"),
+        Coin,
+        Collider::circle(r),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn collect(
+    mut started: EventReader<CollisionStarted>,
+    players: Query<(), With<Player>>,
+    coins: Query<&Transform, With<Coin>>,
+    mut count: ResMut<Coins>,
+    mut picked_up: EventWriter<PickedUp>,
+    mut commands: Commands,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (coin, other) = if coins.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok(transform) = coins.get(coin) else {
+            continue;
+        };
+        if !players.contains(other) {
+            continue;
+        }
+
+        commands.entity(coin).despawn();
+        count.0 += 1;
+        picked_up.send(PickedUp {
+            position: transform.translation.truncate(),
+        });
+    }
+}
+
+/// Something to carry, going into the player's inventory when touched.
+#[derive(Component)]
+pub(crate) struct ItemPickup {
+    item: Item,
+    count: u32,
+}
+
+pub(crate) fn make_item(
+    placed: &level::ItemPickup,
+) -> (
+    SpriteBundle,
+    AtlasSprite,
+    ItemPickup,
+    Collider,
+    Sensor,
+    StateScoped<InGame>,
+) {
+    let size = 30.;
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: placed.item.color(),
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, 0.),
+            ..default()
+        },
+        AtlasSprite("item"),
+        ItemPickup {
+            item: placed.item,
+            count: placed.count,
+        },
+        Collider::rectangle(size, size),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+/// Items stay where they are while the inventory is full.
+pub(crate) fn collect_items(
+    mut started: EventReader<CollisionStarted>,
+    mut players: Query<&mut Inventory, With<Player>>,
+    items: Query<(&ItemPickup, &Transform)>,
+    mut picked_up: EventWriter<PickedUp>,
+    mut commands: Commands,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (item, other) = if items.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok((pickup, transform)) = items.get(item) else {
+            continue;
+        };
+        let Ok(mut inventory) = players.get_mut(other) else {
+            continue;
+        };
+        if !inventory.add(pickup.item, pickup.count) {
+            continue;
+        }
+
+        commands.entity(item).despawn();
+        picked_up.send(PickedUp {
+            position: transform.translation.truncate(),
+        });
+    }
+}
@@ -0,0 +1,92 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{level, state::InGame};
+
+const HEIGHT: f32 = 10.;
+const RELEASED_COLOR: Color = Color::hsl(0., 0.6, 0.4);
+const PRESSED_COLOR: Color = Color::hsl(120., 0.6, 0.45);
+
+/// Held down by any body at least `min_mass` heavy resting on it.
+#[derive(Component)]
+pub(crate) struct PressurePlate {
+    channel: u32,
+    min_mass: f32,
+    pressed: bool,
+}
+
+/// Sent when a trigger wired to `channel` turns on or off. Whatever reacts to
+/// it only knows the channel, not what set it off.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Triggered {
+    pub(crate) channel: u32,
+    pub(crate) active: bool,
+}
+
+pub(crate) fn make_plate(
+    placed: &level::Plate,
+) -> (
+    SpriteBundle,
+    PressurePlate,
+    RigidBody,
+    Collider,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: RELEASED_COLOR,
+                custom_size: Some(Vec2::new(placed.w, HEIGHT)),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y + HEIGHT / 2., 0.),
+            ..default()
+        },
+        PressurePlate {
+            channel: placed.channel,
+            min_mass: placed.min_mass,
+            pressed: false,
+        },
+        RigidBody::Static,
+        Collider::rectangle(placed.w, HEIGHT),
+        StateScoped(InGame),
+    )
+}
+
+/// Checks what is touching each plate after the physics step. Uses the
+/// contacts kept by the physics instead of collision events, so a body that
+/// falls asleep on a plate keeps it held down.
+pub(crate) fn press(
+    mut plates: Query<(Entity, &mut PressurePlate, &mut Sprite)>,
+    masses: Query<&Mass>,
+    collisions: Res<Collisions>,
+    mut triggered: EventWriter<Triggered>,
+) {
+    for (entity, mut plate, mut sprite) in &mut plates {
+        let pressed = collisions
+            .collisions_with_entity(entity)
+            .filter(|contacts| !contacts.is_sensor)
+            .map(|contacts| {
+                if contacts.entity1 == entity {
+                    contacts.entity2
+                } else {
+                    contacts.entity1
+                }
+            })
+            .any(|other| masses.get(other).is_ok_and(|mass| mass.0 >= plate.min_mass));
+        if pressed == plate.pressed {
+            continue;
+        }
+
+        plate.pressed = pressed;
+        sprite.color = if pressed {
+            PRESSED_COLOR
+        } else {
+            RELEASED_COLOR
+        };
+        triggered.send(Triggered {
+            channel: plate.channel,
+            active: pressed,
+        });
+    }
+}
@@ -1,164 +1,1611 @@
+use std::f32::consts::PI;
+
 use avian2d::prelude::*;
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::Deserialize;
 
-use crate::{KeepUpright, MyWorldCoords};
+use crate::{
+    abilities::{Abilities, Ability},
+    anchor::{AimAssist, HookAnchor},
+    atlas::{Animated, AtlasSprite, Sheet},
+    camera::MyWorldCoords,
+    cull::Cull,
+    death,
+    explosion::Exploded,
+    gravity::GravityDirection,
+    health::{self, Damage, Health, Hitstun},
+    input::{Action, ActionState},
+    interpolation::Interpolated,
+    inventory::{self, Inventory, Item},
+    kinematic::{self, KinematicBody},
+    level::{Level, Levels},
+    lighting,
+    netplay::{Loadout, Session},
+    pool::Pool,
+    reticle,
+    rope::{self, RopeSegment},
+    run::Run,
+    save::SaveData,
+    settings::{AssistSettings, GameplaySettings},
+    skins::SkinSheets,
+    squash::{self, SquashStretch},
+    state::{AppState, GameplaySet, InGame, PostPhysicsSet},
+    surface::SurfaceMaterial,
+    trail,
+    weather::WeatherBlend,
+    world::Layer,
+    wrap::{self, Corner},
+    zipline,
+};
 
-#[derive(Component, Default)]
+pub(crate) const PLAYER_RADIUS: f32 = 50.;
+/// How much geometry may stick out in front of the hook point before the
+/// rope counts as blocked
+const HOOK_CLEARANCE: f32 = 5.;
+/// Yanking stops with the rope this short
+const YANK_MIN_LENGTH: f32 = 120.;
+/// The pull hook lets go once the player is this close to where it's tied
+const PULL_ARRIVAL: f32 = PLAYER_RADIUS + 30.;
+/// Speed a dash sets off with, sideways
+const DASH_SPEED: f32 = 1400.;
+/// Seconds between dashes on the ground
+const DASH_COOLDOWN: f32 = 0.5;
+/// Speed a wall jump pushes away from the wall with
+const WALL_JUMP_PUSH: f32 = 700.;
+/// Surfaces this close to upright count as walls
+pub(crate) const WALL_DOT: f32 = 0.3;
+/// Bombs out at once, per player
+const MAX_BOMBS: usize = 3;
+const BOMB_RADIUS: f32 = 15.;
+const BOMB_BLAST_RADIUS: f32 = 250.;
+/// Speed bombs are thrown at on top of the player's own
+const THROW_SPEED: f32 = 800.;
+/// Seconds a grenade takes to charge up fully
+const GRENADE_CHARGE: f32 = 1.;
+const GRENADE_MIN_SPEED: f32 = 300.;
+const GRENADE_MAX_SPEED: f32 = 1600.;
+
+/// The player body, its controls and abilities, and the effects that follow
+/// it around.
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Jumped>()
+            .add_event::<Landed>()
+            .add_event::<SlideStarted>()
+            .add_event::<HookFired>()
+            .add_event::<HookAttached>()
+            .add_event::<HookReleased>()
+            .add_event::<Damage>()
+            .init_resource::<HookRules>()
+            .init_resource::<Movement>()
+            .init_resource::<health::GodMode>()
+            .init_resource::<Pool<RopeSegment>>()
+            .add_systems(OnEnter(InGame), setup)
+            .add_systems(OnExit(InGame), Pool::<RopeSegment>::clear)
+            .add_systems(OnEnter(AppState::Dying), death::go_limp)
+            .add_systems(Update, death::tumble.run_if(in_state(AppState::Dying)))
+            .add_systems(OnEnter(AppState::Playing), death::recover)
+            .add_systems(FixedUpdate, control.before(GameplaySet))
+            .add_systems(FixedUpdate, movement.in_set(GameplaySet))
+            .add_systems(
+                FixedUpdate,
+                (wrap::wrap, hook)
+                    .chain()
+                    .after(movement)
+                    .in_set(GameplaySet),
+            )
+            .add_systems(FixedUpdate, yank.after(hook).in_set(GameplaySet))
+            .add_systems(FixedUpdate, pull.after(hook).in_set(GameplaySet))
+            .add_systems(
+                FixedUpdate,
+                (zipline::cool_down, zipline::grab, zipline::ride)
+                    .chain()
+                    .after(yank)
+                    .in_set(GameplaySet),
+            )
+            .add_systems(
+                Update,
+                (reticle::draw, reticle::draw_arc).in_set(GameplaySet),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                kinematic::move_and_slide
+                    .before(PhysicsSet::Prepare)
+                    .in_set(GameplaySet),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                (is_grounded, keep_upright)
+                    .chain()
+                    .in_set(PostPhysicsSet)
+                    .in_set(GameplaySet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (inventory::select, inventory::consume, bomb)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(FixedUpdate, (bomb_fuse, stick_bombs).in_set(GameplaySet))
+            .add_systems(
+                FixedUpdate,
+                (health::tick, health::apply_damage, death::die)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(
+                Update,
+                (squash::jump_and_land, squash::airborne, squash::spring)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(
+                Update,
+                (trail::start, trail::record, trail::draw)
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
+
+#[derive(Component, Default, Clone, Reflect)]
 pub(crate) struct Player {
-    is_grounded: bool,
+    pub(crate) is_grounded: bool,
+    was_grounded: bool,
+    /// What the player is standing on, if anything
+    pub(crate) ground: Option<Entity>,
+    pub(crate) is_sliding: bool,
+    /// Downward speed from the last frame spent in the air
+    fall_speed: f32,
+    /// Elapsed time at which the hook can be fired again
+    hook_ready_at: f32,
+    /// Seconds the bomb button has been held for with grenades picked
+    pub(crate) grenade_charge: Option<f32>,
+    /// Normal of the wall the player is touching, pointing away from it
+    pub(crate) wall: Option<Vec2>,
+    /// Used the double jump since leaving the ground
+    air_jumped: bool,
+    /// Used the dash since leaving the ground
+    air_dashed: bool,
+    /// Elapsed time at which the player can dash again
+    dash_ready_at: f32,
+}
+
+impl Player {
+    /// Forgets what the player was touching, before finding out again for
+    /// this tick.
+    pub(crate) fn clear_contacts(&mut self) {
+        self.was_grounded = self.is_grounded;
+        self.is_grounded = false;
+        self.ground = None;
+        self.wall = None;
+    }
+
+    /// Gives back the air moves once on the ground and sends [`Landed`] on
+    /// touching down, or else keeps track of how fast the player falls.
+    pub(crate) fn settle_contacts(
+        &mut self,
+        entity: Entity,
+        velocity: Vec2,
+        up: Vec2,
+        landed: &mut EventWriter<Landed>,
+    ) {
+        if self.is_grounded {
+            self.air_jumped = false;
+            self.air_dashed = false;
+        }
+        if self.is_grounded && !self.was_grounded {
+            landed.send(Landed {
+                player: entity,
+                impact_speed: self.fall_speed,
+            });
+        }
+
+        if !self.is_grounded {
+            self.fall_speed = (-velocity.dot(up)).max(0.0);
+        }
+    }
+}
+
+/// What a player's controls do this tick, so every player can be driven
+/// by their own. The local player's come from [`ActionState`] and the
+/// cursor, a [`Remote`] player's over the network.
+#[derive(Component, Default)]
+pub(crate) struct Controls {
+    pub(crate) actions: ActionState,
+    /// Where the cursor is in the world, aiming goes there without a stick
+    pub(crate) cursor: Vec2,
+}
+
+/// The other player in an online game, see
+/// [`NetplayPlugin`](crate::NetplayPlugin). The camera, sounds and screens
+/// follow the player without it.
+#[derive(Component)]
+pub(crate) struct Remote;
+
+/// How long a slide can last and how long until the next one.
+#[derive(Component, Clone)]
+pub(crate) struct Slide {
+    pub(crate) duration: Timer,
+    pub(crate) cooldown: Timer,
+}
+
+impl Default for Slide {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(1.0, TimerMode::Once);
+        // Ready straight away
+        cooldown.tick(cooldown.duration());
+
+        Self {
+            duration: Timer::from_seconds(0.75, TimerMode::Once),
+            cooldown,
+        }
+    }
+}
+
+/// Which of the player's two hooks, each fired with its own button.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+pub(crate) enum Hand {
+    Main,
+    Off,
+}
+
+impl Hand {
+    pub(crate) const ALL: [Hand; 2] = [Hand::Main, Hand::Off];
+
+    pub(crate) fn action(self) -> Action {
+        match self {
+            Hand::Main => Action::Hook,
+            Hand::Off => Action::SecondHook,
+        }
+    }
+}
+
+/// The hooks the player has attached, at most one per [`Hand`].
+#[derive(Component, Reflect, Default)]
+pub(crate) struct Hooks(pub(crate) Vec<Hooked>);
+
+impl Hooks {
+    /// Cut every rope, for when the player gets taken away from where
+    /// they're tied.
+    pub(crate) fn cut_all(
+        &mut self,
+        commands: &mut Commands,
+        links: &mut Pool<RopeSegment>,
+        released: &mut EventWriter<HookReleased>,
+    ) {
+        for hooked in self.0.drain(..) {
+            hooked.cut(commands, links);
+            released.send(HookReleased);
+        }
+    }
+}
+
+/// One attached hook and the rope to it.
+#[derive(Reflect)]
+pub(crate) struct Hooked {
+    pub(crate) hand: Hand,
+    /// Static body at the point the hook caught on, or the object it caught
+    pub(crate) point: Entity,
+    /// The [`DistanceJoint`] between the player and `point`, or the first
+    /// link of a segmented rope
+    pub(crate) rope: Entity,
+    /// `point` is a [`Hookable`] object, which stays when the rope goes
+    pub(crate) object: bool,
+    /// The other joints of a segmented rope, empty otherwise
+    pub(crate) segments: Vec<Entity>,
+    /// The bodies a segmented rope is made of, parked in their [`Pool`] when
+    /// it's cut
+    pub(crate) links: Vec<Entity>,
+    /// Corners a plain rope is bent around, the one nearest the player last
+    pub(crate) corners: Vec<Corner>,
+}
+
+impl Hooked {
+    /// Despawn the rope and the point it was tied to, either may already be
+    /// gone if the level was unloaded.
+    pub(crate) fn cut(&self, commands: &mut Commands, links: &mut Pool<RopeSegment>) {
+        for &link in &self.links {
+            links.park(commands, link);
+        }
+        let corners = self.corners.iter().map(|corner| &corner.body);
+        for &entity in std::iter::once(&self.rope)
+            .chain(&self.segments)
+            .chain(corners)
+        {
+            if let Some(mut entity) = commands.get_entity(entity) {
+                entity.despawn();
+            }
+        }
+        if self.object {
+            return;
+        }
+        if let Some(mut point) = commands.get_entity(self.point) {
+            point.despawn();
+        }
+    }
+
+    /// How much rope goes from `point` around the corners to the last one.
+    pub(crate) fn wrapped_length(&self, point: Vec2) -> f32 {
+        self.corners
+            .iter()
+            .scan(point, |from, corner| {
+                let length = from.distance(corner.position);
+                *from = corner.position;
+                Some(length)
+            })
+            .sum()
+    }
+}
+
+/// Movable objects the rope ties straight onto, so swinging or walking away
+/// drags them along.
+#[derive(Component, Default)]
+pub(crate) struct Hookable;
+
+/// Where the hook catches on.
+pub(crate) struct HookTarget {
+    pub(crate) point: Vec2,
+    /// The [`Hookable`] object there, if it caught one
+    pub(crate) object: Option<Entity>,
+}
+
+/// How the player runs and jumps.
+#[derive(Resource, Clone)]
+pub(crate) struct Movement {
+    /// Speed gained each tick the stick is held
+    pub(crate) accel: f32,
+    /// Fastest the player can run on their own, momentum can go beyond it
+    pub(crate) max_speed: f32,
+    pub(crate) jump_impulse: f32,
+    /// Against the ground while not sliding
+    pub(crate) friction: f32,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self {
+            accel: 100.,
+            max_speed: 1000.,
+            jump_impulse: 600.,
+            friction: 1.,
+        }
+    }
+}
+
+/// How the hook behaves, overridable per level.
+#[derive(Resource, Deserialize, Clone)]
+#[serde(default)]
+pub(crate) struct HookRules {
+    /// Furthest the hook can reach
+    pub(crate) range: f32,
+    /// Seconds after letting go before the hook can be fired again
+    pub(crate) cooldown: f32,
+    /// Let go when something comes between the player and the hook point
+    pub(crate) break_when_blocked: bool,
+    /// Bend the rope around corners instead, letting go only when it can't
+    pub(crate) wrap_around_corners: bool,
+    /// How fast the pull hook reels the player in
+    pub(crate) pull_speed: f32,
+    /// Speed objects are reeled in at, or the player towards heavy ones
+    pub(crate) yank_speed: f32,
+    /// Seconds the game slows down for after firing, none when 0
+    pub(crate) bullet_time: f32,
+}
+
+impl Default for HookRules {
+    fn default() -> Self {
+        Self {
+            range: 5000.,
+            cooldown: 0.,
+            break_when_blocked: true,
+            wrap_around_corners: true,
+            pull_speed: 1500.,
+            yank_speed: 700.,
+            bullet_time: 0.,
+        }
+    }
+}
+
+/// The visible part of the player, kept separate so it can be deformed
+/// without touching the collider.
+#[derive(Component, Default)]
+pub(crate) struct PlayerSprite;
+
+/// Sent when the player jumps off the ground.
+#[derive(Event)]
+pub(crate) struct Jumped;
+
+/// Sent on the frame the player becomes grounded after being in the air.
+#[derive(Event)]
+pub(crate) struct Landed {
+    pub(crate) player: Entity,
+    pub(crate) impact_speed: f32,
+}
+
+/// Sent when the player starts sliding.
+#[derive(Event)]
+pub(crate) struct SlideStarted;
+
+/// Sent when the hook button is pressed, whether or not it hits anything.
+#[derive(Event)]
+pub(crate) struct HookFired;
+
+/// Sent when the hook catches on something and the rope is created.
+#[derive(Event)]
+pub(crate) struct HookAttached {
+    pub(crate) player: Entity,
+    pub(crate) position: Vec2,
 }
 
+/// Sent when the rope is let go.
+#[derive(Event)]
+pub(crate) struct HookReleased;
+
 #[derive(Component, Default)]
-pub(crate) struct Bomb {}
+struct KeepUpright {}
+
+#[derive(Component, Clone)]
+pub(crate) struct Bomb {
+    kind: BombKind,
+    fuse: Timer,
+    /// The player who threw it, none for ones spawned from the console
+    owner: Option<Entity>,
+}
+
+/// The bombs the player can carry.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect, Deserialize)]
+pub(crate) enum BombKind {
+    /// Goes off after its fuse runs out
+    Timed,
+    /// Like a timed one, but holds onto the first thing it hits
+    Sticky,
+    /// Waits for the bomb button to be pressed again
+    Remote,
+    /// Thrown harder the longer the button is held
+    Grenade,
+}
+
+impl BombKind {
+    pub(crate) fn color(self) -> Color {
+        match self {
+            BombKind::Timed => Color::hsl(0., 0.8, 0.3),
+            BombKind::Sticky => Color::hsl(100., 0.7, 0.35),
+            BombKind::Remote => Color::hsl(210., 0.8, 0.4),
+            BombKind::Grenade => Color::hsl(30., 0.9, 0.45),
+        }
+    }
+}
+
+/// On a sticky bomb once it's caught on something, holding the
+/// [`FixedJoint`] that keeps it there.
+#[derive(Component)]
+pub(crate) struct Stuck(Entity);
+
+pub(crate) fn setup(
+    mut commands: Commands,
+    save: Res<SaveData>,
+    skins: Res<SkinSheets>,
+    levels: Res<Levels>,
+    assets: Res<Assets<Level>>,
+    run: Res<Run>,
+    settings: Res<GameplaySettings>,
+    session: Option<Res<Session>>,
+) {
+    let Some(level) = levels.current(&assets) else {
+        return;
+    };
+    let spawn = run.checkpoint.unwrap_or(level.spawn);
+
+    let Some((host, mine, theirs)) = session.as_deref().and_then(Session::players) else {
+        spawn_player(
+            &mut commands,
+            &Loadout::new(&save),
+            spawn,
+            &skins,
+            &settings,
+        );
+        return;
+    };
+    // Side by side with the host on the spawn point. The host's player goes
+    // first on both sides so the entities line up and the two simulations
+    // go through them in the same order.
+    let beside = spawn + Vec2::X * PLAYER_RADIUS * 3.;
+    if host {
+        spawn_player(&mut commands, mine, spawn, &skins, &settings);
+        let remote = spawn_player(&mut commands, theirs, beside, &skins, &settings);
+        commands.entity(remote).insert(Remote);
+    } else {
+        let remote = spawn_player(&mut commands, theirs, spawn, &skins, &settings);
+        commands.entity(remote).insert(Remote);
+        spawn_player(&mut commands, mine, beside, &skins, &settings);
+    }
+}
+
+fn spawn_player(
+    commands: &mut Commands,
+    loadout: &Loadout,
+    at: Vec2,
+    skins: &SkinSheets,
+    settings: &GameplaySettings,
+) -> Entity {
+    let ball_r = PLAYER_RADIUS;
+    let body = if settings.kinematic_body {
+        RigidBody::Kinematic
+    } else {
+        RigidBody::Dynamic
+    };
+    let player = commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(at.x, at.y, 0.)),
+            Player::default(),
+            Slide::default(),
+            Health::new(loadout.max_health),
+            // Nested, bundle tuples only go up to 15 components
+            (
+                body,
+                Collider::circle(ball_r),
+                CollisionLayers::new(Layer::Player, LayerMask::ALL),
+                LockedAxes::ROTATION_LOCKED,
+                Friction::new(0.).with_combine_rule(CoefficientCombine::Multiply),
+                GravityDirection::default(),
+                GravityScale(1.),
+            ),
+            Hooks::default(),
+            Inventory::default(),
+            Abilities(loadout.abilities.clone()),
+            Controls::default(),
+            KeepUpright::default(),
+            Interpolated::default(),
+            StateScoped(InGame),
+        ))
+        .with_children(|parent| {
+            let mut sprite = parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(ball_r * 2., ball_r * 2.)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                AtlasSprite("player"),
+                Animated::default(),
+                PlayerSprite,
+                SquashStretch::default(),
+            ));
+            if let Some(sheet) = skins.get(loadout.skin) {
+                sprite.insert(Sheet(sheet));
+            }
+            parent.spawn(lighting::player_light());
+        })
+        .id();
+    if settings.kinematic_body {
+        commands.entity(player).insert(KinematicBody);
+    }
+    player
+}
+
+/// Hands this tick's input to the player at the keyboard.
+pub(crate) fn control(
+    mut players: Query<&mut Controls, Without<Remote>>,
+    actions: Res<ActionState>,
+    coords: Res<MyWorldCoords>,
+) {
+    for mut controls in &mut players {
+        controls.actions.clone_from(&actions);
+        controls.cursor = coords.0;
+    }
+}
+
+fn is_grounded(
+    mut players: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Player,
+            &LinearVelocity,
+            &GravityDirection,
+        ),
+        Without<KinematicBody>,
+    >,
+    mut collisions: EventReader<Collision>,
+    mut landed: EventWriter<Landed>,
+) {
+    for (_, _, mut player, _, _) in &mut players {
+        player.clear_contacts();
+    }
+
+    for Collision(contacts) in collisions.read() {
+        if contacts.is_sensor {
+            continue;
+        }
+
+        for (entity, other) in [
+            (contacts.entity1, contacts.entity2),
+            (contacts.entity2, contacts.entity1),
+        ] {
+            let Ok((_, transform, mut player, _, gravity)) = players.get_mut(entity) else {
+                continue;
+            };
+            let up = gravity.up();
+            if stands_on(contacts, entity, transform.rotation, up) {
+                player.is_grounded = true;
+                player.ground = Some(other);
+            }
+            if let Some(wall) = surface_normals(contacts, entity, transform.rotation)
+                .find(|normal| normal.dot(up).abs() < WALL_DOT)
+            {
+                player.wall = Some(wall);
+            }
+        }
+    }
+
+    for (entity, _, mut player, velocity, gravity) in &mut players {
+        player.settle_contacts(entity, velocity.0, gravity.up(), &mut landed);
+    }
+}
+
+fn movement(
+    mut players: Query<(
+        &mut Friction,
+        &mut LinearVelocity,
+        &mut Player,
+        &mut Slide,
+        &GravityDirection,
+        &Abilities,
+        &Controls,
+        Has<Hitstun>,
+        Has<Remote>,
+    )>,
+    mut local: ResMut<ActionState>,
+    grounds: Query<&LinearVelocity, Without<Player>>,
+    surfaces: Query<&SurfaceMaterial>,
+    tuning: Res<Movement>,
+    weather: Res<WeatherBlend>,
+    assist: Res<AssistSettings>,
+    time: Res<Time>,
+    mut jumped: EventWriter<Jumped>,
+    mut slide_started: EventWriter<SlideStarted>,
+) {
+    for (
+        mut friction,
+        mut velocity,
+        mut player,
+        mut slide,
+        gravity,
+        abilities,
+        controls,
+        stunned,
+        remote,
+    ) in &mut players
+    {
+        let actions = &controls.actions;
+
+        // No steering out of a knockback
+        let input = if stunned {
+            Vec2::ZERO
+        } else {
+            Vec2::X * actions.move_x()
+        };
+
+        // Jump
+        // TODO: Detect ground
+        if actions.just_pressed(Action::Jump) && player.is_grounded && !stunned {
+            // Leave moving platforms with at least their speed, up and
+            // sideways, contacts only catch up with them a tick late
+            let up = gravity.up();
+            if let Some(ground) = player.ground.and_then(|ground| grounds.get(ground).ok()) {
+                let behind = ground.dot(up) - velocity.dot(up);
+                if behind > 0. {
+                    **velocity += up * behind;
+                }
+                // Sideways only in the direction the platform is going
+                let side = up.perp();
+                let ahead = ground.dot(side);
+                let behind = ahead - velocity.dot(side);
+                if behind * ahead > 0. {
+                    **velocity += side * behind;
+                }
+            }
+            **velocity += up * tuning.jump_impulse;
+            jumped.send(Jumped);
+        } else if actions.just_pressed(Action::Jump) && !player.is_grounded && !stunned {
+            let up = gravity.up();
+            // Off a wall first, the double jump is saved for when there's none
+            let wall = player.wall.filter(|_| abilities.has(Ability::WallJump));
+            if let Some(wall) = wall {
+                **velocity = wall * WALL_JUMP_PUSH + up * tuning.jump_impulse;
+                jumped.send(Jumped);
+            } else if assist.infinite_air_jumps()
+                || (abilities.has(Ability::DoubleJump) && !player.air_jumped)
+            {
+                player.air_jumped = true;
+                let rising = velocity.dot(up);
+                **velocity += up * (tuning.jump_impulse - rising);
+                jumped.send(Jumped);
+            }
+        }
+
+        // Dash, sideways the way the player is steering or else moving
+        let now = time.elapsed_seconds();
+        if actions.just_pressed(Action::Dash)
+            && abilities.has(Ability::Dash)
+            && !player.air_dashed
+            && now >= player.dash_ready_at
+            && !stunned
+        {
+            let up = gravity.up();
+            let side = if actions.move_x() != 0. {
+                actions.move_x().signum()
+            } else if velocity.x != 0. {
+                velocity.x.signum()
+            } else {
+                1.
+            };
+            // Flat through the air, keeping any speed beyond the dash's own
+            let rising = velocity.dot(up);
+            **velocity -= up * rising;
+            velocity.x = side * DASH_SPEED.max(velocity.x * side);
+            player.air_dashed = !player.is_grounded;
+            player.dash_ready_at = now + DASH_COOLDOWN;
+        }
+
+        // Slide
+        // Only tick running timers so the HUD can rely on change detection
+        if !slide.duration.finished() {
+            slide.duration.tick(time.delta());
+        }
+        if !slide.cooldown.finished() {
+            slide.cooldown.tick(time.delta());
+        }
+
+        if actions.just_pressed(Action::Slide) && slide.cooldown.finished() {
+            slide.duration.reset();
+            player.is_sliding = true;
+            slide_started.send(SlideStarted);
+        }
+        if player.is_sliding && (!actions.pressed(Action::Slide) || slide.duration.finished()) {
+            player.is_sliding = false;
+            slide.cooldown.reset();
+            if !remote {
+                local.release(Action::Slide);
+            }
+        }
+
+        if player.is_sliding {
+            friction.static_coefficient = 0.;
+            friction.dynamic_coefficient = 0.;
+        } else {
+            let grip = player
+                .ground
+                .and_then(|ground| surfaces.get(ground).ok())
+                .copied()
+                .unwrap_or_default()
+                .grip(weather.wetness());
+            friction.static_coefficient = tuning.friction * grip;
+            friction.dynamic_coefficient = tuning.friction * grip;
+        }
+
+        let delta_v = input * tuning.accel;
+
+        let max_speed = tuning.max_speed;
+
+        if input.dot(**velocity) < 0.0 {
+            // slow down
+            **velocity += delta_v;
+        } else if velocity.x.abs() < max_speed {
+            **velocity += delta_v;
+            velocity.x = velocity.x.clamp(-max_speed, max_speed);
+        }
+    }
+}
+
+fn hook(
+    mut players: Query<(
+        Entity,
+        &Transform,
+        &mut Player,
+        &mut Hooks,
+        &Abilities,
+        &Controls,
+    )>,
+    cast: HookCast,
+    positions: Query<&Position>,
+    settings: Res<GameplaySettings>,
+    time: Res<Time>,
+    mut fired: EventWriter<HookFired>,
+    mut attached: EventWriter<HookAttached>,
+    mut released: EventWriter<HookReleased>,
+    mut links: ResMut<Pool<RopeSegment>>,
+    mut commands: Commands,
+) {
+    for (player, transform, mut state, mut hooks, abilities, controls) in &mut players {
+        if !abilities.has(Ability::Hook) {
+            continue;
+        }
+
+        let actions = &controls.actions;
+        let pos = transform.translation.truncate();
 
-pub(crate) fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let ball_r = 50.;
-    commands.spawn((
+        for hand in Hand::ALL {
+            let action = hand.action();
+            let cooling_down = time.elapsed_seconds() < state.hook_ready_at;
+            let index = hooks.0.iter().position(|hooked| hooked.hand == hand);
+
+            if index.is_none() && !cooling_down && actions.just_pressed(action) {
+                fired.send(HookFired);
+            }
+
+            match (index, actions.pressed(action)) {
+                (None, true) if !cooling_down => {
+                    let Some(dir) = aim_direction(actions, controls.cursor, pos) else {
+                        continue;
+                    };
+                    let Some(target) = cast.target(player, pos, dir) else {
+                        continue;
+                    };
+
+                    // Objects are held by their middle so the rope can't twist them
+                    let (point, end) = match target.object {
+                        Some(object) => (
+                            object,
+                            positions
+                                .get(object)
+                                .map_or(target.point, |object| object.0),
+                        ),
+                        None => (
+                            commands
+                                .spawn((
+                                    RigidBody::Static,
+                                    Position::from_xy(target.point.x, target.point.y),
+                                    StateScoped(InGame),
+                                ))
+                                .id(),
+                            target.point,
+                        ),
+                    };
+
+                    let (rope, segments, links) = if settings.segmented_rope {
+                        rope::spawn_segmented(&mut commands, &mut links, player, pos, point, end)
+                    } else {
+                        let rope = commands
+                            .spawn((
+                                DistanceJoint::new(player, point)
+                                    .with_rest_length(pos.distance(end)),
+                                StateScoped(InGame),
+                            ))
+                            .id();
+                        (rope, Vec::new(), Vec::new())
+                    };
+
+                    hooks.0.push(Hooked {
+                        hand,
+                        point,
+                        rope,
+                        object: target.object.is_some(),
+                        segments,
+                        links,
+                        corners: Vec::new(),
+                    });
+                    attached.send(HookAttached {
+                        player,
+                        position: target.point,
+                    });
+                }
+                (Some(index), held) => {
+                    let hooked = &hooks.0[index];
+                    // The hook point may already be gone if the level was unloaded.
+                    // Segmented ropes are meant to bend around what's in the way.
+                    let blocked = positions.get(hooked.point).map_or(true, |point| {
+                        let anchor = hooked
+                            .corners
+                            .last()
+                            .map_or(point.0, |corner| corner.position);
+                        cast.rules.break_when_blocked
+                            && hooked.segments.is_empty()
+                            && cast.blocked(&[player, hooked.point], pos, anchor)
+                    });
+                    if held && !blocked {
+                        continue;
+                    }
+
+                    hooks.0.remove(index).cut(&mut commands, &mut links);
+                    state.hook_ready_at = time.elapsed_seconds() + cast.rules.cooldown;
+                    released.send(HookReleased);
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Reels in the rope while yank is held and it's tied to an object. Objects
+/// lighter than the player get pulled over to it, heavier ones pull the
+/// player over to them instead.
+fn yank(
+    mut players: Query<(&Position, &Mass, &mut LinearVelocity, &Hooks, &Controls), With<Player>>,
+    mut objects: Query<(&Position, &Mass, &mut LinearVelocity), Without<Player>>,
+    mut ropes: Query<&mut DistanceJoint>,
+    rules: Res<HookRules>,
+    time: Res<Time>,
+) {
+    for (position, mass, mut velocity, hooks, controls) in &mut players {
+        if !controls.actions.pressed(Action::Yank) {
+            continue;
+        }
+
+        for hooked in hooks.0.iter().filter(|hooked| hooked.object) {
+            let Ok((object_position, object_mass, object_velocity)) = objects.get_mut(hooked.point)
+            else {
+                continue;
+            };
+            let Ok(mut rope) = ropes.get_mut(hooked.rope) else {
+                continue;
+            };
+
+            rope.rest_length =
+                (rope.rest_length - rules.yank_speed * time.delta_seconds()).max(YANK_MIN_LENGTH);
+            if position.distance(object_position.0) <= YANK_MIN_LENGTH {
+                continue;
+            }
+
+            let towards_player = (position.0 - object_position.0).normalize_or_zero();
+            let (mut moved, dir) = if object_mass.0 < mass.0 {
+                (object_velocity, towards_player)
+            } else {
+                (velocity.reborrow(), -towards_player)
+            };
+            let speed = moved.dot(dir);
+            if speed < rules.yank_speed {
+                moved.0 += dir * (rules.yank_speed - speed);
+            }
+        }
+    }
+}
+
+/// With the pull hook on, reels the rope in until the player gets to where
+/// it's tied and lets go there, keeping the speed they arrived with.
+fn pull(
+    mut players: Query<(&Position, &mut Player, &mut Hooks)>,
+    points: Query<&Position, Without<Player>>,
+    mut ropes: Query<&mut DistanceJoint>,
+    settings: Res<GameplaySettings>,
+    rules: Res<HookRules>,
+    time: Res<Time>,
+    mut released: EventWriter<HookReleased>,
+    mut links: ResMut<Pool<RopeSegment>>,
+    mut commands: Commands,
+) {
+    if !settings.pull_hook {
+        return;
+    }
+
+    for (position, mut state, mut hooks) in &mut players {
+        hooks.0.retain_mut(|hooked| {
+            // Objects get reeled in with yank instead
+            if hooked.object || !hooked.segments.is_empty() {
+                return true;
+            }
+            let (Ok(point), Ok(mut rope)) = (points.get(hooked.point), ropes.get_mut(hooked.rope))
+            else {
+                return true;
+            };
+
+            rope.rest_length = (rope.rest_length - rules.pull_speed * time.delta_seconds()).max(0.);
+            let anchor = hooked
+                .corners
+                .last()
+                .map_or(point.0, |corner| corner.position);
+            if position.distance(anchor) > PULL_ARRIVAL {
+                return true;
+            }
+
+            // Carry on round to the next corner
+            if !hooked.corners.is_empty() {
+                wrap::unwrap(hooked, &mut rope, point.0, &mut commands);
+                return true;
+            }
+
+            hooked.cut(&mut commands, &mut links);
+            state.hook_ready_at = time.elapsed_seconds() + rules.cooldown;
+            released.send(HookReleased);
+            false
+        });
+    }
+}
+
+/// Where the player is aiming from `origin`, with a stick or the cursor.
+pub(crate) fn aim_direction(actions: &ActionState, cursor: Vec2, origin: Vec2) -> Option<Dir2> {
+    let dir = actions.aim().unwrap_or(cursor - origin);
+    Dir2::new(dir).ok()
+}
+
+/// Everything needed to work out what the hook would catch on.
+#[derive(SystemParam)]
+pub(crate) struct HookCast<'w, 's> {
+    spatial_query: SpatialQuery<'w, 's>,
+    sensors: Query<'w, 's, (), With<Sensor>>,
+    anchors: Query<'w, 's, &'static Transform, With<HookAnchor>>,
+    hookables: Query<'w, 's, (), With<Hookable>>,
+    assist: Res<'w, AimAssist>,
+    settings: Res<'w, AssistSettings>,
+    pub(crate) rules: Res<'w, HookRules>,
+}
+
+impl HookCast<'_, '_> {
+    /// Where the hook would catch on if fired from `origin`, preferring a
+    /// visible anchor close to the aim over whatever the ray hits. With auto
+    /// grapple on it's the nearest visible anchor in range, if there is one.
+    pub(crate) fn target(&self, player: Entity, origin: Vec2, dir: Dir2) -> Option<HookTarget> {
+        let auto = self.settings.auto_grapple();
+        let anchor = self
+            .anchors
+            .iter()
+            .filter_map(|transform| {
+                let point = transform.translation.truncate();
+                let distance = origin.distance(point);
+                if distance > self.rules.range {
+                    return None;
+                }
+                if auto {
+                    return Some((point, distance));
+                }
+                let angle = dir.angle_between(point - origin).abs();
+                (angle <= self.assist.0).then_some((point, angle))
+            })
+            .filter(|&(point, _)| !self.blocked(&[player], origin, point))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((point, _)) = anchor {
+            return Some(HookTarget {
+                point,
+                object: None,
+            });
+        }
+
+        self.ray(&[player], origin, dir, self.rules.range)
+            .map(|hit| HookTarget {
+                point: origin + dir * hit.time_of_impact,
+                object: self.hookables.contains(hit.entity).then_some(hit.entity),
+            })
+    }
+
+    /// Whether geometry other than `ignored` sits between `from` and `to`.
+    /// Points can be right on a wall, so a little in front of them is still
+    /// clear.
+    pub(crate) fn blocked(&self, ignored: &[Entity], from: Vec2, to: Vec2) -> bool {
+        self.obstruction(ignored, from, to).is_some()
+    }
+
+    /// Where and facing which way the first geometry blocking the way from
+    /// `from` to `to` is, going by the same rules as [`Self::blocked`].
+    pub(crate) fn obstruction(
+        &self,
+        ignored: &[Entity],
+        from: Vec2,
+        to: Vec2,
+    ) -> Option<(Vec2, Vec2)> {
+        let distance = from.distance(to) - HOOK_CLEARANCE;
+        match Dir2::new(to - from) {
+            Ok(dir) if distance > 0. => self
+                .ray(ignored, from, dir, distance)
+                .map(|hit| (from + dir * hit.time_of_impact, hit.normal)),
+            _ => None,
+        }
+    }
+
+    fn ray(&self, ignored: &[Entity], origin: Vec2, dir: Dir2, range: f32) -> Option<RayHitData> {
+        let filter = SpatialQueryFilter::default()
+            .with_mask(!LayerMask::from([Layer::Rope, Layer::Player]))
+            .with_excluded_entities(ignored.iter().copied());
+
+        // Pickups and other triggers shouldn't catch the hook, nor should
+        // the other player online
+        self.spatial_query
+            .cast_ray_predicate(origin, dir, range, true, filter, &|entity| {
+                !self.sensors.contains(entity)
+            })
+    }
+}
+
+fn bomb(
+    mut players: Query<(
+        Entity,
+        &Transform,
+        &LinearVelocity,
+        &mut Player,
+        &mut Inventory,
+        &Controls,
+    )>,
+    bombs: Query<(Entity, &Transform, &Bomb, Option<&Stuck>)>,
+    time: Res<Time>,
+    mut exploded: EventWriter<Exploded>,
+    mut commands: Commands,
+) {
+    for (player, transform, velocity, mut state, mut inventory, controls) in &mut players {
+        let actions = &controls.actions;
+
+        let kind = match inventory.selected() {
+            Some(Item::Bomb(kind)) => Some(kind),
+            _ => None,
+        };
+        // Switching away drops a charging grenade
+        if kind != Some(BombKind::Grenade) && state.grenade_charge.is_some() {
+            state.grenade_charge = None;
+        }
+        let Some(kind) = kind else {
+            continue;
+        };
+
+        // Grenades charge up while the button is held and go on release
+        let speed = match kind {
+            BombKind::Grenade if actions.pressed(Action::Bomb) => {
+                let charge = state.grenade_charge.unwrap_or(0.) + time.delta_seconds();
+                state.grenade_charge = Some(charge.min(GRENADE_CHARGE));
+                continue;
+            }
+            BombKind::Grenade => match state.grenade_charge.take() {
+                Some(charge) => grenade_speed(charge),
+                None => continue,
+            },
+            _ if actions.just_pressed(Action::Bomb) => THROW_SPEED,
+            _ => continue,
+        };
+
+        // With remote bombs out the button sets off the player's own instead
+        if kind == BombKind::Remote {
+            let mut remote = bombs
+                .iter()
+                .filter(|(_, _, bomb, _)| {
+                    bomb.kind == BombKind::Remote && bomb.owner == Some(player)
+                })
+                .peekable();
+            if remote.peek().is_some() {
+                for (entity, transform, _, stuck) in remote {
+                    explode_bomb(&mut commands, &mut exploded, entity, transform, stuck);
+                }
+                continue;
+            }
+        }
+
+        let thrown = bombs
+            .iter()
+            .filter(|(_, _, bomb, _)| bomb.owner == Some(player))
+            .count();
+        if thrown >= MAX_BOMBS {
+            continue;
+        }
+
+        let pos = transform.translation.truncate();
+        let dir = actions
+            .aim()
+            .unwrap_or((controls.cursor - pos).normalize_or_zero());
+        let (position, velocity) = throw(pos, velocity.0, dir, speed);
+
+        inventory.use_selected();
+        commands.spawn(make_bomb(kind, Some(player), position, velocity));
+    }
+}
+
+/// Where a bomb thrown from `position` starts, and how fast it goes.
+pub(crate) fn throw(position: Vec2, velocity: Vec2, dir: Vec2, speed: f32) -> (Vec2, Vec2) {
+    // Spawn just outside the player so it doesn't get stuck inside
+    (
+        position + dir * (PLAYER_RADIUS + BOMB_RADIUS + 5.),
+        velocity + dir * speed,
+    )
+}
+
+/// How hard a grenade charged for `charge` seconds is thrown.
+pub(crate) fn grenade_speed(charge: f32) -> f32 {
+    let t = (charge / GRENADE_CHARGE).clamp(0., 1.);
+    GRENADE_MIN_SPEED + (GRENADE_MAX_SPEED - GRENADE_MIN_SPEED) * t
+}
+
+pub(crate) fn make_bomb(
+    kind: BombKind,
+    owner: Option<Entity>,
+    position: Vec2,
+    velocity: Vec2,
+) -> (
+    SpriteBundle,
+    Bomb,
+    RigidBody,
+    Collider,
+    LinearVelocity,
+    Cull,
+    StateScoped<InGame>,
+) {
+    (
         SpriteBundle {
-            texture: asset_server.load("bevy_pixel_dark.png"),
-            transform: Transform::from_xyz(100., 100., 0.),
             sprite: Sprite {
-                custom_size: Some(Vec2::new(ball_r * 2., ball_r * 2.)),
+                color: kind.color(),
+                custom_size: Some(Vec2::splat(BOMB_RADIUS * 2.)),
                 ..default()
             },
+            transform: Transform::from_xyz(position.x, position.y, 0.),
             ..default()
         },
-        Player::default(),
+        Bomb {
+            kind,
+            fuse: Timer::from_seconds(1.5, TimerMode::Once),
+            owner,
+        },
         RigidBody::Dynamic,
-        Collider::circle(ball_r),
-        LockedAxes::ROTATION_LOCKED,
-        Friction::new(0.).with_combine_rule(CoefficientCombine::Multiply),
-        KeepUpright::default(),
-    ));
+        Collider::circle(BOMB_RADIUS),
+        LinearVelocity(velocity),
+        Cull::Despawn,
+        StateScoped(InGame),
+    )
 }
 
-pub(crate) fn is_grounded(
-    mut players: Query<(&Transform, &mut Player)>,
-    mut collisions: EventReader<Collision>,
+fn explode_bomb(
+    commands: &mut Commands,
+    exploded: &mut EventWriter<Exploded>,
+    bomb: Entity,
+    transform: &Transform,
+    stuck: Option<&Stuck>,
 ) {
-    for (_, mut player) in &mut players {
-        player.is_grounded = false;
+    commands.entity(bomb).despawn();
+    if let Some(mut joint) = stuck.and_then(|stuck| commands.get_entity(stuck.0)) {
+        joint.despawn();
     }
+    exploded.send(Exploded {
+        position: transform.translation.truncate(),
+        radius: BOMB_BLAST_RADIUS,
+    });
+}
 
-    for Collision(contacts) in collisions.read() {
-        assert!(contacts.manifolds.len() == 1);
-        let contact = contacts.manifolds.first().unwrap();
+/// Remote bombs wait for the player instead of a fuse.
+fn bomb_fuse(
+    mut bombs: Query<(Entity, &Transform, &mut Bomb, Option<&Stuck>)>,
+    time: Res<Time>,
+    mut exploded: EventWriter<Exploded>,
+    mut commands: Commands,
+) {
+    for (entity, transform, mut bomb, stuck) in &mut bombs {
+        if bomb.kind == BombKind::Remote {
+            continue;
+        }
+        if bomb.fuse.tick(time.delta()).just_finished() {
+            explode_bomb(&mut commands, &mut exploded, entity, transform, stuck);
+        }
+    }
+}
 
-        if let Ok(mut ent) = players.get_mut(contacts.entity1) {
-            let normal = -contact.global_normal1(&Rotation::from(ent.0.rotation));
-            ent.1.is_grounded |= normal.dot(Vec2::Y) > 0.5;
-        } else if let Ok(mut ent) = players.get_mut(contacts.entity2) {
-            let normal = -contact.global_normal2(&Rotation::from(ent.0.rotation));
-            ent.1.is_grounded |= normal.dot(Vec2::Y) > 0.5;
+/// Fixes sticky bombs to the first thing they hit other than the player,
+/// keeping them where they touched it.
+fn stick_bombs(
+    mut started: EventReader<CollisionStarted>,
+    bombs: Query<&Bomb, Without<Stuck>>,
+    bodies: Query<(&Position, &Rotation), (Without<Player>, Without<Sensor>)>,
+    mut commands: Commands,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (bomb, other) = if bombs.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        if !bombs
+            .get(bomb)
+            .is_ok_and(|bomb| bomb.kind == BombKind::Sticky)
+        {
+            continue;
         }
+        let (Ok((bomb_position, _)), Ok((position, rotation))) =
+            (bodies.get(bomb), bodies.get(other))
+        else {
+            continue;
+        };
+
+        let anchor = rotation.inverse() * (bomb_position.0 - position.0);
+        let joint = commands
+            .spawn((
+                FixedJoint::new(bomb, other).with_local_anchor_2(anchor),
+                StateScoped(InGame),
+            ))
+            .id();
+        commands.entity(bomb).insert(Stuck(joint));
+    }
+}
+
+fn keep_upright_impl(ent: &mut Transform, normal: Vec2) {
+    let angle = f32::atan2(normal.y, normal.x);
+    let mut angle = angle + PI / 2.0;
+
+    if angle.abs() < 0.01 {
+        angle = 0.0;
+    }
+
+    let new_angle = Quat::from_rotation_z(angle);
+
+    if ent.rotation.angle_between(new_angle) > 0.005 {
+        ent.rotation = new_angle;
     }
 }
 
-pub(crate) fn movement(
-    mut player: Query<(&mut Transform, &mut Friction, &mut LinearVelocity, &Player)>,
-    keys: Res<ButtonInput<KeyCode>>,
+fn keep_upright(
+    mut entities: Query<
+        (&mut Transform, &GravityDirection),
+        (
+            With<KeepUpright>,
+            Without<death::Ragdoll>,
+            Without<KinematicBody>,
+        ),
+    >,
+    mut collisions: EventReader<Collision>,
 ) {
-    let (_, mut friction, mut velocity, player) = match player.iter_mut().next() {
-        Some(x) => x,
-        None => return,
-    };
+    for Collision(contacts) in collisions.read() {
+        if contacts.is_sensor {
+            continue;
+        }
+
+        for entity in [contacts.entity1, contacts.entity2] {
+            let Ok((mut transform, gravity)) = entities.get_mut(entity) else {
+                continue;
+            };
+            if let Some(normal) = surface_normal(contacts, entity, transform.rotation, gravity.up())
+            {
+                keep_upright_impl(&mut transform, -normal);
+            }
+        }
+    }
+}
+
+/// Whether `entity` is on top of what it's touching rather than against its
+/// side or underneath it, with `up` pointing away from gravity.
+pub(crate) fn stands_on(contacts: &Contacts, entity: Entity, rotation: Quat, up: Vec2) -> bool {
+    surface_normal(contacts, entity, rotation, up).is_some_and(|normal| normal.dot(up) > 0.5)
+}
+
+/// The normal of the surface `entity` is touching, pointing towards `entity`.
+///
+/// Long or compound colliders can touch in several places at once, in that
+/// case the most upward facing normal wins since that's the one to stand on.
+fn surface_normal(contacts: &Contacts, entity: Entity, rotation: Quat, up: Vec2) -> Option<Vec2> {
+    surface_normals(contacts, entity, rotation).max_by(|a, b| a.dot(up).total_cmp(&b.dot(up)))
+}
+
+/// The normals of every place `entity` touches the surface, pointing towards
+/// `entity`.
+pub(crate) fn surface_normals(
+    contacts: &Contacts,
+    entity: Entity,
+    rotation: Quat,
+) -> impl Iterator<Item = Vec2> + '_ {
+    let rotation = Rotation::from(rotation);
+
+    contacts.manifolds.iter().map(move |manifold| {
+        if entity == contacts.entity1 {
+            -manifold.global_normal1(&rotation)
+        } else {
+            -manifold.global_normal2(&rotation)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::GRAVITY;
+
+    /// A long flat floor with a ledge high above the spawn to hook onto.
+    const FLOOR: &str = "(
+        name: \"Floor\",
+        spawn: (0., 100.),
+        medals: (gold: 1., silver: 2., bronze: 3.),
+        cubes: [
+            (x: 0., y: 0., w: 8000., h: 50., hue: 0.),
+            (x: 0., y: 800., w: 400., h: 50., hue: 0.),
+        ],
+    )";
 
-    // Keyboard input
-    let mut input = Vec2::ZERO;
-    if keys.pressed(KeyCode::KeyA) || keys.pressed(KeyCode::ArrowLeft) {
-        input -= Vec2::X;
+    /// A driven pendulum high over the floor, the player starts on its
+    /// platform at the right end of the swing.
+    const PENDULUM: &str = "(
+        name: \"Pendulum\",
+        spawn: (200., 320.),
+        medals: (gold: 1., silver: 2., bronze: 3.),
+        cubes: [
+            (x: 0., y: 0., w: 8000., h: 50., hue: 0.),
+        ],
+        pendulums: [
+            (x: 0., y: 600., length: 400., w: 300., angle: 30., period: Some(4.)),
+        ],
+    )";
+
+    /// The floor level with the player settled on it.
+    fn app() -> App {
+        app_with(GameplaySettings::default())
     }
-    if keys.pressed(KeyCode::KeyD) || keys.pressed(KeyCode::ArrowRight) {
-        input += Vec2::X;
+
+    fn app_with(gameplay: GameplaySettings) -> App {
+        app_in(FLOOR, gameplay)
     }
 
-    // Jump
-    // TODO: Detect ground
-    if keys.just_pressed(KeyCode::Space) && player.is_grounded {
-        **velocity += Vec2::Y * 600.0;
+    fn app_in(level: &str, gameplay: GameplaySettings) -> App {
+        // Leave the real levels on disk so they can't replace this one
+        let mut app = crate::headless_app(AssetPlugin {
+            file_path: "none".into(),
+            ..default()
+        });
+        app.insert_resource(gameplay);
+        let level: Level = ron::from_str(level).unwrap();
+        let handle = app.world_mut().resource_mut::<Assets<Level>>().add(level);
+        let mut levels = app.world_mut().resource_mut::<Levels>();
+        levels.names = vec!["floor.ron".into()];
+        levels.handles = vec![handle];
+        app.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Playing);
+
+        ticks(&mut app, 60);
+        assert!(player(&mut app).is_grounded, "never landed");
+        app
     }
 
-    // Slide
-    // TODO: put on timer
-    if keys.pressed(KeyCode::ShiftLeft) {
-        friction.static_coefficient = 0.;
-        friction.dynamic_coefficient = 0.;
-    } else {
-        friction.static_coefficient = 1.;
-        friction.dynamic_coefficient = 1.;
+    fn ticks(app: &mut App, count: usize) {
+        for _ in 0..count {
+            app.update();
+        }
     }
 
-    let accel = 100.0;
+    fn player(app: &mut App) -> Player {
+        let world = app.world_mut();
+        world.query::<&Player>().single(world).clone()
+    }
 
-    let delta_v = input * accel;
+    fn body(app: &mut App) -> (Vec2, Vec2) {
+        let world = app.world_mut();
+        let (position, velocity) = world
+            .query_filtered::<(&Position, &LinearVelocity), With<Player>>()
+            .single(world);
+        (position.0, velocity.0)
+    }
+
+    fn keys(app: &mut App) -> Mut<'_, ButtonInput<KeyCode>> {
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>()
+    }
 
-    let max_speed = 1000.0;
+    #[test]
+    fn jump_reaches_its_height() {
+        let mut app = app();
+        let start = body(&mut app).0.y;
+
+        keys(&mut app).press(KeyCode::Space);
+        let mut top = start;
+        for _ in 0..120 {
+            app.update();
+            top = top.max(body(&mut app).0.y);
+        }
 
-    if input.dot(**velocity) < 0.0 {
-        // slow down
-        **velocity += delta_v;
-    } else if velocity.x.abs() < max_speed {
-        **velocity += delta_v;
-        velocity.x = velocity.x.clamp(-max_speed, max_speed);
+        let tuning = app.world().resource::<Movement>();
+        let expected = tuning.jump_impulse.powi(2) / (2. * GRAVITY);
+        let height = top - start;
+        assert!(
+            (height - expected).abs() < expected * 0.05,
+            "jumped {height}, expected {expected}"
+        );
     }
-}
 
-pub(crate) fn hook(
-    mut player: Query<(Entity, &Transform), With<Player>>,
-    mouse: Res<ButtonInput<MouseButton>>,
-    coords: Res<MyWorldCoords>,
-    spatial_query: SpatialQuery,
-    mut current: Local<Option<(Entity, Entity)>>,
-    mut commands: Commands,
-) {
-    let (player, transform) = match player.iter_mut().next() {
-        Some(x) => x,
-        None => return,
-    };
+    #[test]
+    fn running_stays_under_max_speed() {
+        let mut app = app();
+        let max_speed = app.world().resource::<Movement>().max_speed;
 
-    match (*current, mouse.pressed(MouseButton::Right)) {
-        (None, true) => {
-            let coords = coords.0;
-            let pos = Vec2::new(transform.translation.x, transform.translation.y);
+        keys(&mut app).press(KeyCode::KeyD);
+        let mut fastest = 0f32;
+        for _ in 0..150 {
+            app.update();
+            fastest = fastest.max(body(&mut app).1.x);
+        }
 
-            let dir = (coords - pos).normalize();
+        assert!(fastest > max_speed * 0.9, "only reached {fastest}");
+        assert!(fastest <= max_speed + 1., "reached {fastest}");
+    }
 
-            let filter = SpatialQueryFilter::default().with_excluded_entities([player]);
+    #[test]
+    fn airborne_until_landing() {
+        let mut app = app();
 
-            if let Some(hit) =
-                spatial_query.cast_ray(pos, Dir2::try_from(dir).unwrap(), 5000.0, true, filter)
-            {
-                let hit_point = pos + (dir * hit.time_of_impact);
+        keys(&mut app).press(KeyCode::Space);
+        ticks(&mut app, 10);
+        assert!(!player(&mut app).is_grounded, "grounded mid-jump");
+
+        // About 1.2 s in the air
+        keys(&mut app).release(KeyCode::Space);
+        ticks(&mut app, 90);
+        assert!(player(&mut app).is_grounded, "didn't land");
+    }
+
+    #[test]
+    fn kinematic_body_jumps_and_lands() {
+        let mut app = app_with(GameplaySettings {
+            kinematic_body: true,
+            ..default()
+        });
+        let start = body(&mut app).0.y;
 
-                let hook = commands
-                    .spawn((
-                        RigidBody::Static,
-                        Position::from_xy(hit_point.x, hit_point.y),
-                    ))
-                    .id();
+        keys(&mut app).press(KeyCode::Space);
+        ticks(&mut app, 10);
+        assert!(!player(&mut app).is_grounded, "grounded mid-jump");
+        assert!(body(&mut app).0.y > start, "didn't leave the ground");
+
+        keys(&mut app).release(KeyCode::Space);
+        ticks(&mut app, 90);
+        assert!(player(&mut app).is_grounded, "didn't land");
+        let landed = body(&mut app).0.y;
+        assert!(
+            (landed - start).abs() < 2.,
+            "landed at {landed}, from {start}"
+        );
+    }
 
-                let rope = commands
-                    .spawn(DistanceJoint::new(player, hook).with_rest_length(hit.time_of_impact))
-                    .id();
+    #[test]
+    fn jumping_off_a_pendulum_keeps_its_swing() {
+        let mut app = app_in(PENDULUM, default());
+        let platform = |app: &mut App| {
+            let world = app.world_mut();
+            let (position, velocity) = world
+                .query_filtered::<(&Position, &LinearVelocity), With<Hookable>>()
+                .single(world);
+            (position.0, velocity.0)
+        };
 
-                *current = Some((hook, rope));
+        // Through the bottom of the swing, where it goes fastest
+        for _ in 0..300 {
+            app.update();
+            if platform(&mut app).0.x < 0. {
+                break;
             }
         }
-        (Some((hook, rope)), false) => {
-            // despawn
-            commands.entity(rope).despawn();
-            commands.entity(hook).despawn();
-            *current = None;
-        }
-        _ => (),
+        let swing = platform(&mut app).1.x;
+        assert!(swing < -200., "only swinging at {swing}");
+        assert!(player(&mut app).is_grounded, "fell off the platform");
+
+        // Friction alone leaves the player trailing it a little
+        keys(&mut app).press(KeyCode::Space);
+        app.update();
+        let (_, velocity) = body(&mut app);
+        assert!(velocity.y > 0., "didn't jump");
+        assert!(
+            velocity.x < swing + 1.,
+            "left at {}, the platform swings at {swing}",
+            velocity.x
+        );
     }
-}
 
-// pub(crate) fn bomb(
-//     mut player: Query<(Entity, &Transform), With<Player>>,
-//     mut bombs: Query<With<Bomb>>,
-//     mouse: Res<Input<MouseButton>>,
-//     coords: Res<MyWorldCoords>,
-//     mut commands: Commands,
-// ) {
-// }
+    #[test]
+    fn releasing_the_hook_removes_the_joint() {
+        let mut app = app();
+        let joints = |app: &mut App| {
+            let world = app.world_mut();
+            world.query::<&DistanceJoint>().iter(world).count()
+        };
+
+        let above = body(&mut app).0 + Vec2::Y * 500.;
+        app.world_mut().resource_mut::<MyWorldCoords>().0 = above;
+        let mut mouse = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+        mouse.press(MouseButton::Right);
+        ticks(&mut app, 5);
+
+        let world = app.world_mut();
+        let hooks = world.query::<&Hooks>().single(world);
+        let (rope, point) = (hooks.0[0].rope, hooks.0[0].point);
+        assert_eq!(joints(&mut app), 1);
+
+        let mut mouse = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+        mouse.release(MouseButton::Right);
+        ticks(&mut app, 2);
+
+        let world = app.world_mut();
+        assert!(world.query::<&Hooks>().single(world).0.is_empty());
+        assert!(world.get_entity(rope).is_none());
+        assert!(world.get_entity(point).is_none());
+        assert_eq!(joints(&mut app), 0);
+    }
+}
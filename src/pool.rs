@@ -0,0 +1,64 @@
+use std::marker::PhantomData;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::cull::Cull;
+
+/// Entities of one kind put aside to be used again instead of despawning
+/// them. Spawning a body goes through a whole series of archetypes while
+/// physics sets it up, taking one out of the pool only overwrites values.
+///
+/// Parking takes away `T` and [`Cull`] so systems stop seeing the entity,
+/// hides it and stops it colliding.
+#[derive(Resource)]
+pub(crate) struct Pool<T> {
+    free: Vec<Entity>,
+    kind: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Self {
+            free: Vec::new(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> Pool<T> {
+    /// Reuse a parked entity for `bundle` or spawn a new one. The bundle
+    /// needs to set everything parking changed back.
+    pub(crate) fn spawn(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        // Parked entities still go when the level does
+        while let Some(entity) = self.free.pop() {
+            if let Some(mut parked) = commands.get_entity(entity) {
+                parked.try_insert(bundle);
+                return entity;
+            }
+        }
+        commands.spawn(bundle).id()
+    }
+
+    pub(crate) fn park(&mut self, commands: &mut Commands, entity: Entity) {
+        // Something can be done with in more than one way in the same tick
+        if self.free.contains(&entity) {
+            return;
+        }
+
+        let Some(mut parked) = commands.get_entity(entity) else {
+            return;
+        };
+        parked.remove::<(T, Cull)>().try_insert((
+            Visibility::Hidden,
+            RigidBody::Static,
+            LinearVelocity::ZERO,
+            CollisionLayers::NONE,
+        ));
+        self.free.push(entity);
+    }
+
+    pub(crate) fn clear(mut pool: ResMut<Self>) {
+        pool.free.clear();
+    }
+}
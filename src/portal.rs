@@ -0,0 +1,152 @@
+use std::f32::consts::PI;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    interpolation::Interpolated,
+    kinematic::KinematicBody,
+    level,
+    player::{HookReleased, Hooks, Player},
+    pool::Pool,
+    rope::RopeSegment,
+    state::InGame,
+};
+
+const SIZE: Vec2 = Vec2::new(20., 140.);
+/// How far in front of the exit bodies come out, clear of its sensor
+const EXIT_DISTANCE: f32 = 70.;
+/// Seconds a body can't use a portal after going through one
+const COOLDOWN: f32 = 0.5;
+
+/// One end of a linked pair. Bodies come out of the other end facing away
+/// from it, keeping their speed and their direction relative to the portals.
+#[derive(Component)]
+pub(crate) struct Portal {
+    exit: Entity,
+    /// Which way it faces, in radians counter clockwise from +X
+    angle: f32,
+    /// Takes any dynamic body instead of just the player
+    bodies: bool,
+}
+
+/// On a body that just went through a portal, so it doesn't get bounced
+/// straight back.
+#[derive(Component)]
+pub(crate) struct PortalCooldown(Timer);
+
+/// Spawn both ends of a pair placed in a level.
+pub(crate) fn spawn(commands: &mut Commands, placed: &level::PortalPair) {
+    let a = commands.spawn_empty().id();
+    let b = commands.spawn_empty().id();
+    commands
+        .entity(a)
+        .insert(make_portal(&placed.a, b, placed.bodies, 280.));
+    commands
+        .entity(b)
+        .insert(make_portal(&placed.b, a, placed.bodies, 30.));
+}
+
+fn make_portal(
+    placed: &level::Portal,
+    exit: Entity,
+    bodies: bool,
+    hue: f32,
+) -> (SpriteBundle, Portal, Collider, Sensor, StateScoped<InGame>) {
+    let angle = placed.angle.to_radians();
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(hue, 0.9, 0.55),
+                custom_size: Some(SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, -0.5)
+                .with_rotation(Quat::from_rotation_z(angle)),
+            ..default()
+        },
+        Portal {
+            exit,
+            angle,
+            bodies,
+        },
+        Collider::rectangle(SIZE.x, SIZE.y),
+        Sensor,
+        StateScoped(InGame),
+    )
+}
+
+pub(crate) fn enter(
+    mut started: EventReader<CollisionStarted>,
+    portals: Query<(&Portal, &Transform)>,
+    mut bodies: Query<
+        (
+            &RigidBody,
+            &mut Position,
+            &mut LinearVelocity,
+            Has<Player>,
+            Option<&mut Hooks>,
+            Option<&mut Interpolated>,
+            Has<KinematicBody>,
+        ),
+        Without<PortalCooldown>,
+    >,
+    mut released: EventWriter<HookReleased>,
+    mut links: ResMut<Pool<RopeSegment>>,
+    mut commands: Commands,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (portal, other) = if portals.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok((entry, _)) = portals.get(portal) else {
+            continue;
+        };
+        let Ok((exit, exit_transform)) = portals.get(entry.exit) else {
+            continue;
+        };
+        let Ok((body, mut position, mut velocity, is_player, hooks, interpolated, kinematic)) =
+            bodies.get_mut(other)
+        else {
+            continue;
+        };
+        if !(body.is_dynamic() || kinematic) || !(is_player || entry.bodies) {
+            continue;
+        }
+
+        // Going in against the entry comes out along the exit's facing
+        let turn = Vec2::from_angle(exit.angle - entry.angle + PI);
+        let facing = Vec2::from_angle(exit.angle);
+        position.0 = exit_transform.translation.truncate() + facing * EXIT_DISTANCE;
+        velocity.0 = turn.rotate(velocity.0);
+
+        if let Some(mut interpolated) = interpolated {
+            interpolated.snap();
+        }
+        // The rope would drag it straight back
+        if let Some(mut hooks) = hooks {
+            hooks.cut_all(&mut commands, &mut links, &mut released);
+        }
+        commands
+            .entity(other)
+            .insert(PortalCooldown(Timer::from_seconds(
+                COOLDOWN,
+                TimerMode::Once,
+            )));
+    }
+}
+
+pub(crate) fn cool_down(
+    mut bodies: Query<(Entity, &mut PortalCooldown)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut cooldown) in &mut bodies {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<PortalCooldown>();
+        }
+    }
+}
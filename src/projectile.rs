@@ -0,0 +1,145 @@
+use avian2d::prelude::*;
+use bevy::{ecs::entity::EntityHashSet, prelude::*};
+
+use crate::{
+    cull::Cull,
+    health::Damage,
+    pool::Pool,
+    state::{GameplaySet, InGame},
+};
+
+const RADIUS: f32 = 8.;
+/// Seconds before a projectile that hit nothing disappears
+const LIFETIME: f32 = 5.;
+
+/// Anything fired that flies straight and breaks on the first thing it hits.
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ProjectileHit>()
+            .init_resource::<Pool<Projectile>>()
+            .add_systems(
+                FixedUpdate,
+                (expire, hit, damage).chain().in_set(GameplaySet),
+            )
+            .add_systems(OnExit(InGame), Pool::<Projectile>::clear);
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct Projectile {
+    /// Whoever fired it, which it passes through
+    owner: Entity,
+    /// Health taken from whatever it hits
+    damage: u32,
+    lifetime: Timer,
+}
+
+/// Sent when a projectile runs into something, right before it's parked.
+#[derive(Event)]
+pub(crate) struct ProjectileHit {
+    pub(crate) target: Entity,
+    pub(crate) position: Vec2,
+    pub(crate) damage: u32,
+}
+
+/// Spawn with [`Pool<Projectile>::spawn`] so spent projectiles get reused.
+pub(crate) fn make_projectile(
+    owner: Entity,
+    position: Vec2,
+    velocity: Vec2,
+    damage: u32,
+) -> (
+    SpriteBundle,
+    Projectile,
+    RigidBody,
+    Collider,
+    Sensor,
+    GravityScale,
+    LinearVelocity,
+    CollisionLayers,
+    Cull,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(20., 1.0, 0.6),
+                custom_size: Some(Vec2::splat(RADIUS * 2.)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.)),
+            ..default()
+        },
+        Projectile {
+            owner,
+            damage,
+            lifetime: Timer::from_seconds(LIFETIME, TimerMode::Once),
+        },
+        // Dynamic so it also reports contacts with static geometry
+        RigidBody::Dynamic,
+        Collider::circle(RADIUS),
+        Sensor,
+        GravityScale(0.),
+        LinearVelocity(velocity),
+        CollisionLayers::default(),
+        Cull::Recycle,
+        StateScoped(InGame),
+    )
+}
+
+fn expire(
+    mut projectiles: Query<(Entity, &mut Projectile)>,
+    time: Res<Time>,
+    mut pool: ResMut<Pool<Projectile>>,
+    mut commands: Commands,
+) {
+    for (entity, mut projectile) in &mut projectiles {
+        if projectile.lifetime.tick(time.delta()).finished() {
+            pool.park(&mut commands, entity);
+        }
+    }
+}
+
+fn hit(
+    mut started: EventReader<CollisionStarted>,
+    projectiles: Query<(&Projectile, &Position)>,
+    sensors: Query<(), With<Sensor>>,
+    mut hits: EventWriter<ProjectileHit>,
+    mut pool: ResMut<Pool<Projectile>>,
+    mut commands: Commands,
+) {
+    // A projectile can touch several things in one tick, only the first counts
+    let mut spent = EntityHashSet::default();
+
+    for CollisionStarted(a, b) in started.read() {
+        for (projectile, target) in [(*a, *b), (*b, *a)] {
+            let Ok((data, position)) = projectiles.get(projectile) else {
+                continue;
+            };
+            // Pickups, triggers and other projectiles don't stop it
+            if target == data.owner || sensors.contains(target) || spent.contains(&projectile) {
+                continue;
+            }
+
+            spent.insert(projectile);
+            hits.send(ProjectileHit {
+                target,
+                position: position.0,
+                damage: data.damage,
+            });
+            pool.park(&mut commands, projectile);
+        }
+    }
+}
+
+fn damage(mut hits: EventReader<ProjectileHit>, mut damage: EventWriter<Damage>) {
+    for hit in hits.read() {
+        damage.send(Damage {
+            target: hit.target,
+            amount: hit.damage,
+            source: hit.position,
+        });
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    atlas::{Animated, AtlasSprite},
+    level, lighting,
+    state::InGame,
+};
+
+const TORCH_LIGHT_RADIUS: f32 = 350.;
+const TORCH_LIGHT_INTENSITY: f32 = 1.5;
+const TORCH_LIGHT_COLOR: Color = Color::srgb(1., 0.6, 0.25);
+
+/// Decoration that moves but doesn't do anything, placed in the level data.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub(crate) enum PropKind {
+    /// Flickers and lights up what's around it
+    Torch,
+    /// The top of a pool, drawn over what's below it
+    Water,
+    Flag,
+}
+
+impl PropKind {
+    /// Name of the animation in the atlas.
+    fn animation(self) -> &'static str {
+        match self {
+            PropKind::Torch => "torch",
+            PropKind::Water => "water",
+            PropKind::Flag => "flag",
+        }
+    }
+
+    /// Size when the level doesn't give one.
+    fn size(self) -> Vec2 {
+        match self {
+            PropKind::Torch => Vec2::new(32., 64.),
+            PropKind::Water => Vec2::new(200., 40.),
+            PropKind::Flag => Vec2::new(64., 48.),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            PropKind::Torch => Color::WHITE,
+            PropKind::Water => Color::srgba(0.3, 0.55, 0.9, 0.7),
+            PropKind::Flag => Color::hsl(0., 0.8, 0.5),
+        }
+    }
+
+    /// Behind the level, apart from water
+    fn z(self) -> f32 {
+        match self {
+            PropKind::Water => 0.5,
+            PropKind::Torch | PropKind::Flag => -0.5,
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct Prop;
+
+pub(crate) fn spawn(commands: &mut Commands, placed: &level::Prop) {
+    let kind = placed.kind;
+    let size = placed.size.unwrap_or(kind.size());
+    let mut prop = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, kind.z()),
+            ..default()
+        },
+        AtlasSprite(kind.animation()),
+        Animated::default(),
+        Prop,
+        StateScoped(InGame),
+    ));
+
+    if kind == PropKind::Torch {
+        prop.with_children(|parent| {
+            // From the flame rather than the middle of the handle
+            parent.spawn(lighting::make_light(
+                0.,
+                size.y / 4.,
+                TORCH_LIGHT_RADIUS,
+                TORCH_LIGHT_INTENSITY,
+                TORCH_LIGHT_COLOR,
+            ));
+        });
+    }
+}
@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::MyWorldCoords,
+    ghost::{self, GhostTrack},
+    input::{ActionFrame, ActionState},
+    level::Levels,
+    run::Run,
+    save,
+    state::{AppState, GameplaySet, InGame},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const REPLAY_PATH: &str = "replay.ron";
+
+/// Records the inputs of every attempt at a level and plays them back
+/// through [`ActionState`] to recreate it, plus the ghost of the best run.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Replay>()
+            .add_systems(Startup, load)
+            .add_systems(OnEnter(InGame), start)
+            .add_systems(FixedUpdate, record.in_set(GameplaySet))
+            .add_systems(
+                FixedPreUpdate,
+                play_back.run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnEnter(AppState::Dead), finish)
+            .add_systems(OnEnter(AppState::LevelComplete), finish)
+            .add_systems(OnEnter(AppState::Menu), stop)
+            .add_systems(OnEnter(AppState::LevelSelect), stop)
+            .add_systems(Update, banner.run_if(in_state(InGame)))
+            .init_resource::<GhostTrack>()
+            .add_systems(OnEnter(InGame), ghost::spawn)
+            .add_systems(FixedUpdate, ghost::record.in_set(GameplaySet))
+            .add_systems(Update, ghost::follow.run_if(in_state(InGame)))
+            .add_systems(
+                OnEnter(AppState::LevelComplete),
+                ghost::save.after(save::record_completion),
+            );
+    }
+}
+
+/// One fixed tick of input.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Frame {
+    actions: ActionFrame,
+    /// The hook and bombs aim at the cursor without a stick
+    cursor: Vec2,
+}
+
+/// The inputs of one attempt at a level, from the moment it was entered.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Recording {
+    /// File name of the level
+    level: String,
+    /// Checkpoint the attempt started from, `None` is the level's spawn
+    start: Option<Vec2>,
+    /// Run length encoded, most ticks repeat the one before
+    frames: Vec<(u32, Frame)>,
+}
+
+impl Recording {
+    fn push(&mut self, frame: Frame) {
+        match self.frames.last_mut() {
+            Some((count, last)) if *last == frame => *count += 1,
+            _ => self.frames.push((1, frame)),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read() -> Option<Self> {
+        let text = std::fs::read_to_string(REPLAY_PATH).ok()?;
+        match ron::from_str(&text) {
+            Ok(recording) => Some(recording),
+            Err(err) => {
+                warn!("Ignoring unreadable replay: {err}");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write(&self) {
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|text| std::fs::write(REPLAY_PATH, text).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            error!("Could not write replay: {err}");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read() -> Option<Self> {
+        None
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn write(&self) {}
+}
+
+#[derive(Default)]
+enum Mode {
+    #[default]
+    Off,
+    Recording(Recording),
+    /// Waiting for the level to load before playing back
+    Queued(Recording),
+    Playing {
+        recording: Recording,
+        /// Position in the run length encoded frames
+        index: usize,
+        repeat: u32,
+    },
+}
+
+#[derive(Resource, Default)]
+pub(crate) struct Replay {
+    mode: Mode,
+    /// The last finished attempt, available to watch. Starts out as the one
+    /// saved by an earlier session, or shared by someone else.
+    pub(crate) last: Option<Recording>,
+}
+
+impl Replay {
+    /// Restart the level a recording was made in and play it back.
+    pub(crate) fn watch(
+        &mut self,
+        recording: Recording,
+        levels: &mut Levels,
+        run: &mut Run,
+        next: &mut NextState<AppState>,
+    ) {
+        let Some(index) = levels
+            .names
+            .iter()
+            .position(|name| *name == recording.level)
+        else {
+            warn!("Replay is for unknown level {}", recording.level);
+            return;
+        };
+
+        levels.current = index;
+        *run = Run::default();
+        run.checkpoint = recording.start;
+        self.mode = Mode::Queued(recording);
+        next.set(AppState::Restarting);
+    }
+
+    pub(crate) fn is_playing(&self) -> bool {
+        matches!(self.mode, Mode::Playing { .. })
+    }
+}
+
+fn load(mut replay: ResMut<Replay>) {
+    replay.last = Recording::read();
+}
+
+fn start(mut replay: ResMut<Replay>, levels: Res<Levels>, run: Res<Run>) {
+    replay.mode = match std::mem::take(&mut replay.mode) {
+        Mode::Queued(recording) => Mode::Playing {
+            recording,
+            index: 0,
+            repeat: 0,
+        },
+        _ => Mode::Recording(Recording {
+            level: levels
+                .names
+                .get(levels.current)
+                .cloned()
+                .unwrap_or_default(),
+            start: run.checkpoint,
+            frames: Vec::new(),
+        }),
+    };
+}
+
+fn record(mut replay: ResMut<Replay>, actions: Res<ActionState>, coords: Res<MyWorldCoords>) {
+    if let Mode::Recording(recording) = &mut replay.mode {
+        recording.push(Frame {
+            actions: actions.frame(),
+            cursor: coords.0,
+        });
+    }
+}
+
+/// Overwrite the live input with the recording before gameplay sees it.
+fn play_back(
+    mut replay: ResMut<Replay>,
+    mut actions: ResMut<ActionState>,
+    mut coords: ResMut<MyWorldCoords>,
+) {
+    let Mode::Playing {
+        recording,
+        index,
+        repeat,
+    } = &mut replay.mode
+    else {
+        return;
+    };
+
+    let Some((count, frame)) = recording.frames.get(*index) else {
+        // Let go of everything once the recording runs out
+        actions.play(&ActionFrame::default());
+        return;
+    };
+
+    actions.play(&frame.actions);
+    coords.0 = frame.cursor;
+
+    *repeat += 1;
+    if *repeat >= *count {
+        *index += 1;
+        *repeat = 0;
+    }
+}
+
+/// Keep a finished attempt around and on disk, playback just ends.
+fn finish(mut replay: ResMut<Replay>) {
+    if let Mode::Recording(recording) = std::mem::take(&mut replay.mode) {
+        recording.write();
+        replay.last = Some(recording);
+    }
+}
+
+fn stop(mut replay: ResMut<Replay>) {
+    replay.mode = Mode::Off;
+}
+
+fn banner(mut contexts: EguiContexts, replay: Res<Replay>) {
+    if !replay.is_playing() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("replay"))
+        .anchor(egui::Align2::CENTER_TOP, [0., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(egui::RichText::new("REPLAY").strong().size(24.));
+        });
+}
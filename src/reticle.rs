@@ -0,0 +1,132 @@
+use avian2d::prelude::*;
+use bevy::{color::palettes::css, prelude::*};
+
+use crate::{
+    camera::MyWorldCoords,
+    input::ActionState,
+    player::{self, HookCast, Player, Remote},
+    world::FIXED_HZ,
+};
+
+/// Length of each dash along the preview ray, and of the gaps between them
+const DASH: f32 = 25.;
+/// Half the width of the crosshair
+const CROSSHAIR: f32 = 15.;
+/// How far ahead of the player the crosshair sits when aiming with a stick
+const STICK_DISTANCE: f32 = 300.;
+/// Ticks ahead the grenade arc is predicted for
+const ARC_TICKS: usize = 128;
+
+/// Draw a crosshair where the hook is aimed and a dashed ray showing whether
+/// it would catch: green up to the hit, red when it misses or is out of range.
+pub(crate) fn draw(
+    players: Query<(Entity, &Transform), (With<Player>, Without<Remote>)>,
+    joints: Query<&DistanceJoint>,
+    actions: Res<ActionState>,
+    coords: Res<MyWorldCoords>,
+    cast: HookCast,
+    mut gizmos: Gizmos,
+) {
+    let (player, transform) = match players.iter().next() {
+        Some(x) => x,
+        None => return,
+    };
+
+    // Only while aiming, not while swinging
+    if joints.iter().any(|joint| joint.entity1 == player) {
+        return;
+    }
+
+    let pos = transform.translation.truncate();
+    let Some(dir) = player::aim_direction(&actions, coords.0, pos) else {
+        return;
+    };
+
+    let target = match actions.aim() {
+        Some(_) => pos + dir * STICK_DISTANCE,
+        None => coords.0,
+    };
+
+    let (end, color) = match cast.target(player, pos, dir) {
+        Some(target) => (target.point, css::LIME),
+        None => (
+            pos + dir * cast.rules.range.min(pos.distance(target)),
+            css::RED,
+        ),
+    };
+
+    // Snapping to an anchor can bend the ray away from the aim
+    let Some(ray) = Dir2::new(end - pos).ok() else {
+        return;
+    };
+    let length = pos.distance(end);
+    let mut start = 0.;
+    while start < length {
+        let stop = (start + DASH).min(length);
+        gizmos.line_2d(pos + ray * start, pos + ray * stop, color);
+        start += DASH * 2.;
+    }
+
+    gizmos.line_2d(
+        target - Vec2::X * CROSSHAIR,
+        target + Vec2::X * CROSSHAIR,
+        color,
+    );
+    gizmos.line_2d(
+        target - Vec2::Y * CROSSHAIR,
+        target + Vec2::Y * CROSSHAIR,
+        color,
+    );
+}
+
+/// Draw where a grenade would fly if thrown now, stepping it the same way
+/// physics will until it hits something.
+pub(crate) fn draw_arc(
+    players: Query<(Entity, &Transform, &LinearVelocity, &Player), Without<Remote>>,
+    sensors: Query<(), With<Sensor>>,
+    actions: Res<ActionState>,
+    coords: Res<MyWorldCoords>,
+    gravity: Res<Gravity>,
+    spatial_query: SpatialQuery,
+    mut gizmos: Gizmos,
+) {
+    let Some((player, transform, velocity, state)) = players.iter().next() else {
+        return;
+    };
+    let Some(charge) = state.grenade_charge else {
+        return;
+    };
+
+    let pos = transform.translation.truncate();
+    let dir = actions
+        .aim()
+        .unwrap_or((coords.0 - pos).normalize_or_zero());
+    let (mut position, mut velocity) =
+        player::throw(pos, velocity.0, dir, player::grenade_speed(charge));
+
+    let dt = 1. / FIXED_HZ as f32;
+    let filter = SpatialQueryFilter::default().with_excluded_entities([player]);
+    for _ in 0..ARC_TICKS {
+        velocity += gravity.0 * dt;
+        let next = position + velocity * dt;
+        let Ok(ray) = Dir2::new(next - position) else {
+            break;
+        };
+
+        let hit = spatial_query.cast_ray_predicate(
+            position,
+            ray,
+            position.distance(next),
+            true,
+            filter.clone(),
+            &|entity| !sensors.contains(entity),
+        );
+        if let Some(hit) = hit {
+            gizmos.line_2d(position, position + ray * hit.time_of_impact, css::ORANGE);
+            break;
+        }
+
+        gizmos.line_2d(position, next, css::ORANGE);
+        position = next;
+    }
+}
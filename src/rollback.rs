@@ -0,0 +1,479 @@
+use std::{
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use avian2d::{position::PreviousRotation, prelude::*, sync::PreviousGlobalTransform};
+use bevy::{app::FixedMain, prelude::*};
+
+use crate::{
+    camera::MyWorldCoords,
+    gravity::GravityDirection,
+    health::{Health, Hitstun},
+    input::{ActionFrame, ActionState},
+    inventory::Inventory,
+    player::{Bomb, Hand, Hooked, Hooks, Player, Slide},
+    replay::Replay,
+    run::Run,
+    state::{AppState, InGame},
+    weather::WeatherBlend,
+};
+
+/// Checks that the simulation can be rolled back, the groundwork for rollback
+/// netplay. Every frame it rewinds to the [`Snapshot`] from `distance` ticks
+/// ago, simulates those ticks again with the same inputs and reports when the
+/// result differs from what happened the first time.
+///
+/// Meant to be run together with [`DeterministicPlugin`](crate::DeterministicPlugin).
+/// Sounds and effects of the simulated ticks play again.
+pub struct SyncTestPlugin {
+    pub distance: usize,
+}
+
+impl Plugin for SyncTestPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(History {
+            distance: self.distance.max(1),
+            ..default()
+        })
+        .init_resource::<SyncReport>()
+        .add_systems(FixedFirst, save.run_if(in_state(AppState::Playing)))
+        .add_systems(
+            FixedPostUpdate,
+            save_input.run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(OnExit(AppState::Playing), clear)
+        .add_systems(Last, check.run_if(in_state(AppState::Playing)));
+    }
+}
+
+/// State of one moving body.
+#[derive(Clone)]
+struct Body {
+    entity: Entity,
+    position: Position,
+    rotation: Rotation,
+    linear: LinearVelocity,
+    angular: AngularVelocity,
+    /// Physics moves bodies that look like they were moved by hand, these
+    /// have to agree with the position
+    transform: Transform,
+    global_transform: GlobalTransform,
+    previous_transform: Option<PreviousGlobalTransform>,
+    previous_rotation: Option<PreviousRotation>,
+    accumulated: Option<AccumulatedTranslation>,
+    player: Option<Player>,
+    slide: Option<Slide>,
+    health: Option<Health>,
+    hitstun: Option<Hitstun>,
+    bomb: Option<Bomb>,
+    inventory: Option<Inventory>,
+    gravity: Option<GravityDirection>,
+    gravity_scale: Option<GravityScale>,
+    /// Where each hook caught on and the rope to it
+    hooks: Vec<HookSnapshot>,
+}
+
+/// Which hook, where it caught on, the rope to it and whether it's tied to
+/// an object.
+type HookSnapshot = (Hand, Vec2, DistanceJoint, bool);
+
+/// Everything gameplay changes from one tick to the next, so it can be put
+/// back to simulate from that tick again.
+///
+/// Only covers moving bodies and the player, the scripted timers of enemies,
+/// spawners and bosses aren't rollback safe yet. Bodies despawned since the
+/// snapshot can't be brought back.
+pub(crate) struct Snapshot {
+    time: Time<Fixed>,
+    physics_time: Time<Physics>,
+    collisions: Collisions,
+    /// Rain changes how the player grips the ground
+    weather: Option<WeatherBlend>,
+    /// The clock and checkpoint of the attempt
+    run: Option<Run>,
+    /// Sorted by entity
+    bodies: Vec<Body>,
+}
+
+impl Snapshot {
+    pub(crate) fn take(world: &mut World) -> Self {
+        let mut entities = world
+            .query::<(Entity, &RigidBody)>()
+            .iter(world)
+            .filter(|(_, body)| !body.is_static())
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        entities.sort();
+
+        let bodies = entities
+            .into_iter()
+            .filter_map(|entity| {
+                let entity = world.get_entity(entity)?;
+                Some(Body {
+                    entity: entity.id(),
+                    position: *entity.get::<Position>()?,
+                    rotation: *entity.get::<Rotation>()?,
+                    linear: *entity.get::<LinearVelocity>()?,
+                    angular: *entity.get::<AngularVelocity>()?,
+                    transform: *entity.get::<Transform>()?,
+                    global_transform: *entity.get::<GlobalTransform>()?,
+                    previous_transform: entity.get::<PreviousGlobalTransform>().copied(),
+                    previous_rotation: entity.get::<PreviousRotation>().copied(),
+                    accumulated: entity.get::<AccumulatedTranslation>().copied(),
+                    player: entity.get::<Player>().cloned(),
+                    slide: entity.get::<Slide>().cloned(),
+                    health: entity.get::<Health>().cloned(),
+                    hitstun: entity.get::<Hitstun>().cloned(),
+                    bomb: entity.get::<Bomb>().cloned(),
+                    inventory: entity.get::<Inventory>().cloned(),
+                    gravity: entity.get::<GravityDirection>().copied(),
+                    gravity_scale: entity.get::<GravityScale>().copied(),
+                    hooks: entity.get::<Hooks>().map_or_else(Vec::new, |hooks| {
+                        hooks
+                            .0
+                            .iter()
+                            .filter_map(|hooked| {
+                                let point = world.get::<Position>(hooked.point)?;
+                                let mut rope = *world.get::<DistanceJoint>(hooked.rope)?;
+                                // Comes back unwrapped, with the rope the corners took up
+                                rope.entity2 = hooked.point;
+                                rope.rest_length += hooked.wrapped_length(point.0);
+                                Some((hooked.hand, point.0, rope, hooked.object))
+                            })
+                            .collect()
+                    }),
+                })
+            })
+            .collect();
+
+        Self {
+            time: *world.resource::<Time<Fixed>>(),
+            physics_time: *world.resource::<Time<Physics>>(),
+            collisions: world.resource::<Collisions>().clone(),
+            weather: world.get_resource::<WeatherBlend>().cloned(),
+            run: world.get_resource::<Run>().cloned(),
+            bodies,
+        }
+    }
+
+    pub(crate) fn restore(&self, world: &mut World) {
+        *world.resource_mut::<Time<Fixed>>() = self.time;
+        *world.resource_mut::<Time<Physics>>() = self.physics_time;
+        *world.resource_mut::<Collisions>() = self.collisions.clone();
+        if let Some(weather) = &self.weather {
+            world.insert_resource(weather.clone());
+        }
+        if let Some(run) = &self.run {
+            world.insert_resource(run.clone());
+        }
+
+        // Anything that started moving since gets simulated into existence again
+        let spawned = world
+            .query::<(Entity, &RigidBody)>()
+            .iter(world)
+            .filter(|(entity, body)| {
+                !body.is_static()
+                    && self
+                        .bodies
+                        .binary_search_by_key(entity, |body| body.entity)
+                        .is_err()
+            })
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in spawned {
+            world.entity_mut(entity).despawn_recursive();
+        }
+
+        for body in &self.bodies {
+            if world.get_entity(body.entity).is_none() {
+                continue;
+            }
+
+            world.entity_mut(body.entity).insert((
+                body.position,
+                body.rotation,
+                body.linear,
+                body.angular,
+                body.transform,
+                body.global_transform,
+            ));
+            restore(world, body.entity, &body.previous_transform);
+            restore(world, body.entity, &body.previous_rotation);
+            restore(world, body.entity, &body.accumulated);
+            restore(world, body.entity, &body.player);
+            restore(world, body.entity, &body.slide);
+            restore(world, body.entity, &body.health);
+            restore(world, body.entity, &body.hitstun);
+            restore(world, body.entity, &body.bomb);
+            restore(world, body.entity, &body.inventory);
+            restore(world, body.entity, &body.gravity);
+            restore(world, body.entity, &body.gravity_scale);
+            restore_hooks(world, body.entity, &body.hooks);
+        }
+    }
+
+    /// Sums up where the bodies are, to compare with another machine's
+    /// snapshot of the same tick. Entities are numbered differently there so
+    /// only the positions count, in the same order regardless.
+    pub(crate) fn checksum(&self) -> u64 {
+        let mut positions = self
+            .bodies
+            .iter()
+            .map(|body| (body.position.x.to_bits(), body.position.y.to_bits()))
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+        positions.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The first body that ended up somewhere else than in `other`.
+    fn mismatch(&self, other: &Snapshot) -> Option<Entity> {
+        if self.bodies.len() != other.bodies.len() {
+            return Some(Entity::PLACEHOLDER);
+        }
+
+        self.bodies
+            .iter()
+            .zip(&other.bodies)
+            .find(|(a, b)| {
+                a.entity != b.entity
+                    || a.position != b.position
+                    || a.rotation != b.rotation
+                    || a.linear != b.linear
+                    || a.angular != b.angular
+                    || a.hooks
+                        .iter()
+                        .map(|(_, point, ..)| point)
+                        .ne(b.hooks.iter().map(|(_, point, ..)| point))
+            })
+            .map(|(a, _)| a.entity)
+    }
+}
+
+fn restore<T: Component + Clone>(world: &mut World, entity: Entity, value: &Option<T>) {
+    let mut entity = world.entity_mut(entity);
+    match value {
+        Some(value) => {
+            entity.insert(value.clone());
+        }
+        None => {
+            entity.remove::<T>();
+        }
+    }
+}
+
+/// The ropes are rebuilt rather than patched, the ones there now may be
+/// attached somewhere else or not exist at all. Objects they were tied to are
+/// bodies of their own and get restored with the rest. Segmented ropes come
+/// back as plain ones, their links aren't rollback safe, and wrapped ones
+/// straight.
+fn restore_hooks(world: &mut World, body: Entity, hooks: &[HookSnapshot]) {
+    let Some(old) = world
+        .get_mut::<Hooks>(body)
+        .map(|mut hooks| std::mem::take(&mut hooks.0))
+    else {
+        return;
+    };
+    for hooked in old {
+        let spawned = (!hooked.object).then_some(hooked.point);
+        let corners = hooked.corners.iter().map(|corner| Some(corner.body));
+        let links = hooked
+            .segments
+            .into_iter()
+            .chain(hooked.links)
+            .map(Some)
+            .chain(corners);
+        for entity in [Some(hooked.rope), spawned]
+            .into_iter()
+            .chain(links)
+            .flatten()
+        {
+            if let Some(entity) = world.get_entity_mut(entity) {
+                entity.despawn();
+            }
+        }
+    }
+
+    let mut restored = Vec::new();
+    for &(hand, position, joint, object) in hooks {
+        let point = if object {
+            joint.entity2
+        } else {
+            world
+                .spawn((RigidBody::Static, Position(position), StateScoped(InGame)))
+                .id()
+        };
+        let rope = world
+            .spawn((
+                DistanceJoint {
+                    entity1: body,
+                    entity2: point,
+                    ..joint
+                },
+                StateScoped(InGame),
+            ))
+            .id();
+        restored.push(Hooked {
+            hand,
+            point,
+            rope,
+            object,
+            segments: Vec::new(),
+            links: Vec::new(),
+            corners: Vec::new(),
+        });
+    }
+    world.entity_mut(body).insert(Hooks(restored));
+}
+
+/// The state at the start of a tick, and the input it was simulated with.
+struct Tick {
+    snapshot: Snapshot,
+    actions: ActionFrame,
+    cursor: Vec2,
+}
+
+#[derive(Resource, Default)]
+struct History {
+    distance: usize,
+    ticks: VecDeque<Tick>,
+    /// Ticks simulated again shouldn't be recorded
+    resimulating: bool,
+}
+
+/// How the checks went so far.
+#[derive(Resource, Default)]
+pub(crate) struct SyncReport {
+    pub(crate) checks: u32,
+    pub(crate) mismatches: u32,
+}
+
+fn save(world: &mut World) {
+    if world.resource::<History>().resimulating {
+        return;
+    }
+
+    // Bodies spawned outside of a tick, like a level being built, only get a
+    // position in the next physics step. There is nothing to rewind them to.
+    let unprepared = world
+        .query_filtered::<(), (With<RigidBody>, Without<Position>)>()
+        .iter(world)
+        .next()
+        .is_some();
+    if unprepared {
+        world.resource_mut::<History>().ticks.clear();
+        return;
+    }
+
+    let snapshot = Snapshot::take(world);
+    let mut history = world.resource_mut::<History>();
+    history.ticks.push_back(Tick {
+        snapshot,
+        actions: ActionFrame::default(),
+        cursor: Vec2::ZERO,
+    });
+    while history.ticks.len() > history.distance {
+        history.ticks.pop_front();
+    }
+}
+
+/// After the tick, once a replay has had its say.
+fn save_input(mut history: ResMut<History>, actions: Res<ActionState>, coords: Res<MyWorldCoords>) {
+    if history.resimulating {
+        return;
+    }
+
+    if let Some(tick) = history.ticks.back_mut() {
+        tick.actions = actions.frame();
+        tick.cursor = coords.0;
+    }
+}
+
+fn clear(mut history: ResMut<History>) {
+    history.ticks.clear();
+}
+
+fn check(world: &mut World) {
+    let history = world.resource::<History>();
+    if history.ticks.len() < history.distance {
+        return;
+    }
+    // Playback would move on to later frames of the recording
+    if world
+        .get_resource::<Replay>()
+        .is_some_and(Replay::is_playing)
+    {
+        return;
+    }
+
+    let live = Snapshot::take(world);
+    let time = *world.resource::<Time>();
+    let actions = world.resource::<ActionState>().frame();
+    let cursor = world.resource::<MyWorldCoords>().0;
+
+    let mut history = world.resource_mut::<History>();
+    history.resimulating = true;
+    let ticks = std::mem::take(&mut history.ticks);
+
+    ticks[0].snapshot.restore(world);
+    for (i, tick) in ticks.iter().enumerate() {
+        // Snapshots are taken once the tick's time has been added
+        if i > 0 {
+            let mut fixed = world.resource_mut::<Time<Fixed>>();
+            let timestep = fixed.timestep();
+            fixed.advance_by(timestep);
+        }
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        world.resource_mut::<ActionState>().play(&tick.actions);
+        world.resource_mut::<MyWorldCoords>().0 = tick.cursor;
+        world.run_schedule(FixedMain);
+    }
+
+    let resimulated = Snapshot::take(world);
+    *world.resource_mut::<Time<Fixed>>() = live.time;
+    *world.resource_mut::<Time>() = time;
+    world.resource_mut::<ActionState>().play(&actions);
+    world.resource_mut::<MyWorldCoords>().0 = cursor;
+
+    let mut history = world.resource_mut::<History>();
+    history.ticks = ticks;
+    history.resimulating = false;
+
+    let mismatch = live.mismatch(&resimulated);
+    let mut report = world.resource_mut::<SyncReport>();
+    report.checks += 1;
+    if let Some(entity) = mismatch {
+        report.mismatches += 1;
+        error!("Rolling back changed the outcome for {entity}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deterministic::tests;
+
+    /// Swing on the hook into the step, let go and jump. Bits are
+    /// `MoveRight`, `Jump` and `Hook`.
+    const INPUTS: &str = "[
+        (10, (held: 0, latched: 0, stick: None, aim: None)),
+        (1, (held: 18, latched: 16, stick: None, aim: Some((1., 0.3)))),
+        (40, (held: 18, latched: 0, stick: None, aim: Some((1., 0.3)))),
+        (20, (held: 2, latched: 0, stick: None, aim: None)),
+        (1, (held: 6, latched: 4, stick: None, aim: None)),
+        (30, (held: 2, latched: 0, stick: None, aim: None)),
+    ]";
+
+    #[test]
+    fn rolling_back_changes_nothing() {
+        let mut app = tests::app(7);
+        app.add_plugins(SyncTestPlugin { distance: 4 });
+        let rolled_back = tests::play(app, INPUTS);
+
+        let straight = tests::play(tests::app(7), INPUTS);
+        assert_eq!(rolled_back, straight);
+    }
+}
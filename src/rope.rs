@@ -0,0 +1,85 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{pool::Pool, state::InGame, world::Layer};
+
+/// Longest a link of a segmented rope gets
+const SEGMENT_LENGTH: f32 = 40.;
+const SEGMENT_RADIUS: f32 = 4.;
+/// Long ropes get longer links instead of more of them
+const MAX_SEGMENTS: usize = 40;
+
+/// A link of a segmented rope, which catches on corners and sags when slack.
+#[derive(Component)]
+pub(crate) struct RopeSegment;
+
+/// Chains small bodies between `player` at `from` and `point` at `to`,
+/// taking them out of `pool` where it has any. Returns the joint at the
+/// player's end, the other joints and the bodies.
+pub(crate) fn spawn_segmented(
+    commands: &mut Commands,
+    pool: &mut Pool<RopeSegment>,
+    player: Entity,
+    from: Vec2,
+    point: Entity,
+    to: Vec2,
+) -> (Entity, Vec<Entity>, Vec<Entity>) {
+    let length = from.distance(to);
+    let count = ((length / SEGMENT_LENGTH).ceil() as usize).clamp(1, MAX_SEGMENTS);
+    let link = length / count as f32;
+
+    let links: Vec<_> = (1..count)
+        .map(|i| {
+            let position = from.lerp(to, i as f32 / count as f32);
+            pool.spawn(commands, make_segment(position))
+        })
+        .collect();
+    let chain: Vec<_> = std::iter::once(player)
+        .chain(links.iter().copied())
+        .chain([point])
+        .collect();
+
+    // Links can bunch up but never stretch past their length
+    let mut joints = chain.windows(2).map(|pair| {
+        commands
+            .spawn((
+                DistanceJoint::new(pair[0], pair[1]).with_limits(0., link),
+                StateScoped(InGame),
+            ))
+            .id()
+    });
+    let rope = joints.next().expect("a rope has at least one link");
+    (rope, joints.collect(), links)
+}
+
+fn make_segment(
+    position: Vec2,
+) -> (
+    SpriteBundle,
+    RopeSegment,
+    RigidBody,
+    AngularVelocity,
+    Collider,
+    CollisionLayers,
+    StateScoped<InGame>,
+) {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(30., 0.3, 0.7),
+                custom_size: Some(Vec2::splat(SEGMENT_RADIUS * 2.)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(-0.1)),
+            ..default()
+        },
+        RopeSegment,
+        RigidBody::Dynamic,
+        // A reused link could still be spinning from its last rope
+        AngularVelocity::ZERO,
+        Collider::circle(SEGMENT_RADIUS),
+        // Drapes over the level, but not over the player or itself
+        CollisionLayers::new(Layer::Rope, !LayerMask::from([Layer::Player, Layer::Rope])),
+        StateScoped(InGame),
+    )
+}
@@ -0,0 +1,75 @@
+use bevy::{prelude::*, time::Stopwatch};
+use serde::{Deserialize, Serialize};
+
+use crate::{input::Action, player::Controls, stats::Stats};
+
+/// One attempt at a level. Dying rebuilds the level but keeps the run going,
+/// it is only cleared when starting the level over.
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Run {
+    /// Where the player respawns, `None` is the level's spawn point
+    pub(crate) checkpoint: Option<Vec2>,
+    pub(crate) deaths: u32,
+    /// Starts on the first input and stops at the goal
+    pub(crate) time: Stopwatch,
+    started: bool,
+    /// Time each checkpoint was first reached, by its index in the level
+    pub(crate) splits: Vec<Option<f32>>,
+    pub(crate) stats: Stats,
+}
+
+impl Run {
+    /// Record a split for checkpoint `index` unless it already has one.
+    /// Returns the new split.
+    pub(crate) fn split(&mut self, index: usize) -> Option<f32> {
+        if self.splits.get(index).is_some_and(Option::is_some) {
+            return None;
+        }
+
+        if self.splits.len() <= index {
+            self.splits.resize(index + 1, None);
+        }
+        let time = self.time.elapsed_secs();
+        self.splits[index] = Some(time);
+        Some(time)
+    }
+}
+
+pub(crate) fn tick(mut run: ResMut<Run>, players: Query<&Controls>, time: Res<Time>) {
+    if !run.started {
+        let moved = players.iter().any(|controls| {
+            let actions = &controls.actions;
+            actions.move_x() != 0.
+                || [
+                    Action::Jump,
+                    Action::Slide,
+                    Action::Hook,
+                    Action::SecondHook,
+                    Action::Bomb,
+                ]
+                .into_iter()
+                .any(|action| actions.pressed(action))
+        });
+        if !moved {
+            return;
+        }
+        run.started = true;
+    }
+
+    run.time.tick(time.delta());
+}
+
+pub(crate) fn reset(mut run: ResMut<Run>) {
+    *run = Run::default();
+}
+
+/// Minutes, seconds and hundredths, e.g. `1:05.30`.
+pub(crate) fn format_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.).floor();
+    format!("{}:{:05.2}", minutes, seconds - minutes * 60.)
+}
+
+/// Signed difference to a best time, e.g. `-0.52` when ahead.
+pub(crate) fn format_delta(seconds: f32) -> String {
+    format!("{seconds:+.2}")
+}
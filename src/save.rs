@@ -0,0 +1,257 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    abilities::{self, Ability},
+    achievements::Achievement,
+    audio::VolumeSettings,
+    daily::Attempt,
+    input::KeyBindings,
+    level::Levels,
+    mutators::{Mutator, Mutators},
+    pickup::Coins,
+    run::Run,
+    shop::Upgrade,
+    skins::Skin,
+    stats::Stats,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_PATH: &str = "save.ron";
+#[cfg(target_arch = "wasm32")]
+const SAVE_KEY: &str = "glatformer_save";
+/// Bumped whenever loading an older save needs more than defaults for the
+/// new fields, see [`SaveData::migrate`]
+const VERSION: u32 = 3;
+
+/// Progress that is kept between sessions.
+#[derive(Resource, Serialize, Deserialize)]
+pub(crate) struct SaveData {
+    /// Saves from before versioning have none
+    #[serde(default)]
+    version: u32,
+    /// Keyed by level file name, only completed levels have an entry
+    pub(crate) levels: HashMap<String, LevelProgress>,
+    /// File names of the levels that can be played besides the first
+    #[serde(default)]
+    pub(crate) unlocked: HashSet<String>,
+    /// Abilities found so far, older saves get the ones a new game starts with
+    #[serde(default = "abilities::starting")]
+    pub(crate) abilities: HashSet<Ability>,
+    /// Coins banked from completed levels and not spent yet
+    #[serde(default)]
+    pub(crate) coins: u32,
+    /// Tiers bought of each upgrade in the shop
+    #[serde(default)]
+    pub(crate) upgrades: HashMap<Upgrade, u32>,
+    #[serde(default)]
+    pub(crate) achievements: HashSet<Achievement>,
+    /// Coins found in every completed run, for an achievement
+    #[serde(default)]
+    pub(crate) coins_collected: u32,
+    /// Lifetime totals
+    #[serde(default)]
+    pub(crate) stats: Stats,
+    /// The latest daily challenge played
+    #[serde(default)]
+    pub(crate) daily: Option<Attempt>,
+    /// Map cells visited in each level, keyed by level file name
+    #[serde(default)]
+    pub(crate) explored: HashMap<String, HashSet<IVec2>>,
+    /// What the player looks like
+    #[serde(default)]
+    pub(crate) skin: Skin,
+    /// Version 1 saves kept the settings too, they're only read to move them
+    /// to their own file
+    #[serde(default, skip_serializing)]
+    settings: Option<SavedSettings>,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            levels: HashMap::new(),
+            unlocked: HashSet::new(),
+            abilities: abilities::starting(),
+            coins: 0,
+            upgrades: HashMap::new(),
+            achievements: HashSet::new(),
+            coins_collected: 0,
+            stats: Stats::default(),
+            daily: None,
+            explored: HashMap::new(),
+            skin: Skin::default(),
+            settings: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct LevelProgress {
+    /// Fastest completion in seconds, infinite while the level has only been
+    /// finished with mutators that speed it up
+    pub(crate) best_time: f32,
+    /// Checkpoint splits of the fastest completion
+    #[serde(default)]
+    pub(crate) best_splits: Vec<Option<f32>>,
+    /// Most coins collected in one completion
+    #[serde(default)]
+    pub(crate) coins: u32,
+    /// Mutators the fastest completion was played with
+    #[serde(default)]
+    pub(crate) best_mutators: Vec<Mutator>,
+}
+
+/// The settings as version 1 saves kept them.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct SavedSettings {
+    pub(crate) volume: VolumeSettings,
+    pub(crate) bindings: KeyBindings,
+}
+
+impl SaveData {
+    pub(crate) fn is_unlocked(&self, levels: &Levels, index: usize) -> bool {
+        index == 0
+            || levels
+                .names
+                .get(index)
+                .is_some_and(|name| self.unlocked.contains(name))
+    }
+
+    pub(crate) fn tier(&self, upgrade: Upgrade) -> u32 {
+        self.upgrades.get(&upgrade).copied().unwrap_or(0)
+    }
+
+    /// Bring an older save up to [`VERSION`].
+    fn migrate(&mut self) {
+        if self.version > VERSION {
+            warn!(
+                "Save file is from a newer version ({}), some progress may be lost",
+                self.version
+            );
+        }
+        if self.version < 1 {
+            // Every level was open before unlocking, keep the ones played
+            self.unlocked.extend(self.levels.keys().cloned());
+        }
+        if self.version < 2 {
+            // Coins weren't banked before the shop, pay out the ones found
+            self.coins = self.levels.values().map(|progress| progress.coins).sum();
+        }
+        // Version 3 moved the settings to their own file, which picks them up
+        // from older saves itself, see `old_settings`
+        self.version = VERSION;
+    }
+
+    /// Settings left in the save from before they had their own file.
+    pub(crate) fn old_settings() -> Option<SavedSettings> {
+        Self::read()?.settings
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match ron::from_str::<Self>(text) {
+            Ok(mut save) => {
+                save.migrate();
+                Some(save)
+            }
+            Err(err) => {
+                warn!("Ignoring unreadable save file: {err}");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read() -> Option<Self> {
+        let text = std::fs::read_to_string(SAVE_PATH).ok()?;
+        let save = Self::parse(&text);
+        if save.is_none() {
+            // Keep it around instead of overwriting it on the next write
+            let backup = format!("{SAVE_PATH}.bak");
+            if let Err(err) = std::fs::rename(SAVE_PATH, &backup) {
+                error!("Could not move unreadable save file to {backup}: {err}");
+            }
+        }
+        save
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn write(&self) {
+        let result = ron::ser::to_string_pretty(self, default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| std::fs::write(SAVE_PATH, text).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            error!("Could not write save file: {err}");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read() -> Option<Self> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        Self::parse(&storage.get_item(SAVE_KEY).ok()??)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn write(&self) {
+        let Some(storage) = web_sys::window().and_then(|window| window.local_storage().ok()?)
+        else {
+            return;
+        };
+
+        let result = ron::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                storage
+                    .set_item(SAVE_KEY, &text)
+                    .map_err(|err| format!("{err:?}"))
+            });
+
+        if let Err(err) = result {
+            error!("Could not write save file: {err}");
+        }
+    }
+}
+
+pub(crate) fn load(mut commands: Commands) {
+    commands.insert_resource(SaveData::read().unwrap_or_default());
+}
+
+pub(crate) fn record_completion(
+    mut save: ResMut<SaveData>,
+    levels: Res<Levels>,
+    run: Res<Run>,
+    coins: Res<Coins>,
+    mutators: Res<Mutators>,
+) {
+    let Some(name) = levels.names.get(levels.current) else {
+        return;
+    };
+
+    let time = run.time.elapsed_secs();
+    let progress = save.levels.entry(name.clone()).or_insert(LevelProgress {
+        best_time: f32::INFINITY,
+        best_splits: Vec::new(),
+        coins: 0,
+        best_mutators: Vec::new(),
+    });
+    if time < progress.best_time && !mutators.speed_up() {
+        progress.best_time = time;
+        progress.best_splits.clone_from(&run.splits);
+        progress.best_mutators = mutators.0.iter().copied().collect();
+    }
+    // Only coins beyond the best haul count, replaying a level can't farm them
+    let banked = coins.0.saturating_sub(progress.coins);
+    progress.coins = progress.coins.max(coins.0);
+    save.coins += banked;
+
+    if let Some(next) = levels.names.get(levels.current + 1) {
+        save.unlocked.insert(next.clone());
+    }
+
+    save.write();
+}
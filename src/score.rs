@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::{
+    enemy::EnemyDefeated,
+    level::{Level, Levels},
+    pickup::PickedUp,
+    run::Run,
+};
+
+const COIN_POINTS: u32 = 100;
+const ENEMY_POINTS: u32 = 250;
+/// Points for every second a completion beats the bronze time by
+const TIME_POINTS: f32 = 50.;
+/// Seconds the next scoring action has to follow the last one to keep the
+/// combo going
+const COMBO_WINDOW: f32 = 2.;
+const MAX_COMBO: u32 = 8;
+
+/// Points earned in the current attempt at a level.
+#[derive(Resource)]
+pub(crate) struct Score {
+    pub(crate) points: u32,
+    /// Multiplier for the next points, grows as scoring actions chain
+    pub(crate) combo: u32,
+    combo_timer: Timer,
+    /// Awarded on completion for beating the bronze time
+    pub(crate) time_bonus: u32,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            points: 0,
+            combo: 1,
+            combo_timer: Timer::from_seconds(COMBO_WINDOW, TimerMode::Once),
+            time_bonus: 0,
+        }
+    }
+}
+
+impl Score {
+    fn add(&mut self, points: u32) {
+        self.points += points * self.combo;
+        self.combo = (self.combo + 1).min(MAX_COMBO);
+        self.combo_timer.reset();
+    }
+}
+
+pub(crate) fn collect(
+    mut picked_up: EventReader<PickedUp>,
+    mut defeated: EventReader<EnemyDefeated>,
+    mut score: ResMut<Score>,
+) {
+    for _ in picked_up.read() {
+        score.add(COIN_POINTS);
+    }
+    for _ in defeated.read() {
+        score.add(ENEMY_POINTS);
+    }
+}
+
+/// Drop the combo once nothing has scored for a while.
+pub(crate) fn decay(mut score: ResMut<Score>, time: Res<Time>) {
+    if score.combo == 1 {
+        return;
+    }
+
+    if score.combo_timer.tick(time.delta()).finished() {
+        score.combo = 1;
+    }
+}
+
+pub(crate) fn time_bonus(
+    mut score: ResMut<Score>,
+    run: Res<Run>,
+    levels: Res<Levels>,
+    assets: Res<Assets<Level>>,
+) {
+    let Some(level) = levels.current(&assets) else {
+        return;
+    };
+
+    let saved = level.medals.bronze - run.time.elapsed_secs();
+    score.time_bonus = (saved.max(0.) * TIME_POINTS) as u32;
+    score.points += score.time_bonus;
+}
@@ -0,0 +1,675 @@
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::VolumeSettings,
+    debug::DebugTools,
+    difficulty::Difficulty,
+    input::{Action, Binding, HoldSettings, KeyBindings, RumbleSettings, SLOTS},
+    leaderboard::LeaderboardConfig,
+    locale::{self, Language, Locale},
+    netplay,
+    palette::{self, Palette},
+    save::SaveData,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const CONFIG_PATH: &str = "settings.ron";
+#[cfg(target_arch = "wasm32")]
+const CONFIG_KEY: &str = "glatformer_settings";
+/// Window sizes offered in the video settings, the browser sizes the canvas
+/// itself
+#[cfg(not(target_arch = "wasm32"))]
+const RESOLUTIONS: [UVec2; 5] = [
+    UVec2::new(1280, 720),
+    UVec2::new(1600, 900),
+    UVec2::new(1920, 1080),
+    UVec2::new(2560, 1440),
+    UVec2::new(3840, 2160),
+];
+
+/// Loads the settings from the last session and keeps them up to date. Goes
+/// before the plugins using them so they find the loaded values in place of
+/// their defaults.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let config = Config::read()
+            .or_else(Config::from_save)
+            .unwrap_or_default();
+        app.insert_resource(config.volume.clone())
+            .insert_resource(config.bindings.clone())
+            .insert_resource(config.holds.clone())
+            .insert_resource(config.rumble.clone())
+            .insert_resource(config.video.clone())
+            .insert_resource(config.gameplay.clone())
+            .insert_resource(config.assist.clone())
+            .insert_resource(config.difficulty)
+            .insert_resource(config.language)
+            .insert_resource(Locale::new(config.language))
+            .insert_resource(config.palette)
+            .insert_resource(config)
+            .add_systems(Startup, apply_video)
+            .add_systems(PostUpdate, palette::recolor)
+            .add_systems(
+                Update,
+                (
+                    apply_video.run_if(resource_changed::<VideoSettings>),
+                    scale_egui,
+                    locale::apply.run_if(resource_changed::<Language>),
+                    // Not the host's rules the guest plays by online
+                    store
+                        .run_if(settings_closed)
+                        .run_if(not(netplay::following)),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct VideoSettings {
+    pub(crate) fullscreen: bool,
+    pub(crate) vsync: bool,
+    /// Size of the window when it isn't fullscreen
+    pub(crate) resolution: UVec2,
+    /// Size of the menus and HUD compared to the usual
+    pub(crate) ui_scale: f32,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            vsync: true,
+            resolution: UVec2::new(1280, 720),
+            ui_scale: 1.,
+        }
+    }
+}
+
+/// Choices that change how the game plays rather than how it looks or sounds.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct GameplaySettings {
+    /// Build the hook's rope out of links that wrap around corners, instead
+    /// of a single straight joint
+    pub(crate) segmented_rope: bool,
+    /// The hook reels the player straight in and lets go on arrival, instead
+    /// of holding them at a fixed length to swing
+    pub(crate) pull_hook: bool,
+    /// The player's own bombs hurt them, turn off to bomb jump for free
+    pub(crate) bomb_self_damage: bool,
+    /// Move the player with a kinematic body that slides along what it hits,
+    /// instead of a dynamic one the physics solver pushes around. Takes
+    /// effect the next time the player spawns
+    pub(crate) kinematic_body: bool,
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            segmented_rope: false,
+            pull_hook: false,
+            bomb_self_damage: true,
+            kinematic_body: false,
+        }
+    }
+}
+
+/// Help for players who find the game too hard. Everything but `enabled` only
+/// counts while it's on, so the choices are kept when it's turned off.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AssistSettings {
+    /// Turns assist mode on, which also stops bombs hurting the player
+    pub(crate) enabled: bool,
+    /// How fast the game runs, on top of the slow motion keys
+    pub(crate) game_speed: f32,
+    pub(crate) invincible: bool,
+    pub(crate) infinite_air_jumps: bool,
+    /// The hook goes to the nearest anchor in range, wherever it's aimed
+    pub(crate) auto_grapple: bool,
+}
+
+impl Default for AssistSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            game_speed: 1.,
+            invincible: false,
+            infinite_air_jumps: false,
+            auto_grapple: false,
+        }
+    }
+}
+
+impl AssistSettings {
+    pub(crate) fn game_speed(&self) -> f32 {
+        if self.enabled {
+            self.game_speed
+        } else {
+            1.
+        }
+    }
+
+    pub(crate) fn invincible(&self) -> bool {
+        self.enabled && self.invincible
+    }
+
+    pub(crate) fn infinite_air_jumps(&self) -> bool {
+        self.enabled && self.infinite_air_jumps
+    }
+
+    pub(crate) fn auto_grapple(&self) -> bool {
+        self.enabled && self.auto_grapple
+    }
+}
+
+/// The settings as last written, anything missing keeps its default.
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Config {
+    volume: VolumeSettings,
+    bindings: KeyBindings,
+    holds: HoldSettings,
+    rumble: RumbleSettings,
+    video: VideoSettings,
+    gameplay: GameplaySettings,
+    assist: AssistSettings,
+    difficulty: Difficulty,
+    language: Language,
+    palette: Palette,
+}
+
+impl Config {
+    /// Moves over the settings an older save kept, the first time the game
+    /// runs without a settings file.
+    fn from_save() -> Option<Self> {
+        let saved = SaveData::old_settings()?;
+        let config = Self {
+            volume: saved.volume,
+            bindings: saved.bindings,
+            ..default()
+        };
+        config.write();
+        Some(config)
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match ron::from_str(text) {
+            Ok(config) => Some(config),
+            Err(err) => {
+                warn!("Ignoring unreadable settings: {err}");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read() -> Option<Self> {
+        Self::parse(&std::fs::read_to_string(CONFIG_PATH).ok()?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write(&self) {
+        let result = ron::ser::to_string_pretty(self, default())
+            .map_err(|err| err.to_string())
+            .and_then(|text| std::fs::write(CONFIG_PATH, text).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            error!("Could not write settings: {err}");
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read() -> Option<Self> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        Self::parse(&storage.get_item(CONFIG_KEY).ok()??)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn write(&self) {
+        let Some(storage) = web_sys::window().and_then(|window| window.local_storage().ok()?)
+        else {
+            return;
+        };
+
+        if let Ok(text) = ron::to_string(self) {
+            let _ = storage.set_item(CONFIG_KEY, &text);
+        }
+    }
+}
+
+fn apply_video(
+    video: Res<VideoSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    ui_scale.0 = video.ui_scale;
+    let Some(mut window) = windows.iter_mut().next() else {
+        return;
+    };
+
+    window.mode = if video.fullscreen {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+    window.present_mode = if video.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    if !video.fullscreen {
+        let size = video.resolution.as_vec2();
+        window.resolution.set(size.x, size.y);
+    }
+}
+
+/// Egui adds its settings to each window once it's open, so this keeps them
+/// in line rather than setting them once.
+fn scale_egui(video: Res<VideoSettings>, mut windows: Query<&mut EguiSettings>) {
+    for mut settings in &mut windows {
+        if settings.scale_factor != video.ui_scale {
+            settings.scale_factor = video.ui_scale;
+        }
+    }
+}
+
+/// Write the settings once the window closes, if anything changed.
+fn store(
+    mut config: ResMut<Config>,
+    volume: Res<VolumeSettings>,
+    bindings: Res<KeyBindings>,
+    holds: Res<HoldSettings>,
+    rumble: Res<RumbleSettings>,
+    video: Res<VideoSettings>,
+    gameplay: Res<GameplaySettings>,
+    assist: Res<AssistSettings>,
+    difficulty: Res<Difficulty>,
+    language: Res<Language>,
+    palette: Res<Palette>,
+) {
+    if config.volume == *volume
+        && config.bindings == *bindings
+        && config.holds == *holds
+        && config.rumble == *rumble
+        && config.video == *video
+        && config.gameplay == *gameplay
+        && config.assist == *assist
+        && config.difficulty == *difficulty
+        && config.language == *language
+        && config.palette == *palette
+    {
+        return;
+    }
+
+    config.volume.clone_from(&volume);
+    config.bindings.clone_from(&bindings);
+    config.holds.clone_from(&holds);
+    config.rumble.clone_from(&rumble);
+    config.video.clone_from(&video);
+    config.gameplay.clone_from(&gameplay);
+    config.assist.clone_from(&assist);
+    config.difficulty = *difficulty;
+    config.language = *language;
+    config.palette = *palette;
+    config.write();
+}
+
+/// Whether the settings window is showing. It can be opened from both the
+/// main menu and the pause menu and draws over whichever one opened it.
+#[derive(Resource, Default)]
+pub(crate) struct SettingsMenu {
+    pub(crate) open: bool,
+    /// Binding slot waiting for the next key, mouse or gamepad press
+    rebinding: Option<(Action, usize)>,
+}
+
+pub(crate) fn settings_closed(menu: Res<SettingsMenu>) -> bool {
+    !menu.open
+}
+
+pub(crate) fn settings_window(
+    mut contexts: EguiContexts,
+    mut menu: ResMut<SettingsMenu>,
+    mut bindings: ResMut<KeyBindings>,
+    mut holds: ResMut<HoldSettings>,
+    mut rumble: ResMut<RumbleSettings>,
+    mut volume: ResMut<VolumeSettings>,
+    mut video: ResMut<VideoSettings>,
+    mut gameplay: ResMut<GameplaySettings>,
+    mut assist: ResMut<AssistSettings>,
+    mut difficulty: ResMut<Difficulty>,
+    mut language: ResMut<Language>,
+    mut palette: ResMut<Palette>,
+    mut leaderboard: ResMut<LeaderboardConfig>,
+    tools: Option<ResMut<DebugTools>>,
+    locale: Res<Locale>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new(locale.get("settings"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading(locale.get("settings-volume"));
+            let volume = volume.as_mut();
+            egui::Grid::new("volume").show(ui, |ui| {
+                for (name, value) in [
+                    ("volume-master", &mut volume.master),
+                    ("volume-player", &mut volume.player),
+                    ("volume-hook", &mut volume.hook),
+                    ("volume-world", &mut volume.world),
+                    ("volume-music", &mut volume.music),
+                ] {
+                    ui.label(locale.get(name));
+                    ui.add(egui::Slider::new(value, 0.0..=1.0));
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.heading(locale.get("settings-video"));
+            // Only mark the settings changed when a box is clicked
+            let mut fullscreen = video.fullscreen;
+            let mut vsync = video.vsync;
+            ui.checkbox(&mut fullscreen, locale.get("video-fullscreen"));
+            ui.checkbox(&mut vsync, locale.get("video-vsync"));
+            if (fullscreen, vsync) != (video.fullscreen, video.vsync) {
+                video.fullscreen = fullscreen;
+                video.vsync = vsync;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.add_enabled_ui(!video.fullscreen, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(locale.get("video-resolution"));
+                    egui::ComboBox::from_id_salt("resolution")
+                        .selected_text(format!("{} × {}", video.resolution.x, video.resolution.y))
+                        .show_ui(ui, |ui| {
+                            for choice in RESOLUTIONS {
+                                let label = format!("{} × {}", choice.x, choice.y);
+                                if ui
+                                    .selectable_label(video.resolution == choice, label)
+                                    .clicked()
+                                    && video.resolution != choice
+                                {
+                                    video.resolution = choice;
+                                }
+                            }
+                        });
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label(locale.get("video-ui-scale"));
+                let mut ui_scale = video.ui_scale;
+                ui.add(
+                    egui::Slider::new(&mut ui_scale, 0.75..=2.0)
+                        .step_by(0.25)
+                        .custom_formatter(|scale, _| format!("{:.0}%", scale * 100.)),
+                );
+                if ui_scale != video.ui_scale {
+                    video.ui_scale = ui_scale;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(locale.get("video-palette"));
+                egui::ComboBox::from_id_salt("palette")
+                    .selected_text(locale.get(palette.message()))
+                    .show_ui(ui, |ui| {
+                        for choice in Palette::ALL {
+                            if ui
+                                .selectable_label(*palette == choice, locale.get(choice.message()))
+                                .clicked()
+                            {
+                                palette.set_if_neq(choice);
+                            }
+                        }
+                    });
+            });
+
+            ui.separator();
+            ui.heading(locale.get("settings-gameplay"));
+            ui.horizontal(|ui| {
+                ui.label(locale.get("gameplay-difficulty"));
+                egui::ComboBox::from_id_salt("difficulty")
+                    .selected_text(locale.get(difficulty.message()))
+                    .show_ui(ui, |ui| {
+                        for choice in Difficulty::ALL {
+                            if ui
+                                .selectable_label(
+                                    *difficulty == choice,
+                                    locale.get(choice.message()),
+                                )
+                                .clicked()
+                            {
+                                difficulty.set_if_neq(choice);
+                            }
+                        }
+                    });
+            });
+            let mut segmented_rope = gameplay.segmented_rope;
+            if ui
+                .checkbox(&mut segmented_rope, locale.get("gameplay-segmented-rope"))
+                .changed()
+            {
+                gameplay.segmented_rope = segmented_rope;
+            }
+            let mut pull_hook = gameplay.pull_hook;
+            if ui
+                .checkbox(&mut pull_hook, locale.get("gameplay-pull-hook"))
+                .changed()
+            {
+                gameplay.pull_hook = pull_hook;
+            }
+            let mut bomb_self_damage = gameplay.bomb_self_damage;
+            if ui
+                .checkbox(
+                    &mut bomb_self_damage,
+                    locale.get("gameplay-bomb-self-damage"),
+                )
+                .changed()
+            {
+                gameplay.bomb_self_damage = bomb_self_damage;
+            }
+            let mut kinematic_body = gameplay.kinematic_body;
+            if ui
+                .checkbox(&mut kinematic_body, locale.get("gameplay-kinematic-body"))
+                .changed()
+            {
+                gameplay.kinematic_body = kinematic_body;
+            }
+
+            ui.separator();
+            ui.heading(locale.get("settings-assist"));
+            // Edit a copy so sliding doesn't mark the settings changed for nothing
+            let mut edited = assist.clone();
+            ui.checkbox(&mut edited.enabled, locale.get("assist-enabled"));
+            ui.add_enabled_ui(edited.enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(locale.get("assist-game-speed"));
+                    ui.add(
+                        egui::Slider::new(&mut edited.game_speed, 0.25..=1.0)
+                            .step_by(0.05)
+                            .custom_formatter(|speed, _| format!("{:.0}%", speed * 100.)),
+                    );
+                });
+                ui.checkbox(&mut edited.invincible, locale.get("assist-invincible"));
+                ui.checkbox(
+                    &mut edited.infinite_air_jumps,
+                    locale.get("assist-infinite-air-jumps"),
+                );
+                ui.checkbox(&mut edited.auto_grapple, locale.get("assist-auto-grapple"));
+            });
+            if edited != *assist {
+                *assist = edited;
+            }
+
+            ui.separator();
+            ui.heading(locale.get("settings-language"));
+            egui::ComboBox::from_id_salt("language")
+                .selected_text(language.native_name())
+                .show_ui(ui, |ui| {
+                    for choice in Language::ALL {
+                        if ui
+                            .selectable_label(*language == choice, choice.native_name())
+                            .clicked()
+                        {
+                            language.set_if_neq(choice);
+                        }
+                    }
+                });
+
+            if let Some(mut tools) = tools {
+                ui.separator();
+                ui.heading(locale.get("settings-debug"));
+                let mut enabled = tools.0;
+                if ui
+                    .checkbox(&mut enabled, locale.get("debug-tools"))
+                    .changed()
+                {
+                    tools.0 = enabled;
+                }
+            }
+
+            if leaderboard.endpoint.is_some() {
+                ui.separator();
+                ui.heading(locale.get("settings-leaderboard"));
+                ui.horizontal(|ui| {
+                    ui.label(locale.get("leaderboard-name"));
+                    ui.text_edit_singleline(&mut leaderboard.name);
+                });
+            }
+
+            ui.separator();
+            ui.heading(locale.get("settings-controls"));
+            let mut toggle_slide = holds.toggle_slide;
+            if ui
+                .checkbox(&mut toggle_slide, locale.get("controls-toggle-slide"))
+                .changed()
+            {
+                holds.toggle_slide = toggle_slide;
+            }
+            let mut toggle_hook = holds.toggle_hook;
+            if ui
+                .checkbox(&mut toggle_hook, locale.get("controls-toggle-hook"))
+                .changed()
+            {
+                holds.toggle_hook = toggle_hook;
+            }
+            let mut sticky_debug_modifier = holds.sticky_debug_modifier;
+            if ui
+                .checkbox(
+                    &mut sticky_debug_modifier,
+                    locale.get("controls-sticky-debug-modifier"),
+                )
+                .changed()
+            {
+                holds.sticky_debug_modifier = sticky_debug_modifier;
+            }
+            // Edit a copy so sliding doesn't mark the settings changed for nothing
+            let mut edited = rumble.clone();
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut edited.enabled, locale.get("controls-rumble"));
+                ui.add_enabled(
+                    edited.enabled,
+                    egui::Slider::new(&mut edited.strength, 0.0..=1.0)
+                        .custom_formatter(|strength, _| format!("{:.0}%", strength * 100.)),
+                );
+            });
+            if edited != *rumble {
+                *rumble = edited;
+            }
+            egui::Grid::new("bindings").show(ui, |ui| {
+                for action in Action::ALL {
+                    ui.label(locale.get(action.message()));
+
+                    for slot in 0..SLOTS {
+                        let label = if menu.rebinding == Some((action, slot)) {
+                            locale.get("controls-press-a-key").to_string()
+                        } else {
+                            bindings.get(action)[slot].map_or("-".to_string(), Binding::name)
+                        };
+
+                        let button = ui.add(egui::Button::new(label).min_size([140., 0.].into()));
+                        if button.clicked() {
+                            menu.rebinding = Some((action, slot));
+                        }
+                        if button.secondary_clicked() {
+                            bindings.get_mut(action)[slot] = None;
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.label(locale.get("controls-clear-hint"));
+            if ui.button(locale.get("controls-reset")).clicked() {
+                *bindings = KeyBindings::default();
+            }
+        });
+
+    if !open {
+        menu.open = false;
+        menu.rebinding = None;
+    }
+}
+
+/// Assigns the next press to the binding being changed, Escape cancels or
+/// closes the window.
+pub(crate) fn settings_input(
+    mut menu: ResMut<SettingsMenu>,
+    mut bindings: ResMut<KeyBindings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    pads: Res<ButtonInput<GamepadButton>>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    let Some((action, slot)) = menu.rebinding else {
+        if keys.just_pressed(KeyCode::Escape) {
+            menu.open = false;
+        }
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        menu.rebinding = None;
+        return;
+    }
+
+    let binding = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            pads.get_just_pressed()
+                .next()
+                .map(|button| Binding::Pad(button.button_type))
+        });
+
+    if let Some(binding) = binding {
+        bindings.get_mut(action)[slot] = Some(binding);
+        menu.rebinding = None;
+    }
+}
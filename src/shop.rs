@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    health::Health,
+    locale::Locale,
+    player::{HookRules, Player},
+    save::SaveData,
+};
+
+/// Health the player starts with before any upgrades
+const BASE_HEALTH: u32 = 3;
+/// Most tiers any upgrade can be bought up to
+const MAX_TIER: u32 = 3;
+/// Hook range each tier of [`Upgrade::LongerHook`] adds
+const RANGE_PER_TIER: f32 = 750.;
+/// How much faster each tier of [`Upgrade::FasterReel`] reels in
+const REEL_PER_TIER: f32 = 0.25;
+
+/// Lasting improvements bought with banked coins.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub(crate) enum Upgrade {
+    ExtraHealth,
+    LongerHook,
+    FasterReel,
+}
+
+impl Upgrade {
+    const ALL: [Upgrade; 3] = [
+        Upgrade::ExtraHealth,
+        Upgrade::LongerHook,
+        Upgrade::FasterReel,
+    ];
+
+    /// Id of the upgrade's name in the locale files, its description is
+    /// under the same id with `-description` on the end.
+    fn message(self) -> &'static str {
+        match self {
+            Upgrade::ExtraHealth => "upgrade-extra-health",
+            Upgrade::LongerHook => "upgrade-longer-hook",
+            Upgrade::FasterReel => "upgrade-faster-reel",
+        }
+    }
+
+    /// Coins the next tier costs, each one dearer than the last.
+    fn cost(self, tier: u32) -> u32 {
+        let base = match self {
+            Upgrade::ExtraHealth => 10,
+            Upgrade::LongerHook => 8,
+            Upgrade::FasterReel => 6,
+        };
+        base * (tier + 1)
+    }
+}
+
+pub(crate) fn max_health(save: &SaveData) -> u32 {
+    BASE_HEALTH + save.tier(Upgrade::ExtraHealth)
+}
+
+/// A level's hook rules with the bought upgrades on top.
+pub(crate) fn upgrade_hook(mut rules: HookRules, save: &SaveData) -> HookRules {
+    rules.range += RANGE_PER_TIER * save.tier(Upgrade::LongerHook) as f32;
+    let reel = reel_scale(save.tier(Upgrade::FasterReel));
+    rules.pull_speed *= reel;
+    rules.yank_speed *= reel;
+    rules
+}
+
+fn reel_scale(tier: u32) -> f32 {
+    1. + REEL_PER_TIER * tier as f32
+}
+
+/// Whether the shop window is showing. Like the settings it draws over
+/// whichever screen opened it.
+#[derive(Resource, Default)]
+pub(crate) struct ShopMenu {
+    pub(crate) open: bool,
+}
+
+/// Spends banked coins on upgrades. Purchases are saved straight away and
+/// also applied to the player when bought between levels.
+pub(crate) fn shop_window(
+    mut contexts: EguiContexts,
+    mut menu: ResMut<ShopMenu>,
+    mut save: ResMut<SaveData>,
+    mut players: Query<&mut Health, With<Player>>,
+    mut rules: ResMut<HookRules>,
+    locale: Res<Locale>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    let mut bought = None;
+    let mut open = true;
+    egui::Window::new(locale.get("shop"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(locale.format("shop-coins", &[("count", &save.coins)]));
+            ui.separator();
+
+            egui::Grid::new("upgrades").show(ui, |ui| {
+                for upgrade in Upgrade::ALL {
+                    let tier = save.tier(upgrade);
+                    ui.vertical(|ui| {
+                        let name = locale.get(upgrade.message());
+                        ui.label(format!("{name}  {tier} / {MAX_TIER}"));
+                        let description = format!("{}-description", upgrade.message());
+                        ui.label(egui::RichText::new(locale.get(&description)).weak());
+                    });
+
+                    if tier >= MAX_TIER {
+                        ui.label(egui::RichText::new(locale.get("shop-maxed")).weak());
+                    } else {
+                        let cost = upgrade.cost(tier);
+                        if ui
+                            .add_enabled(
+                                save.coins >= cost,
+                                egui::Button::new(locale.format("shop-buy", &[("cost", &cost)])),
+                            )
+                            .clicked()
+                        {
+                            bought = Some((upgrade, cost));
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    menu.open = open;
+
+    let Some((upgrade, cost)) = bought else {
+        return;
+    };
+    save.coins -= cost;
+    *save.upgrades.entry(upgrade).or_insert(0) += 1;
+    save.write();
+
+    // Only the one new tier, the rules and health already have the older ones
+    let tier = save.tier(upgrade);
+    match upgrade {
+        Upgrade::ExtraHealth => {
+            for mut health in &mut players {
+                health.max += 1;
+                health.current += 1;
+            }
+        }
+        Upgrade::LongerHook => rules.range += RANGE_PER_TIER,
+        Upgrade::FasterReel => {
+            let scale = reel_scale(tier) / reel_scale(tier - 1);
+            rules.pull_speed *= scale;
+            rules.yank_speed *= scale;
+        }
+    }
+}
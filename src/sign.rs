@@ -0,0 +1,99 @@
+use bevy::{prelude::*, sprite::Anchor, text::Text2dBounds};
+
+use crate::{level, locale::Locale, player::Player, state::InGame};
+
+/// How close the player has to be for a sign's bubble to show
+const READ_RANGE: f32 = 150.;
+const POST_SIZE: Vec2 = Vec2::new(10., 60.);
+const BOARD_SIZE: Vec2 = Vec2::new(60., 40.);
+/// Widest a bubble gets before its text wraps
+const BUBBLE_WIDTH: f32 = 400.;
+const FONT_SIZE: f32 = 22.;
+
+/// A post with some text on it, shown in a bubble above while the player is
+/// close.
+#[derive(Component)]
+pub(crate) struct Sign;
+
+/// The text over a [`Sign`], a child of it.
+#[derive(Component)]
+pub(crate) struct Bubble;
+
+/// The text is looked up in the locale files, and shown as it is when it
+/// isn't an id there.
+pub(crate) fn spawn(commands: &mut Commands, placed: &level::Sign, locale: &Locale) {
+    let wood = Color::hsl(30., 0.45, 0.35);
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: wood,
+                    custom_size: Some(POST_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_xyz(placed.x, placed.y, -0.5),
+                ..default()
+            },
+            Sign,
+            StateScoped(InGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: wood,
+                    custom_size: Some(BOARD_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., POST_SIZE.y / 2., 0.),
+                ..default()
+            });
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        locale.get(&placed.text),
+                        TextStyle {
+                            font_size: FONT_SIZE,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                    .with_justify(JustifyText::Center),
+                    text_2d_bounds: Text2dBounds {
+                        size: Vec2::new(BUBBLE_WIDTH, f32::INFINITY),
+                    },
+                    text_anchor: Anchor::BottomCenter,
+                    // In front of everything the player might be standing in
+                    transform: Transform::from_xyz(0., POST_SIZE.y / 2. + BOARD_SIZE.y, 10.),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Bubble,
+            ));
+        });
+}
+
+/// Shows the bubbles of the signs the player is close to.
+pub(crate) fn show_nearby(
+    players: Query<&Transform, With<Player>>,
+    signs: Query<(&Transform, &Children), With<Sign>>,
+    mut bubbles: Query<&mut Visibility, With<Bubble>>,
+) {
+    let pos = players
+        .iter()
+        .next()
+        .map(|player| player.translation.truncate());
+
+    for (transform, children) in &signs {
+        let near =
+            pos.is_some_and(|pos| transform.translation.truncate().distance(pos) <= READ_RANGE);
+        let shown = if near {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        let mut iter = bubbles.iter_many_mut(children);
+        while let Some(mut visibility) = iter.fetch_next() {
+            visibility.set_if_neq(shown);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atlas::{Atlas, SpriteAtlas},
+    locale::Locale,
+    save::SaveData,
+};
+
+/// Size of each skin's picture on the character select screen
+const PREVIEW_SIZE: [f32; 2] = [96., 72.];
+
+/// Looks for the player. Each has a sprite sheet laid out like the player's
+/// part of the usual atlas, with the same animation names.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub(crate) enum Skin {
+    /// Drawn from the usual atlas
+    #[default]
+    Classic,
+    Ember,
+    Moss,
+    Frost,
+}
+
+impl Skin {
+    const ALL: [Skin; 4] = [Skin::Classic, Skin::Ember, Skin::Moss, Skin::Frost];
+
+    /// Id of the skin's name in the locale files.
+    fn message(self) -> &'static str {
+        match self {
+            Skin::Classic => "skin-classic",
+            Skin::Ember => "skin-ember",
+            Skin::Moss => "skin-moss",
+            Skin::Frost => "skin-frost",
+        }
+    }
+
+    /// Manifest of the skin's sheet, `None` for the usual atlas.
+    fn sheet(self) -> Option<&'static str> {
+        match self {
+            Skin::Classic => None,
+            Skin::Ember => Some("sprites/skins/ember.ron"),
+            Skin::Moss => Some("sprites/skins/moss.ron"),
+            Skin::Frost => Some("sprites/skins/frost.ron"),
+        }
+    }
+}
+
+/// Every skin's sheet, loaded up front so switching is instant.
+#[derive(Resource, Default)]
+pub(crate) struct SkinSheets(HashMap<Skin, Handle<SpriteAtlas>>);
+
+impl SkinSheets {
+    /// Sheet to draw the player from in `skin`, `None` for the usual atlas.
+    pub(crate) fn get(&self, skin: Skin) -> Option<Handle<SpriteAtlas>> {
+        self.0.get(&skin).cloned()
+    }
+
+    pub(crate) fn all(&self) -> impl Iterator<Item = &Handle<SpriteAtlas>> {
+        self.0.values()
+    }
+}
+
+pub(crate) fn load_sheets(mut sheets: ResMut<SkinSheets>, asset_server: Res<AssetServer>) {
+    for skin in Skin::ALL {
+        if let Some(path) = skin.sheet() {
+            sheets.0.insert(skin, asset_server.load(path));
+        }
+    }
+}
+
+/// Whether the character select window is showing, opened from the main
+/// menu.
+#[derive(Resource, Default)]
+pub(crate) struct SkinMenu {
+    pub(crate) open: bool,
+}
+
+/// A picture of each skin to pick from, the choice goes straight into the
+/// save.
+pub(crate) fn skin_window(
+    mut contexts: EguiContexts,
+    mut menu: ResMut<SkinMenu>,
+    mut save: ResMut<SaveData>,
+    sheets: Res<SkinSheets>,
+    atlas: Res<Atlas>,
+    atlases: Res<Assets<SpriteAtlas>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    locale: Res<Locale>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    // Registered with egui first, it can't borrow the context while drawing
+    let previews: Vec<_> = Skin::ALL
+        .into_iter()
+        .map(|skin| {
+            let sheet = sheets.get(skin).unwrap_or_else(|| atlas.0.clone());
+            let (image, uv) = atlases.get(&sheet)?.uv("player", &layouts)?;
+            let uv = egui::Rect::from_min_max(
+                egui::pos2(uv.min.x, uv.min.y),
+                egui::pos2(uv.max.x, uv.max.y),
+            );
+            Some((contexts.add_image(image.clone_weak()), uv))
+        })
+        .collect();
+
+    let mut picked = None;
+    let mut open = true;
+    egui::Window::new(locale.get("skins"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for (skin, preview) in Skin::ALL.into_iter().zip(&previews) {
+                    ui.vertical_centered(|ui| {
+                        match preview {
+                            Some((texture, uv)) => {
+                                let image = egui::Image::new(egui::load::SizedTexture::new(
+                                    *texture,
+                                    PREVIEW_SIZE,
+                                ))
+                                .uv(*uv);
+                                ui.add(image);
+                            }
+                            None => {
+                                ui.add_sized(PREVIEW_SIZE, egui::Spinner::new());
+                            }
+                        }
+                        if ui
+                            .selectable_label(save.skin == skin, locale.get(skin.message()))
+                            .clicked()
+                        {
+                            picked = Some(skin);
+                        }
+                    });
+                }
+            });
+        });
+
+    if let Some(skin) = picked.filter(|&skin| skin != save.skin) {
+        save.skin = skin;
+        save.write();
+    }
+    if !open {
+        menu.open = false;
+    }
+}
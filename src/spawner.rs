@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    cull::Cull,
+    difficulty::Difficulty,
+    enemy::{self, EnemyKind},
+    level,
+    player::Player,
+    state::InGame,
+};
+
+/// Emits enemies of one kind, either endlessly or in a fixed sequence of
+/// waves where the next wave waits until the last one has been defeated.
+#[derive(Component)]
+pub(crate) struct Spawner {
+    kind: EnemyKind,
+    reload: Timer,
+    max_alive: usize,
+    /// Spawners behind a trigger zone wait for the player to walk in
+    active: bool,
+    /// Wave sizes still to come, empty and `endless` without waves
+    waves: VecDeque<u32>,
+    endless: bool,
+    /// Enemies still to spawn in the current wave
+    remaining: u32,
+}
+
+/// Marks enemies with the spawner they came from, to count how many are alive.
+#[derive(Component)]
+pub(crate) struct SpawnedBy(pub(crate) Entity);
+
+/// Activates a spawner once the player enters it.
+#[derive(Component)]
+pub(crate) struct SpawnTrigger(Entity);
+
+/// Spawn a spawner placed in a level, and its trigger zone if it has one.
+/// Enemies come faster the harder the [`Difficulty`].
+pub(crate) fn spawn(commands: &mut Commands, placed: &level::Spawner, difficulty: Difficulty) {
+    let spawner = commands
+        .spawn((
+            Spawner {
+                kind: placed.kind,
+                reload: Timer::from_seconds(
+                    placed.interval / difficulty.enemy_speed(),
+                    TimerMode::Repeating,
+                ),
+                max_alive: placed.max_alive,
+                active: placed.trigger.is_none(),
+                waves: placed.waves.iter().copied().collect(),
+                endless: placed.waves.is_empty(),
+                remaining: 0,
+            },
+            TransformBundle::from_transform(Transform::from_xyz(placed.x, placed.y, 0.)),
+            StateScoped(InGame),
+        ))
+        .id();
+
+    if let Some(zone) = &placed.trigger {
+        commands.spawn((
+            SpawnTrigger(spawner),
+            TransformBundle::from_transform(Transform::from_xyz(zone.x, zone.y, 0.)),
+            Collider::rectangle(zone.w, zone.h),
+            Sensor,
+            StateScoped(InGame),
+        ));
+    }
+}
+
+pub(crate) fn trigger(
+    mut started: EventReader<CollisionStarted>,
+    triggers: Query<&SpawnTrigger>,
+    players: Query<(), With<Player>>,
+    mut spawners: Query<&mut Spawner>,
+) {
+    for CollisionStarted(a, b) in started.read() {
+        let (zone, other) = if triggers.contains(*a) {
+            (*a, *b)
+        } else {
+            (*b, *a)
+        };
+
+        let Ok(SpawnTrigger(spawner)) = triggers.get(zone) else {
+            continue;
+        };
+        if !players.contains(other) {
+            continue;
+        }
+
+        if let Ok(mut spawner) = spawners.get_mut(*spawner) {
+            spawner.active = true;
+        }
+    }
+}
+
+pub(crate) fn emit(
+    mut spawners: Query<(Entity, &mut Spawner, &Transform)>,
+    spawned: Query<&SpawnedBy>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut spawner, transform) in &mut spawners {
+        if !spawner.active {
+            continue;
+        }
+
+        let alive = spawned.iter().filter(|by| by.0 == entity).count();
+
+        if !spawner.endless && spawner.remaining == 0 {
+            // The next wave only starts once the last one is cleared
+            if alive > 0 {
+                continue;
+            }
+            match spawner.waves.pop_front() {
+                Some(size) => spawner.remaining = size,
+                None => continue,
+            }
+        }
+
+        if !spawner.reload.tick(time.delta()).just_finished() || alive >= spawner.max_alive {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let enemy = enemy::spawn(&mut commands, spawner.kind, position);
+        commands
+            .entity(enemy)
+            .insert((SpawnedBy(entity), Cull::Recycle));
+
+        if !spawner.endless {
+            spawner.remaining -= 1;
+        }
+    }
+}
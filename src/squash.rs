@@ -0,0 +1,83 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::player::{Jumped, Landed, Player};
+
+const STIFFNESS: f32 = 300.0;
+const DAMPING: f32 = 12.0;
+
+/// Spring driven squash and stretch for a sprite.
+///
+/// `stretch` is how much taller than normal the sprite is, negative values
+/// squash it. The width is scaled inversely so the area stays about the same.
+#[derive(Component, Default)]
+pub(crate) struct SquashStretch {
+    stretch: f32,
+    velocity: f32,
+    /// Where the spring settles, non zero while falling fast
+    target: f32,
+}
+
+impl SquashStretch {
+    fn kick(&mut self, amount: f32) {
+        self.velocity += amount;
+    }
+}
+
+pub(crate) fn jump_and_land(
+    mut sprites: Query<&mut SquashStretch>,
+    mut jumped: EventReader<Jumped>,
+    mut landed: EventReader<Landed>,
+) {
+    let jumps = jumped.read().count();
+    let impact = landed.read().map(|ev| ev.impact_speed).fold(0.0, f32::max);
+
+    for mut squash in &mut sprites {
+        if jumps > 0 {
+            squash.kick(4.0);
+        }
+        if impact > 0.0 {
+            squash.kick(-(impact * 0.006).clamp(1.0, 8.0));
+        }
+    }
+}
+
+pub(crate) fn airborne(
+    players: Query<(&Player, &LinearVelocity, &Children)>,
+    mut sprites: Query<&mut SquashStretch>,
+    mut last_vertical: Local<f32>,
+) {
+    for (player, velocity, children) in &players {
+        let apex = !player.is_grounded && *last_vertical > 0.0 && velocity.y <= 0.0;
+        let target = if player.is_grounded {
+            0.0
+        } else {
+            (-velocity.y / 5000.0).clamp(0.0, 0.2)
+        };
+
+        let mut sprites = sprites.iter_many_mut(children);
+        while let Some(mut squash) = sprites.fetch_next() {
+            if apex {
+                squash.kick(-1.5);
+            }
+            squash.target = target;
+        }
+
+        *last_vertical = velocity.y;
+    }
+}
+
+pub(crate) fn spring(time: Res<Time>, mut sprites: Query<(&mut SquashStretch, &mut Transform)>) {
+    // Large steps (e.g. after a hitch) would make the spring explode
+    let dt = time.delta_seconds().min(1.0 / 30.0);
+
+    for (mut squash, mut transform) in &mut sprites {
+        let accel = STIFFNESS * (squash.target - squash.stretch) - DAMPING * squash.velocity;
+        squash.velocity += accel * dt;
+        let velocity = squash.velocity;
+        squash.stretch += velocity * dt;
+
+        let height = (1.0 + squash.stretch).max(0.2);
+        transform.scale = Vec3::new(1.0 / height, height, 1.0);
+    }
+}
@@ -0,0 +1,154 @@
+use avian2d::prelude::*;
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::netplay;
+
+/// The app state machine, and pausing physics whenever the game isn't being
+/// played.
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<AppState>()
+            .add_computed_state::<InGame>()
+            .enable_state_scoped_entities::<InGame>()
+            .configure_sets(PreUpdate, InputSet.after(InputSystem))
+            .configure_sets(Update, GameplaySet.run_if(in_state(AppState::Playing)))
+            .configure_sets(FixedUpdate, GameplaySet.run_if(in_state(AppState::Playing)))
+            .configure_sets(
+                FixedPostUpdate,
+                (
+                    GameplaySet.run_if(in_state(AppState::Playing)),
+                    PostPhysicsSet.after(PhysicsSet::Sync),
+                ),
+            )
+            .configure_sets(
+                PostUpdate,
+                CameraSet.before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(Update, toggle_editor.run_if(not(netplay::online)))
+            .add_systems(OnEnter(AppState::Restarting), finish_restart);
+
+        for state in [
+            AppState::Paused,
+            AppState::PhotoMode,
+            AppState::Map,
+            AppState::Editor,
+            AppState::Dead,
+            AppState::LevelComplete,
+        ] {
+            app.add_systems(OnEnter(state), pause_physics)
+                .add_systems(OnExit(state), resume_physics);
+        }
+    }
+}
+
+/// Top level state of the game.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum AppState {
+    /// Waiting for the assets before showing anything, see
+    /// [`LoadingPlugin`](crate::LoadingPlugin)
+    Loading,
+    #[default]
+    Menu,
+    LevelSelect,
+    Playing,
+    Paused,
+    /// Paused with the camera let loose for taking pictures
+    PhotoMode,
+    /// Paused on an overview of the whole level
+    Map,
+    /// Simulation stops and the geometry tools are available
+    Editor,
+    /// The player just died and tumbles limp for a moment, nothing but
+    /// physics runs
+    Dying,
+    /// The player died, waiting for them to retry
+    Dead,
+    /// The goal was reached, showing the results
+    LevelComplete,
+    /// Leaves the game for a single frame so the level gets reloaded
+    Restarting,
+}
+
+/// Any state where a level is loaded. Entities scoped to this state are
+/// despawned when going back to the menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = AppState;
+
+    fn compute(sources: AppState) -> Option<Self> {
+        match sources {
+            AppState::Loading | AppState::Menu | AppState::LevelSelect | AppState::Restarting => {
+                None
+            }
+            _ => Some(InGame),
+        }
+    }
+}
+
+/// Systems that run the game itself, only active while playing.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct GameplaySet;
+
+/// Turns raw input into resources like the cursor position, in `PreUpdate` so
+/// everything later in the frame (including the fixed ticks) sees this frame's
+/// input.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct InputSet;
+
+/// Reacts to the physics step that just ran, in `FixedPostUpdate`.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PostPhysicsSet;
+
+/// Moves the camera once everything it looks at has moved, at the end of
+/// `PostUpdate` right before transforms are propagated.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CameraSet;
+
+pub(crate) fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<AppState>>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    match state.get() {
+        AppState::Playing => next.set(AppState::Paused),
+        AppState::Paused => next.set(AppState::Playing),
+        AppState::PhotoMode => next.set(AppState::Paused),
+        _ => (),
+    }
+}
+
+fn toggle_editor(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<AppState>>,
+    mut next: ResMut<NextState<AppState>>,
+) {
+    if !keys.just_pressed(KeyCode::F1) {
+        return;
+    }
+
+    match state.get() {
+        AppState::Playing => next.set(AppState::Editor),
+        AppState::Editor => next.set(AppState::Playing),
+        _ => (),
+    }
+}
+
+fn finish_restart(mut next: ResMut<NextState<AppState>>) {
+    next.set(AppState::Playing);
+}
+
+fn pause_physics(mut time: ResMut<Time<Physics>>) {
+    time.pause();
+}
+
+fn resume_physics(mut time: ResMut<Time<Physics>>) {
+    time.unpause();
+}
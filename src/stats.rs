@@ -0,0 +1,170 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explosion::Exploded,
+    locale::Locale,
+    player::{Hooks, Jumped, Player, Remote},
+    replay::Replay,
+    run::{self, Run},
+    save::SaveData,
+};
+
+/// World units in a meter, for showing distances
+const UNITS_PER_METER: f32 = 100.;
+/// Moving further than this in one tick is a respawn or a portal, not travel
+const MAX_STEP: f32 = 200.;
+
+/// Counts of what the player has done, kept for the current run, the
+/// session and, in the save, for good.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Stats {
+    pub(crate) jumps: u32,
+    pub(crate) deaths: u32,
+    /// World units moved
+    pub(crate) distance: f32,
+    /// Seconds spent in the air on a rope
+    pub(crate) swinging: f32,
+    pub(crate) bombs: u32,
+}
+
+impl Stats {
+    /// Label ids and values, in the order they're listed.
+    pub(crate) fn rows(&self) -> [(&'static str, String); 5] {
+        [
+            ("stats-jumps", self.jumps.to_string()),
+            ("stats-deaths", self.deaths.to_string()),
+            (
+                "stats-distance",
+                format!("{:.0} m", self.distance / UNITS_PER_METER),
+            ),
+            ("stats-swinging", run::format_time(self.swinging)),
+            ("stats-bombs", self.bombs.to_string()),
+        ]
+    }
+}
+
+/// Everything since the game was started.
+#[derive(Resource, Default)]
+pub(crate) struct SessionStats(pub(crate) Stats);
+
+/// Apply `change` to the run, session and lifetime stats alike. A replay
+/// only counts towards its own run, it was played once already.
+fn record(
+    run: &mut Run,
+    session: &mut SessionStats,
+    save: &mut SaveData,
+    replay: &Replay,
+    change: impl Fn(&mut Stats),
+) {
+    change(&mut run.stats);
+    if replay.is_playing() {
+        return;
+    }
+    change(&mut session.0);
+    change(&mut save.stats);
+}
+
+pub(crate) fn track(
+    players: Query<(&Position, &Player, &Hooks), Without<Remote>>,
+    mut jumped: EventReader<Jumped>,
+    mut exploded: EventReader<Exploded>,
+    mut last: Local<Option<Vec2>>,
+    mut run: ResMut<Run>,
+    mut session: ResMut<SessionStats>,
+    mut save: ResMut<SaveData>,
+    replay: Res<Replay>,
+    time: Res<Time>,
+) {
+    let jumps = jumped.read().count() as u32;
+    // Only the player's bombs explode
+    let bombs = exploded.read().count() as u32;
+    let Some((position, player, hooks)) = players.iter().next() else {
+        *last = None;
+        return;
+    };
+
+    let step = last.map_or(0., |last| last.distance(position.0));
+    let distance = if step < MAX_STEP { step } else { 0. };
+    *last = Some(position.0);
+    let swinging = if !hooks.0.is_empty() && !player.is_grounded {
+        time.delta_seconds()
+    } else {
+        0.
+    };
+
+    if jumps == 0 && bombs == 0 && distance == 0. && swinging == 0. {
+        return;
+    }
+    record(&mut run, &mut session, &mut save, &replay, |stats| {
+        stats.jumps += jumps;
+        stats.bombs += bombs;
+        stats.distance += distance;
+        stats.swinging += swinging;
+    });
+}
+
+/// Counts a death and saves, since quitting from the death screen would
+/// otherwise lose the run's stats.
+pub(crate) fn count_death(
+    mut run: ResMut<Run>,
+    mut session: ResMut<SessionStats>,
+    mut save: ResMut<SaveData>,
+    replay: Res<Replay>,
+) {
+    record(&mut run, &mut session, &mut save, &replay, |stats| {
+        stats.deaths += 1
+    });
+    if !replay.is_playing() {
+        save.write();
+    }
+}
+
+/// Whether the stats window is showing, opened from the main menu.
+#[derive(Resource, Default)]
+pub(crate) struct StatsMenu {
+    pub(crate) open: bool,
+}
+
+/// The session and lifetime stats side by side.
+pub(crate) fn stats_window(
+    mut contexts: EguiContexts,
+    mut menu: ResMut<StatsMenu>,
+    session: Res<SessionStats>,
+    save: Res<SaveData>,
+    locale: Res<Locale>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new(locale.get("stats"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0., 0.])
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("stats").show(ui, |ui| {
+                ui.label("");
+                ui.label(egui::RichText::new(locale.get("stats-session")).strong());
+                ui.label(egui::RichText::new(locale.get("stats-lifetime")).strong());
+                ui.end_row();
+                for ((name, session), (_, lifetime)) in
+                    session.0.rows().into_iter().zip(save.stats.rows())
+                {
+                    ui.label(locale.get(name));
+                    ui.label(session);
+                    ui.label(lifetime);
+                    ui.end_row();
+                }
+            });
+        });
+
+    if !open {
+        menu.open = false;
+    }
+}
@@ -0,0 +1,51 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::world::SUBSTEPS;
+
+/// Range the substep count moves in. Slow scenes get by with `min`, the full
+/// `max` is for anything fast and for the hook rope, which stretches without
+/// them.
+#[derive(Resource, Clone)]
+pub(crate) struct Substeps {
+    pub(crate) min: u32,
+    pub(crate) max: u32,
+    /// Speed in pixels per second from which a body needs all of `max`
+    pub(crate) fast_speed: f32,
+}
+
+impl Default for Substeps {
+    fn default() -> Self {
+        Self {
+            min: 8,
+            max: SUBSTEPS,
+            fast_speed: 2000.,
+        }
+    }
+}
+
+/// Pick the count for the coming step from how fast things move now. Only
+/// depends on the state of the simulation, so replays take the same steps.
+pub(crate) fn adapt(
+    bounds: Res<Substeps>,
+    bodies: Query<(&RigidBody, &LinearVelocity), Without<Sleeping>>,
+    joints: Query<(), With<DistanceJoint>>,
+    mut count: ResMut<SubstepCount>,
+) {
+    let max = bounds.max.max(bounds.min);
+    let wanted = if joints.is_empty() {
+        let fastest = bodies
+            .iter()
+            .filter(|(body, _)| body.is_dynamic())
+            .map(|(_, velocity)| velocity.length())
+            .fold(0., f32::max);
+        let fraction = (fastest / bounds.fast_speed.max(1.)).min(1.);
+        bounds.min + ((max - bounds.min) as f32 * fraction).ceil() as u32
+    } else {
+        max
+    };
+
+    if count.0 != wanted {
+        count.0 = wanted;
+    }
+}
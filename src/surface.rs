@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// What a piece of level geometry is made of, used to pick footstep sounds
+/// and how slippery it gets in the rain.
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub(crate) enum SurfaceMaterial {
+    #[default]
+    Stone,
+    Metal,
+    Ice,
+}
+
+impl SurfaceMaterial {
+    /// How much of the usual friction is left at `wetness`, from 0 dry to 1
+    /// soaked. Dry, everything grips the same.
+    pub(crate) fn grip(self, wetness: f32) -> f32 {
+        let soaked = match self {
+            SurfaceMaterial::Stone => 0.6,
+            SurfaceMaterial::Metal => 0.3,
+            SurfaceMaterial::Ice => 0.1,
+        };
+        1. + (soaked - 1.) * wetness.clamp(0., 1.)
+    }
+}
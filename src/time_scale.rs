@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    player::{HookFired, HookRules},
+    settings::AssistSettings,
+};
+
+/// Speeds the `[` and `]` keys step through, `\` goes back to normal
+const STEPS: [f32; 7] = [0.1, 0.25, 0.5, 0.75, 1., 1.5, 2.];
+/// How fast the game runs during bullet time
+const BULLET_SPEED: f32 = 0.3;
+
+/// Slow motion and fast forward, for practising a level and for bullet time.
+///
+/// Scales the virtual clock, so fixed ticks and with them physics come less
+/// often while each one still covers the same time. Replays and rollback
+/// stay exact at any speed. Physics on its own can't be slowed that way, it
+/// would need the speed recorded with every tick.
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .add_systems(Update, (step, bullet_time, apply, indicator).chain());
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct TimeScale {
+    /// Picked with the keys
+    pub(crate) speed: f32,
+    /// Real seconds of bullet time left
+    bullet: f32,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            bullet: 0.,
+        }
+    }
+}
+
+impl TimeScale {
+    fn current(&self) -> f32 {
+        if self.bullet > 0. {
+            self.speed * BULLET_SPEED
+        } else {
+            self.speed
+        }
+    }
+}
+
+fn step(keys: Res<ButtonInput<KeyCode>>, mut scale: ResMut<TimeScale>) {
+    let index = STEPS
+        .iter()
+        .position(|&speed| speed >= scale.speed)
+        .unwrap_or(STEPS.len() - 1);
+
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        scale.speed = STEPS[index.saturating_sub(1)];
+    }
+    if keys.just_pressed(KeyCode::BracketRight) {
+        scale.speed = STEPS[(index + 1).min(STEPS.len() - 1)];
+    }
+    if keys.just_pressed(KeyCode::Backslash) {
+        scale.speed = 1.;
+    }
+}
+
+/// Slow down for a moment whenever the hook is fired, on levels that ask
+/// for it.
+fn bullet_time(
+    mut fired: EventReader<HookFired>,
+    mut scale: ResMut<TimeScale>,
+    rules: Res<HookRules>,
+    time: Res<Time<Real>>,
+) {
+    if fired.read().count() > 0 && rules.bullet_time > 0. {
+        scale.bullet = rules.bullet_time;
+    } else if scale.bullet > 0. {
+        scale.bullet = (scale.bullet - time.delta_seconds()).max(0.);
+    }
+}
+
+fn apply(scale: Res<TimeScale>, assist: Res<AssistSettings>, mut time: ResMut<Time<Virtual>>) {
+    let speed = scale.current() * assist.game_speed();
+    if time.relative_speed() != speed {
+        time.set_relative_speed(speed);
+    }
+}
+
+fn indicator(mut contexts: EguiContexts, scale: Res<TimeScale>) {
+    if scale.speed == 1. {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("time scale"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10., -10.])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(egui::RichText::new(format!("{}x", scale.speed)).strong());
+        });
+}
@@ -0,0 +1,94 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{difficulty::Difficulty, level, state::InGame, surface::SurfaceMaterial};
+
+/// A platform that blinks in and out on a fixed cycle, solid for `on`
+/// seconds then gone for `off`.
+#[derive(Component)]
+pub(crate) struct Timed {
+    on: f32,
+    off: f32,
+    /// Seconds into the cycle, starts at the level's offset so neighbouring
+    /// platforms can take turns
+    elapsed: f32,
+}
+
+impl Timed {
+    fn is_solid(&self) -> bool {
+        self.elapsed.rem_euclid(self.on + self.off) < self.on
+    }
+}
+
+/// The whole cycle is stretched by the [`Difficulty`], so platforms taking
+/// turns still line up.
+pub(crate) fn make_timed(
+    placed: &level::Timed,
+    difficulty: Difficulty,
+) -> (
+    SpriteBundle,
+    Timed,
+    RigidBody,
+    Collider,
+    CollisionLayers,
+    Friction,
+    SurfaceMaterial,
+    StateScoped<InGame>,
+) {
+    let scale = difficulty.hazard_time();
+    let timed = Timed {
+        on: placed.on * scale,
+        off: placed.off * scale,
+        elapsed: placed.offset * scale,
+    };
+    let solid = timed.is_solid();
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(190., 0.6, 0.5),
+                custom_size: Some(Vec2::new(placed.w, placed.h)),
+                ..default()
+            },
+            transform: Transform::from_xyz(placed.x, placed.y, 0.),
+            visibility: if solid {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            ..default()
+        },
+        timed,
+        RigidBody::Static,
+        Collider::rectangle(placed.w, placed.h),
+        if solid {
+            CollisionLayers::default()
+        } else {
+            CollisionLayers::NONE
+        },
+        Friction::new(1.),
+        SurfaceMaterial::Metal,
+        StateScoped(InGame),
+    )
+}
+
+/// Advances every cycle, switching the collider and sprite together.
+pub(crate) fn cycle(
+    mut platforms: Query<(&mut Timed, &mut CollisionLayers, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (mut platform, mut layers, mut visibility) in &mut platforms {
+        let was_solid = platform.is_solid();
+        platform.elapsed += time.delta_seconds();
+        if platform.is_solid() == was_solid {
+            continue;
+        }
+
+        if was_solid {
+            *layers = CollisionLayers::NONE;
+            *visibility = Visibility::Hidden;
+        } else {
+            *layers = CollisionLayers::default();
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::{input::touch::TouchInput, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    camera::MainCamera,
+    input::{Action, ActionSources, ActionState},
+    player::{Player, Remote},
+    state::InGame,
+};
+
+/// Touches starting left of this fraction of the screen width drive the stick
+const STICK_AREA: f32 = 0.4;
+/// How far a finger has to be dragged for full speed, in logical pixels
+const STICK_RADIUS: f32 = 80.;
+const JUMP_BUTTON_SIZE: f32 = 120.;
+const JUMP_BUTTON_MARGIN: f32 = 30.;
+
+/// On screen controls for the web build on phones and tablets.
+///
+/// Drag on the left of the screen to move, tap the button in the bottom right
+/// to jump and hold anywhere else to fire the hook at that spot.
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchControls>()
+            .add_systems(Startup, setup)
+            .add_systems(PreUpdate, detect_touch.before(ActionSources))
+            .add_systems(PreUpdate, read_touches.in_set(ActionSources))
+            .add_systems(Update, show_overlay);
+    }
+}
+
+/// Turned on the first time the screen is touched.
+#[derive(Resource, Default)]
+pub(crate) struct TouchControls {
+    pub(crate) enabled: bool,
+}
+
+#[derive(Component)]
+struct TouchOverlay;
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TouchOverlay,
+        ))
+        .with_children(|parent| {
+            // Stick area
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(STICK_AREA * 100.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                background_color: Color::srgba(1., 1., 1., 0.03).into(),
+                ..default()
+            });
+
+            parent.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(JUMP_BUTTON_MARGIN),
+                    bottom: Val::Px(JUMP_BUTTON_MARGIN),
+                    width: Val::Px(JUMP_BUTTON_SIZE),
+                    height: Val::Px(JUMP_BUTTON_SIZE),
+                    ..default()
+                },
+                background_color: Color::srgba(1., 1., 1., 0.15).into(),
+                border_radius: BorderRadius::MAX,
+                ..default()
+            });
+        });
+}
+
+fn detect_touch(mut touches: EventReader<TouchInput>, mut controls: ResMut<TouchControls>) {
+    if touches.read().next().is_some() && !controls.enabled {
+        info!("Touch input detected, enabling touch controls");
+        controls.enabled = true;
+    }
+}
+
+fn show_overlay(
+    controls: Res<TouchControls>,
+    in_game: Option<Res<State<InGame>>>,
+    mut overlay: Query<&mut Visibility, With<TouchOverlay>>,
+) {
+    let visible = controls.enabled && in_game.is_some();
+    for mut visibility in &mut overlay {
+        *visibility = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn on_jump_button(position: Vec2, window: &Window) -> bool {
+    let extent = JUMP_BUTTON_MARGIN + JUMP_BUTTON_SIZE;
+    position.x > window.width() - extent
+        && position.x < window.width() - JUMP_BUTTON_MARGIN
+        && position.y > window.height() - extent
+        && position.y < window.height() - JUMP_BUTTON_MARGIN
+}
+
+fn read_touches(
+    mut state: ResMut<ActionState>,
+    controls: Res<TouchControls>,
+    touches: Res<Touches>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    player: Query<&GlobalTransform, (With<Player>, Without<Remote>)>,
+) {
+    if !controls.enabled {
+        return;
+    }
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+
+    for touch in touches.iter() {
+        // Decide what a finger does by where it first went down
+        let start = touch.start_position();
+
+        if on_jump_button(start, window) {
+            state.hold(Action::Jump);
+        } else if start.x < window.width() * STICK_AREA {
+            let offset = touch.position().x - start.x;
+            state.set_stick(offset / STICK_RADIUS);
+        } else {
+            state.hold(Action::Hook);
+
+            let (Ok((camera, camera_transform)), Some(player)) =
+                (q_camera.get_single(), player.iter().next())
+            else {
+                continue;
+            };
+            if let Some(target) = camera
+                .viewport_to_world(camera_transform, touch.position())
+                .map(|ray| ray.origin.truncate())
+            {
+                state.set_aim(target - player.translation().truncate());
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use avian2d::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::{player::Player, state::InGame};
+
+/// Speed above which the player leaves a trail (dashes, big falls)
+const TRAIL_SPEED: f32 = 1200.0;
+/// Lower threshold while swinging from the hook
+const HOOK_TRAIL_SPEED: f32 = 700.0;
+/// How long a recorded position stays in the trail, in seconds
+const LIFETIME: f32 = 0.25;
+const WIDTH: f32 = 60.0;
+
+/// Recent positions of an entity, drawn as a fading ribbon.
+///
+/// Added to the player once they move fast enough and removed again once
+/// they slow down and the ribbon has faded out.
+#[derive(Component)]
+pub(crate) struct Trail {
+    points: VecDeque<(Vec2, f32)>,
+    ribbon: Entity,
+}
+
+fn is_fast(entity: Entity, velocity: &LinearVelocity, joints: &Query<&DistanceJoint>) -> bool {
+    let hooked = joints.iter().any(|joint| joint.entity1 == entity);
+    let threshold = if hooked {
+        HOOK_TRAIL_SPEED
+    } else {
+        TRAIL_SPEED
+    };
+
+    velocity.length() > threshold
+}
+
+pub(crate) fn start(
+    players: Query<(Entity, &Transform, &LinearVelocity), (With<Player>, Without<Trail>)>,
+    joints: Query<&DistanceJoint>,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    for (entity, transform, velocity) in &players {
+        if !is_fast(entity, velocity, &joints) {
+            continue;
+        }
+
+        let now = time.elapsed_seconds();
+        let first = (transform.translation.truncate(), now);
+        let points = VecDeque::from([first]);
+
+        let ribbon = commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    // Degenerate until there are enough points to draw
+                    mesh: Mesh2dHandle(
+                        meshes.add(ribbon_mesh(&VecDeque::from([first, first]), now)),
+                    ),
+                    material: materials.add(ColorMaterial::from_color(Color::WHITE)),
+                    // Behind the player and level
+                    transform: Transform::from_xyz(0., 0., -1.),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                StateScoped(InGame),
+            ))
+            .id();
+
+        commands.entity(entity).insert(Trail { points, ribbon });
+    }
+}
+
+pub(crate) fn record(
+    mut trails: Query<(Entity, &mut Trail, &Transform, &LinearVelocity)>,
+    joints: Query<&DistanceJoint>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let now = time.elapsed_seconds();
+
+    for (entity, mut trail, transform, velocity) in &mut trails {
+        while trail
+            .points
+            .front()
+            .is_some_and(|(_, t)| now - t > LIFETIME)
+        {
+            trail.points.pop_front();
+        }
+
+        if is_fast(entity, velocity, &joints) {
+            trail
+                .points
+                .push_back((transform.translation.truncate(), now));
+        } else if trail.points.is_empty() {
+            commands.entity(trail.ribbon).despawn();
+            commands.entity(entity).remove::<Trail>();
+        }
+    }
+}
+
+pub(crate) fn draw(
+    trails: Query<&Trail>,
+    mut ribbons: Query<(&Mesh2dHandle, &mut Visibility)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+
+    for trail in &trails {
+        let Ok((handle, mut visibility)) = ribbons.get_mut(trail.ribbon) else {
+            continue;
+        };
+
+        if trail.points.len() < 2 {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        if let Some(mesh) = meshes.get_mut(&handle.0) {
+            *mesh = ribbon_mesh(&trail.points, now);
+            *visibility = Visibility::Visible;
+        }
+    }
+}
+
+/// Build a triangle strip along `points` that thins out and fades with age.
+fn ribbon_mesh(points: &VecDeque<(Vec2, f32)>, now: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(points.len() * 2);
+    let mut colors = Vec::with_capacity(points.len() * 2);
+    let mut indices = Vec::with_capacity(points.len().saturating_sub(1) * 6);
+
+    for (i, &(point, t)) in points.iter().enumerate() {
+        let prev = points[i.saturating_sub(1)].0;
+        let next = points.get(i + 1).map_or(point, |(p, _)| *p);
+        let normal = (next - prev).perp().normalize_or_zero();
+
+        let fade = (1.0 - (now - t) / LIFETIME).clamp(0.0, 1.0);
+        let offset = normal * WIDTH / 2.0 * fade;
+
+        positions.push((point + offset).extend(0.0).to_array());
+        positions.push((point - offset).extend(0.0).to_array());
+
+        let color = LinearRgba::new(1.0, 1.0, 1.0, fade * 0.5).to_f32_array();
+        colors.push(color);
+        colors.push(color);
+
+        if i > 0 {
+            let base = (i as u32 - 1) * 2;
+            indices.extend([base, base + 1, base + 2, base + 1, base + 3, base + 2]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
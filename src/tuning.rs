@@ -0,0 +1,109 @@
+use avian2d::{math::Vector, prelude::*};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    console,
+    player::{HookRules, Movement},
+    substeps::Substeps,
+    world::GRAVITY,
+};
+
+/// Whether the tuning panel is showing, F2 toggles it.
+#[derive(Resource, Default)]
+pub(crate) struct TuningPanel {
+    open: bool,
+}
+
+pub(crate) fn toggle(keys: Res<ButtonInput<KeyCode>>, mut panel: ResMut<TuningPanel>) {
+    if keys.just_pressed(KeyCode::F2) {
+        panel.open = !panel.open;
+    }
+}
+
+/// Sliders for the physics and movement constants, applied as they're
+/// dragged. Nothing is saved, copy the values into the code once they feel
+/// right.
+pub(crate) fn panel(
+    mut contexts: EguiContexts,
+    mut panel: ResMut<TuningPanel>,
+    mut gravity: ResMut<Gravity>,
+    mut substeps: ResMut<Substeps>,
+    count: Res<SubstepCount>,
+    mut movement: ResMut<Movement>,
+    mut hook: ResMut<HookRules>,
+) {
+    if !panel.open {
+        return;
+    }
+
+    egui::Window::new("Tuning")
+        .open(&mut panel.open)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-10., 10.])
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("tuning").show(ui, |ui| {
+                ui.label("Gravity");
+                let mut down = -gravity.0.y;
+                if ui.add(egui::Slider::new(&mut down, 0.0..=3000.0)).changed() {
+                    gravity.0 = Vector::NEG_Y * down;
+                }
+                ui.end_row();
+
+                ui.label("Substeps");
+                ui.label(count.0.to_string());
+                ui.end_row();
+
+                ui.label("Fewest substeps");
+                ui.add(egui::Slider::new(&mut substeps.min, 1..=100));
+                ui.end_row();
+
+                ui.label("Most substeps");
+                ui.add(egui::Slider::new(&mut substeps.max, 1..=100));
+                ui.end_row();
+
+                let movement = movement.as_mut();
+                for (name, value, range) in [
+                    ("Acceleration", &mut movement.accel, 0.0..=500.0),
+                    ("Max speed", &mut movement.max_speed, 0.0..=3000.0),
+                    ("Jump impulse", &mut movement.jump_impulse, 0.0..=2000.0),
+                    ("Friction", &mut movement.friction, 0.0..=2.0),
+                    ("Fast speed", &mut substeps.fast_speed, 100.0..=5000.0),
+                    ("Hook length", &mut hook.range, 0.0..=10000.0),
+                ] {
+                    ui.label(name);
+                    ui.add(egui::Slider::new(value, range));
+                    ui.end_row();
+                }
+            });
+
+            if ui.button("Reset").clicked() {
+                gravity.0 = Vector::NEG_Y * GRAVITY;
+                *substeps = Substeps::default();
+                *movement = Movement::default();
+                // Levels can set their own, the next one loaded brings it back
+                hook.range = HookRules::default().range;
+            }
+        });
+}
+
+pub(crate) const SET_HELP: &str =
+    "set <gravity|min_substeps|max_substeps|accel|max_speed|jump|friction|hook_range> <value>  Change a constant";
+
+/// The console's way into the same values as the panel.
+pub(crate) fn set(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let name = args.first().copied().unwrap_or_default();
+    let value: f32 = console::arg(args, 1, "value")?;
+    match name {
+        "gravity" => world.resource_mut::<Gravity>().0 = Vector::Y * value,
+        "min_substeps" => world.resource_mut::<Substeps>().min = console::arg(args, 1, "value")?,
+        "max_substeps" => world.resource_mut::<Substeps>().max = console::arg(args, 1, "value")?,
+        "accel" => world.resource_mut::<Movement>().accel = value,
+        "max_speed" => world.resource_mut::<Movement>().max_speed = value,
+        "jump" => world.resource_mut::<Movement>().jump_impulse = value,
+        "friction" => world.resource_mut::<Movement>().friction = value,
+        "hook_range" => world.resource_mut::<HookRules>().range = value,
+        _ => return Err(format!("can't set {name:?}, see help")),
+    }
+    Ok(String::new())
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{Action, ActionState, Binding, InputDevice, KeyBindings},
+    level,
+    locale::Locale,
+    player::{Player, Remote},
+    state::{GameplaySet, InGame},
+};
+
+const FONT_SIZE: f32 = 28.;
+
+/// Contextual input prompts placed in levels, shown while the player is in
+/// their area until they do what the prompt asks.
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(InGame), setup)
+            .add_systems(Update, (dismiss, show).chain().in_set(GameplaySet));
+    }
+}
+
+/// An area that asks the player to do something while they're in it.
+#[derive(Component)]
+pub(crate) struct Tutorial {
+    area: Rect,
+    action: Action,
+    /// Say "Hold" instead of "Press"
+    hold: bool,
+    /// Locale id of what the action does, finishing "Press Space to ..."
+    text: String,
+}
+
+/// The prompt along the bottom of the screen, rebuilt whenever the tutorial
+/// or the device it's for changes.
+#[derive(Component)]
+struct Prompt;
+
+pub(crate) fn make_tutorial(
+    placed: &level::Tutorial,
+) -> (TransformBundle, Tutorial, StateScoped<InGame>) {
+    let center = Vec2::new(placed.x, placed.y);
+    (
+        TransformBundle::from_transform(Transform::from_translation(center.extend(0.))),
+        Tutorial {
+            area: Rect::from_center_size(center, Vec2::new(placed.w, placed.h)),
+            action: placed.action,
+            hold: placed.hold,
+            text: placed.text.clone(),
+        },
+        StateScoped(InGame),
+    )
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(80.),
+                width: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.),
+                ..default()
+            },
+            ..default()
+        },
+        Prompt,
+        StateScoped(InGame),
+    ));
+}
+
+fn text(value: &str) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: FONT_SIZE,
+            color: Color::WHITE,
+            ..default()
+        },
+    )
+}
+
+/// The tutorial the player is standing in, if any.
+fn current<'a>(
+    players: &Query<&Transform, (With<Player>, Without<Remote>)>,
+    tutorials: &'a Query<(Entity, &Tutorial)>,
+) -> Option<(Entity, &'a Tutorial)> {
+    let pos = players.iter().next()?.translation.truncate();
+    tutorials
+        .iter()
+        .find(|(_, tutorial)| tutorial.area.contains(pos))
+}
+
+/// Done with a tutorial once its action is performed inside it, it doesn't
+/// come back until the level is restarted.
+fn dismiss(
+    players: Query<&Transform, (With<Player>, Without<Remote>)>,
+    tutorials: Query<(Entity, &Tutorial)>,
+    actions: Res<ActionState>,
+    mut commands: Commands,
+) {
+    if let Some((entity, tutorial)) = current(&players, &tutorials) {
+        if actions.just_pressed_this_frame(tutorial.action) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn show(
+    players: Query<&Transform, (With<Player>, Without<Remote>)>,
+    tutorials: Query<(Entity, &Tutorial)>,
+    prompts: Query<(Entity, Ref<Prompt>)>,
+    bindings: Res<KeyBindings>,
+    device: Res<InputDevice>,
+    locale: Res<Locale>,
+    mut shown: Local<Option<(Entity, InputDevice)>>,
+    mut commands: Commands,
+) {
+    let Ok((prompt, added)) = prompts.get_single() else {
+        return;
+    };
+    let current = current(&players, &tutorials);
+    let wanted = current.map(|(entity, _)| (entity, *device));
+    if wanted == *shown && !added.is_added() && !locale.is_changed() {
+        return;
+    }
+    *shown = wanted;
+
+    let mut prompt = commands.entity(prompt);
+    prompt.despawn_descendants();
+    let Some((_, tutorial)) = current else {
+        return;
+    };
+
+    // Only the bindings on the device in use, unless it has none
+    let all: Vec<Binding> = bindings
+        .get(tutorial.action)
+        .iter()
+        .flatten()
+        .copied()
+        .collect();
+    let mut keys: Vec<Binding> = all.iter().copied().filter(|b| b.is_on(*device)).collect();
+    if keys.is_empty() {
+        keys = all;
+    }
+
+    prompt.with_children(|parent| {
+        parent.spawn(text(locale.get(if tutorial.hold {
+            "tutorial-hold"
+        } else {
+            "tutorial-press"
+        })));
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                parent.spawn(text(locale.get("tutorial-or")));
+            }
+            // Drawn as a key cap
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(8.), Val::Px(2.)),
+                        border: UiRect::all(Val::Px(2.)),
+                        ..default()
+                    },
+                    border_color: Color::WHITE.into(),
+                    border_radius: BorderRadius::all(Val::Px(6.)),
+                    background_color: Color::srgba(0., 0., 0., 0.5).into(),
+                    ..default()
+                })
+                .with_children(|cap| {
+                    cap.spawn(text(&key.prompt(&locale)));
+                });
+        }
+        let action = locale.get(&tutorial.text);
+        parent.spawn(text(&locale.format("tutorial-to", &[("action", &action)])));
+    });
+}
@@ -0,0 +1,239 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    atlas::AtlasSprite,
+    camera::MainCamera,
+    level::{Level, Levels},
+    player::Player,
+    state::InGame,
+};
+
+/// Seconds for one kind of weather to fade into another
+const TRANSITION: f32 = 3.;
+/// Screen area the particles fill around the camera, they wrap around its
+/// edges
+const AREA: Vec2 = Vec2::new(2560., 1600.);
+/// In front of the level, behind the map's fog
+const WEATHER_Z: f32 = 400.;
+const RAIN_DROPS: usize = 300;
+const SNOW_FLAKES: usize = 200;
+const FOG_BANKS: usize = 12;
+
+/// What the sky is doing over a level or part of one. Rain makes surfaces
+/// slippery, see [`SurfaceMaterial::grip`](crate::surface::SurfaceMaterial::grip).
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Deserialize)]
+pub(crate) enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+    Fog,
+}
+
+impl Weather {
+    const ALL: [Weather; 4] = [Weather::Clear, Weather::Rain, Weather::Snow, Weather::Fog];
+
+    /// Particles of this weather on screen at full strength, and their size,
+    /// color and speed in screen pixels.
+    fn particles(self) -> Option<(usize, Vec2, Color, Vec2)> {
+        match self {
+            Weather::Clear => None,
+            Weather::Rain => Some((
+                RAIN_DROPS,
+                Vec2::new(2., 28.),
+                Color::srgba(0.7, 0.8, 1., 0.5),
+                Vec2::new(-200., -1500.),
+            )),
+            Weather::Snow => Some((
+                SNOW_FLAKES,
+                Vec2::splat(6.),
+                Color::srgba(1., 1., 1., 0.8),
+                Vec2::new(0., -100.),
+            )),
+            Weather::Fog => Some((
+                FOG_BANKS,
+                Vec2::new(900., 500.),
+                Color::srgba(0.6, 0.6, 0.65, 0.25),
+                Vec2::new(30., 0.),
+            )),
+        }
+    }
+}
+
+/// How strongly each kind of weather shows, from 0 to 1. Eases over to the
+/// weather where the player is on fixed ticks, since the rain changes how
+/// the player moves.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct WeatherBlend {
+    strength: [f32; Weather::ALL.len()],
+}
+
+impl WeatherBlend {
+    fn get(&self, weather: Weather) -> f32 {
+        self.strength[weather as usize]
+    }
+
+    /// How wet surfaces are, from 0 dry to 1 soaked.
+    pub(crate) fn wetness(&self) -> f32 {
+        self.get(Weather::Rain)
+    }
+
+    /// All `weather` straight away.
+    fn only(weather: Weather) -> Self {
+        let mut blend = Self::default();
+        blend.strength[weather as usize] = 1.;
+        blend
+    }
+}
+
+/// One raindrop, snowflake or bank of fog, kept around the camera.
+#[derive(Component)]
+pub(crate) struct Particle {
+    weather: Weather,
+    /// From the middle of the screen, in screen pixels
+    offset: Vec2,
+    /// Sets the particle apart from the others, e.g. when snow sways
+    phase: f32,
+}
+
+/// The weather where the player is, the first zone they're in or else the
+/// level's own.
+fn weather_at(level: &Level, position: Vec2) -> Weather {
+    level
+        .weather_zones
+        .iter()
+        .find(|zone| {
+            Rect::from_center_size(
+                Vec2::new(zone.area.x, zone.area.y),
+                Vec2::new(zone.area.w, zone.area.h),
+            )
+            .contains(position)
+        })
+        .map_or(level.weather, |zone| zone.weather)
+}
+
+/// Starts the level in its own weather and spawns the particles of every
+/// kind it has anywhere.
+pub(crate) fn setup(mut commands: Commands, levels: Res<Levels>, assets: Res<Assets<Level>>) {
+    let Some(level) = levels.current(&assets) else {
+        return;
+    };
+    commands.insert_resource(WeatherBlend::only(level.weather));
+
+    for weather in Weather::ALL {
+        let used = level.weather == weather
+            || level
+                .weather_zones
+                .iter()
+                .any(|zone| zone.weather == weather);
+        let Some((count, size, color, _)) = weather.particles().filter(|_| used) else {
+            continue;
+        };
+        for i in 0..count {
+            // Evenly spread without needing the game's random numbers
+            let spread = Vec2::new(
+                (i as f32 * 0.754_877_7).fract(),
+                (i as f32 * 0.569_840_3).fract(),
+            );
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                AtlasSprite("particle"),
+                Particle {
+                    weather,
+                    offset: (spread - 0.5) * AREA,
+                    phase: i as f32,
+                },
+                StateScoped(InGame),
+            ));
+        }
+    }
+}
+
+/// Eases the weather towards the kind where the player is, or a mix of
+/// where each player is.
+pub(crate) fn blend(
+    mut blend: ResMut<WeatherBlend>,
+    players: Query<&Transform, With<Player>>,
+    levels: Res<Levels>,
+    assets: Res<Assets<Level>>,
+    time: Res<Time>,
+) {
+    let Some(level) = levels.current(&assets) else {
+        return;
+    };
+    let here = players
+        .iter()
+        .map(|player| weather_at(level, player.translation.truncate()))
+        .collect::<Vec<_>>();
+    if here.is_empty() {
+        return;
+    }
+
+    let step = time.delta_seconds() / TRANSITION;
+    for weather in Weather::ALL {
+        let target =
+            here.iter().filter(|&&kind| kind == weather).count() as f32 / here.len() as f32;
+        let strength = &mut blend.strength[weather as usize];
+        *strength += (target - *strength).clamp(-step, step);
+    }
+}
+
+/// Moves the particles along and keeps them around the camera, faded to how
+/// strong their weather is.
+pub(crate) fn drift(
+    mut particles: Query<(&mut Particle, &mut Transform, &mut Sprite, &mut Visibility)>,
+    cameras: Query<&Transform, (With<MainCamera>, Without<Particle>)>,
+    blend: Res<WeatherBlend>,
+    time: Res<Time>,
+) {
+    let Some(camera) = cameras.iter().next() else {
+        return;
+    };
+
+    let dt = time.delta_seconds();
+    for (mut particle, mut transform, mut sprite, mut visibility) in &mut particles {
+        let Some((_, _, color, velocity)) = particle.weather.particles() else {
+            continue;
+        };
+        let strength = blend.get(particle.weather);
+        if strength <= 0. {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+
+        let sway = match particle.weather {
+            Weather::Snow => Vec2::X * 40. * (time.elapsed_seconds() + particle.phase).sin(),
+            _ => Vec2::ZERO,
+        };
+        let offset = particle.offset + (velocity + sway) * dt;
+        particle.offset = (offset + AREA / 2.).rem_euclid(AREA) - AREA / 2.;
+
+        // Same size on screen however far the camera is zoomed out
+        let on_screen = camera.scale.truncate() * particle.offset;
+        transform.translation = camera.translation.truncate().extend(WEATHER_Z)
+            + camera.rotation * on_screen.extend(0.);
+        transform.scale = camera.scale;
+        // Streaks point the way the rain falls
+        let tilt = match particle.weather {
+            Weather::Rain => velocity.y.atan2(velocity.x) + FRAC_PI_2,
+            _ => 0.,
+        };
+        transform.rotation = camera.rotation * Quat::from_rotation_z(tilt);
+        let faded = color.with_alpha(color.alpha() * strength);
+        if sprite.color != faded {
+            sprite.color = faded;
+        }
+    }
+}
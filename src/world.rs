@@ -0,0 +1,190 @@
+use avian2d::{math::Vector, prelude::*};
+use bevy::prelude::*;
+
+use crate::{
+    abilities, anchor, checkpoint,
+    console::ConsoleExt,
+    crumble, cull,
+    daily::{self, Daily},
+    deterministic::{Deterministic, GameRng},
+    elevator, explosion, goal, gravity,
+    input::ActionFilters,
+    interpolation,
+    level::{self, Level, LevelIndex, Levels, RonLoader},
+    lighting,
+    mutators::{self, Mutators},
+    pendulum, pickup, plate, portal, run, save, score, sign,
+    state::{AppState, CameraSet, GameplaySet, InGame, PostPhysicsSet},
+    stats::{self, SessionStats},
+    substeps::{self, Substeps},
+    timed,
+    weather::{self, WeatherBlend},
+};
+
+/// Gameplay and physics both step at this rate
+pub(crate) const FIXED_HZ: f64 = 64.0;
+/// Most substeps a physics step takes, and how many it always takes in
+/// deterministic runs
+pub(crate) const SUBSTEPS: u32 = 50;
+/// Downwards, in pixels per second squared
+pub(crate) const GRAVITY: f32 = 1000.0;
+
+/// What a collider is, for bodies that have to ignore some others. Level
+/// geometry and anything else left on the default layers is in all of them.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Layer {
+    Player,
+    /// Links of a segmented rope
+    Rope,
+}
+
+// By hand, the derive checks features this crate doesn't have and warns
+impl PhysicsLayer for Layer {
+    fn to_bits(&self) -> u32 {
+        1 << *self as u32
+    }
+
+    fn all_bits() -> u32 {
+        Layer::Player.to_bits() | Layer::Rope.to_bits()
+    }
+}
+
+/// Loading and building levels and everything placed in them, plus the
+/// progress made through them.
+pub struct WorldPlugin;
+
+impl Plugin for WorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(FIXED_HZ))
+            // Physics runs in the fixed schedule, once per tick
+            .insert_resource(Time::new_with(Physics::fixed_once_hz(FIXED_HZ)))
+            .insert_resource(SubstepCount(SUBSTEPS))
+            .init_resource::<Substeps>()
+            .insert_resource(Gravity(Vector::NEG_Y * GRAVITY))
+            .init_asset::<Level>()
+            .init_asset::<LevelIndex>()
+            .init_asset_loader::<RonLoader<Level>>()
+            .init_asset_loader::<RonLoader<LevelIndex>>()
+            .init_resource::<Levels>()
+            .init_resource::<pickup::Coins>()
+            .init_resource::<anchor::AimAssist>()
+            .init_resource::<run::Run>()
+            .init_resource::<score::Score>()
+            .init_resource::<SessionStats>()
+            .init_resource::<Daily>()
+            .init_resource::<Mutators>()
+            .init_resource::<WeatherBlend>()
+            .init_resource::<GameRng>()
+            .init_resource::<cull::Bounds>()
+            .add_event::<explosion::Exploded>()
+            .add_event::<pickup::PickedUp>()
+            .add_event::<checkpoint::SplitRecorded>()
+            .add_event::<plate::Triggered>()
+            .add_event::<elevator::ElevatorStarted>()
+            .add_event::<pendulum::Swung>()
+            .add_console_command(
+                "load",
+                "load <level>  Restart into a level",
+                level::load_command,
+            )
+            .add_systems(Startup, (level::load_index, save::load))
+            .add_systems(Update, level::load_levels)
+            .add_systems(
+                OnEnter(InGame),
+                (
+                    level::setup,
+                    cull::bounds,
+                    mutators::apply.after(level::setup),
+                    weather::setup,
+                ),
+            )
+            .add_systems(OnExit(InGame), mutators::restore)
+            .add_systems(
+                PreUpdate,
+                mutators::mirror_input
+                    .in_set(ActionFilters)
+                    .run_if(in_state(InGame)),
+            )
+            .add_systems(OnEnter(AppState::Menu), (run::reset, daily::end))
+            .add_systems(OnEnter(AppState::LevelSelect), (run::reset, daily::end))
+            .add_systems(
+                OnEnter(AppState::LevelComplete),
+                (
+                    // Mutators make daily times incomparable to the usual ones
+                    save::record_completion.run_if(not(daily::is_active)),
+                    score::time_bonus,
+                    daily::finish.after(save::record_completion),
+                ),
+            )
+            .add_systems(Update, mutators::apply_to_player.in_set(GameplaySet))
+            .add_systems(FixedUpdate, weather::blend.in_set(GameplaySet))
+            .add_systems(Update, weather::drift.run_if(in_state(InGame)))
+            .add_systems(FixedUpdate, explosion::push_bodies.in_set(GameplaySet))
+            .add_systems(FixedUpdate, explosion::damage.in_set(GameplaySet))
+            .add_systems(FixedUpdate, pickup::collect.in_set(GameplaySet))
+            .add_systems(FixedUpdate, stats::track.in_set(GameplaySet))
+            .add_systems(OnEnter(AppState::Dead), stats::count_death)
+            .add_systems(FixedUpdate, pickup::collect_items.in_set(GameplaySet))
+            .add_systems(FixedUpdate, abilities::collect.in_set(GameplaySet))
+            .add_systems(FixedUpdate, checkpoint::activate.in_set(GameplaySet))
+            .add_systems(
+                FixedUpdate,
+                (crumble::step_on, crumble::crumble)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(Update, crumble::shake.in_set(GameplaySet))
+            .add_systems(Update, sign::show_nearby.in_set(GameplaySet))
+            .add_systems(FixedUpdate, timed::cycle.in_set(GameplaySet))
+            .add_systems(
+                FixedUpdate,
+                (pendulum::drive, pendulum::swing).in_set(GameplaySet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (elevator::listen, elevator::travel)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (portal::cool_down, portal::enter)
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    gravity::enter_zones,
+                    gravity::touch_orbs,
+                    gravity::enter_volumes,
+                    gravity::apply,
+                )
+                    .chain()
+                    .in_set(GameplaySet),
+            )
+            .add_systems(FixedUpdate, goal::reach.in_set(GameplaySet))
+            .add_systems(FixedUpdate, run::tick.in_set(GameplaySet))
+            .add_systems(FixedUpdate, cull::cull.in_set(GameplaySet))
+            .add_systems(
+                FixedUpdate,
+                (score::collect, score::decay).in_set(GameplaySet),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                substeps::adapt
+                    .before(PhysicsSet::Prepare)
+                    .run_if(not(resource_exists::<Deterministic>)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                interpolation::record.in_set(PostPhysicsSet),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                plate::press.in_set(PostPhysicsSet).in_set(GameplaySet),
+            )
+            .add_systems(PostUpdate, interpolation::offset_children.before(CameraSet))
+            .add_systems(Update, lighting::add_occluders);
+    }
+}
@@ -0,0 +1,115 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    player::{HookCast, Hooked, Hooks, Player},
+    state::InGame,
+};
+
+/// How far off the surface a corner is put, so the rope past it stays clear
+const MARGIN: f32 = 2.;
+/// Beyond this the rope lets go as it would without wrapping
+const MAX_CORNERS: usize = 16;
+
+/// A corner of the level the rope is bent around.
+#[derive(Clone, Copy, Reflect)]
+pub(crate) struct Corner {
+    /// Static body the rope is tied to there
+    pub(crate) body: Entity,
+    pub(crate) position: Vec2,
+    /// Which way the rope turns at the corner, it unwraps once that flips
+    side: f32,
+}
+
+/// Which side of the line from `from` through `corner` the player is on.
+fn side(from: Vec2, corner: Vec2, player: Vec2) -> f32 {
+    (corner - from).perp_dot(player - corner)
+}
+
+/// Bends a plain rope around whatever comes between the player and where it's
+/// tied, and straightens it out again once the player swings back. Only the
+/// part past the last corner swings, so each one shortens the radius.
+pub(crate) fn wrap(
+    mut players: Query<(Entity, &Position, &mut Hooks), With<Player>>,
+    points: Query<&Position, Without<Player>>,
+    mut ropes: Query<&mut DistanceJoint>,
+    cast: HookCast,
+    mut commands: Commands,
+) {
+    if !cast.rules.wrap_around_corners {
+        return;
+    }
+
+    for (player, position, mut hooks) in &mut players {
+        for hooked in hooks.0.iter_mut() {
+            if !hooked.segments.is_empty() {
+                continue;
+            }
+            let Ok(point) = points.get(hooked.point) else {
+                continue;
+            };
+            let Ok(mut rope) = ropes.get_mut(hooked.rope) else {
+                continue;
+            };
+            let before = |corners: &[Corner]| {
+                corners
+                    .len()
+                    .checked_sub(2)
+                    .map_or(point.0, |i| corners[i].position)
+            };
+
+            while let Some(&corner) = hooked.corners.last() {
+                let from = before(&hooked.corners);
+                if side(from, corner.position, position.0) * corner.side >= 0. {
+                    break;
+                }
+                unwrap(hooked, &mut rope, point.0, &mut commands);
+            }
+
+            let anchor = hooked
+                .corners
+                .last()
+                .map_or(point.0, |corner| corner.position);
+            if hooked.corners.len() < MAX_CORNERS {
+                if let Some((hit, normal)) =
+                    cast.obstruction(&[player, hooked.point], position.0, anchor)
+                {
+                    let corner = hit + normal * MARGIN;
+                    rope.rest_length = (rope.rest_length - anchor.distance(corner)).max(0.);
+                    let body = commands
+                        .spawn((RigidBody::Static, Position(corner), StateScoped(InGame)))
+                        .id();
+                    hooked.corners.push(Corner {
+                        body,
+                        position: corner,
+                        side: side(anchor, corner, position.0),
+                    });
+                    rope.entity2 = body;
+                }
+            }
+        }
+    }
+}
+
+/// Straightens the rope out past the corner nearest the player, giving back
+/// the length it took up. `point` is where the rope is tied.
+pub(crate) fn unwrap(
+    hooked: &mut Hooked,
+    rope: &mut DistanceJoint,
+    point: Vec2,
+    commands: &mut Commands,
+) {
+    let Some(corner) = hooked.corners.pop() else {
+        return;
+    };
+    let from = hooked
+        .corners
+        .last()
+        .map_or(point, |corner| corner.position);
+    rope.rest_length += from.distance(corner.position);
+    rope.entity2 = hooked
+        .corners
+        .last()
+        .map_or(hooked.point, |corner| corner.body);
+    commands.entity(corner.body).despawn();
+}
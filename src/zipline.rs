@@ -0,0 +1,209 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    gravity::GravityDirection,
+    input::Action,
+    level,
+    player::{Controls, HookReleased, Hooked, Hooks, Movement, Player, PLAYER_RADIUS},
+    pool::Pool,
+    rope::RopeSegment,
+    state::InGame,
+    world::GRAVITY,
+};
+
+const THICKNESS: f32 = 4.;
+/// How close the player has to get to the line to grab it
+const REACH: f32 = PLAYER_RADIUS + 10.;
+/// Speed the rope pulls the player up to a line it hooked
+const REEL_SPEED: f32 = 1200.;
+/// Speed lost per second to the slider rubbing on the line
+const DRAG: f32 = 150.;
+/// Seconds after letting go before the player can grab a line again
+const COOLDOWN: f32 = 0.3;
+
+/// A line strung between two points to slide down.
+#[derive(Component)]
+pub(crate) struct ZipLine {
+    a: Vec2,
+    b: Vec2,
+}
+
+impl ZipLine {
+    /// How far along the line the point closest to `point` is, 0 at `a` and
+    /// 1 at `b`.
+    fn along(&self, point: Vec2) -> f32 {
+        let line = self.b - self.a;
+        ((point - self.a).dot(line) / line.length_squared()).clamp(0., 1.)
+    }
+
+    fn at(&self, t: f32) -> Vec2 {
+        self.a.lerp(self.b, t)
+    }
+}
+
+/// On the player while hanging from a line.
+#[derive(Component)]
+pub(crate) struct Riding {
+    line: Entity,
+    t: f32,
+    /// Along the line towards `b`, negative towards `a`
+    speed: f32,
+}
+
+#[derive(Component)]
+pub(crate) struct ZipCooldown(Timer);
+
+pub(crate) fn make_zipline(
+    placed: &level::ZipLine,
+) -> (
+    SpriteBundle,
+    ZipLine,
+    RigidBody,
+    Collider,
+    CollisionLayers,
+    StateScoped<InGame>,
+) {
+    let line = placed.b - placed.a;
+    let center = (placed.a + placed.b) / 2.;
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::hsl(0., 0., 0.75),
+                custom_size: Some(Vec2::new(line.length(), THICKNESS)),
+                ..default()
+            },
+            transform: Transform::from_translation(center.extend(-0.6))
+                .with_rotation(Quat::from_rotation_z(line.to_angle())),
+            ..default()
+        },
+        ZipLine {
+            a: placed.a,
+            b: placed.b,
+        },
+        RigidBody::Static,
+        Collider::rectangle(line.length(), THICKNESS),
+        // Nothing bumps into it, but the hook's ray still finds it
+        CollisionLayers::ALL_MEMBERSHIPS,
+        StateScoped(InGame),
+    )
+}
+
+/// Pulls the player up to a line the hook caught, then swaps the rope for
+/// the slider once it's in reach.
+pub(crate) fn grab(
+    mut players: Query<
+        (Entity, &Position, &LinearVelocity, &Player, &mut Hooks),
+        (Without<Riding>, Without<ZipCooldown>),
+    >,
+    lines: Query<(Entity, &ZipLine)>,
+    points: Query<&Position, Without<Player>>,
+    mut ropes: Query<&mut DistanceJoint>,
+    time: Res<Time>,
+    mut released: EventWriter<HookReleased>,
+    mut links: ResMut<Pool<RopeSegment>>,
+    mut commands: Commands,
+) {
+    for (player, position, velocity, state, mut hooks) in &mut players {
+        let on_line = |hooked: &&Hooked| {
+            points.get(hooked.point).is_ok_and(|point| {
+                lines
+                    .iter()
+                    .any(|(_, line)| line.at(line.along(point.0)).distance(point.0) <= THICKNESS)
+            })
+        };
+        let mut hooked_line = false;
+        for hooked in hooks.0.iter().filter(on_line) {
+            hooked_line = true;
+            if let Ok(mut rope) = ropes.get_mut(hooked.rope) {
+                rope.rest_length = (rope.rest_length - REEL_SPEED * time.delta_seconds()).max(0.);
+            }
+        }
+
+        // Only from the air, walking under a low line shouldn't catch on it
+        if state.is_grounded && !hooked_line {
+            continue;
+        }
+        let Some((line, zip, t)) = lines
+            .iter()
+            .map(|(line, zip)| (line, zip, zip.along(position.0)))
+            .find(|(_, zip, t)| zip.at(*t).distance(position.0) <= REACH)
+        else {
+            continue;
+        };
+
+        if !hooks.0.is_empty() {
+            hooks.cut_all(&mut commands, &mut links, &mut released);
+        }
+        let dir = (zip.b - zip.a).normalize_or_zero();
+        commands.entity(player).insert(Riding {
+            line,
+            t,
+            speed: velocity.dot(dir),
+        });
+    }
+}
+
+/// Slides riders along their line under gravity, letting go at the ends or
+/// with a jump, which keeps the speed they had on the line.
+pub(crate) fn ride(
+    mut players: Query<(
+        Entity,
+        &mut Riding,
+        &mut Position,
+        &mut LinearVelocity,
+        &GravityDirection,
+        &Controls,
+    )>,
+    lines: Query<&ZipLine>,
+    tuning: Res<Movement>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let delta = time.delta_seconds();
+    for (player, mut riding, mut position, mut velocity, gravity, controls) in &mut players {
+        let Ok(zip) = lines.get(riding.line) else {
+            commands.entity(player).remove::<Riding>();
+            continue;
+        };
+
+        let line = zip.b - zip.a;
+        let dir = line.normalize_or_zero();
+        let up = gravity.up();
+
+        let pull = (-up * GRAVITY).dot(dir);
+        let drag = (DRAG * delta).min(riding.speed.abs()) * riding.speed.signum();
+        riding.speed += pull * delta - drag;
+        riding.t += riding.speed * delta / line.length().max(1.);
+
+        let off_end = !(0. ..=1.).contains(&riding.t);
+        let jumped = controls.actions.just_pressed(Action::Jump);
+        riding.t = riding.t.clamp(0., 1.);
+
+        // Hangs under the line, or over it with gravity flipped
+        position.0 = zip.at(riding.t) - up * PLAYER_RADIUS;
+        velocity.0 = dir * riding.speed;
+
+        if off_end || jumped {
+            if jumped {
+                velocity.0 += up * tuning.jump_impulse;
+            }
+            commands
+                .entity(player)
+                .remove::<Riding>()
+                .insert(ZipCooldown(Timer::from_seconds(COOLDOWN, TimerMode::Once)));
+        }
+    }
+}
+
+pub(crate) fn cool_down(
+    mut players: Query<(Entity, &mut ZipCooldown)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut cooldown) in &mut players {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<ZipCooldown>();
+        }
+    }
+}